@@ -0,0 +1,189 @@
+//! Ethereum keypair and brain-wallet account derivation
+//!
+//! Query subset seeds were previously derived from a plain counter
+//! (`seed[..8] = i.to_le_bytes()`), which a user cannot reproduce on another
+//! device or bind to an identity. This module derives a secp256k1 keypair -
+//! either randomly or deterministically from a passphrase ("brain wallet") -
+//! and uses it to seed a per-user PRF, so a user's PIR query stream becomes
+//! deterministic and recoverable from a phrase.
+
+use k256::ecdsa::signature::hazmat::PrehashVerifier;
+use k256::ecdsa::{RecoveryId, Signature, SigningKey, VerifyingKey};
+use k256::elliptic_curve::sec1::ToEncodedPoint;
+use tiny_keccak::{Hasher, Keccak};
+
+/// 20-byte Ethereum address
+pub type Address = [u8; 20];
+
+/// An Ethereum account: a secp256k1 keypair and its derived address.
+pub struct Account {
+    signing_key: SigningKey,
+    pub address: Address,
+}
+
+impl Account {
+    /// Generate a new random keypair.
+    pub fn generate() -> Self {
+        let signing_key = SigningKey::random(&mut rand::thread_rng());
+        Self::from_signing_key(signing_key)
+    }
+
+    /// Derive a "brain wallet" account from a passphrase (and optional salt)
+    /// by iterated keccak hashing until the digest is a valid secp256k1
+    /// private key (non-zero and less than the curve order).
+    ///
+    /// The derivation is deterministic: the same passphrase and salt always
+    /// recover the same account, letting a user reproduce their account (and
+    /// hence their PIR query schedule) on another device from memory alone.
+    pub fn from_brain_wallet(passphrase: &str, salt: Option<&[u8]>) -> Self {
+        let mut digest = keccak256(&[passphrase.as_bytes(), salt.unwrap_or(&[])]);
+
+        let signing_key = loop {
+            match SigningKey::from_bytes((&digest).into()) {
+                Ok(key) => break key,
+                Err(_) => digest = keccak256(&[&digest]),
+            }
+        };
+
+        Self::from_signing_key(signing_key)
+    }
+
+    /// Restore an account from a raw 32-byte private key.
+    pub fn from_secret(secret: &[u8; 32]) -> Result<Self, k256::ecdsa::Error> {
+        let signing_key = SigningKey::from_bytes(secret.into())?;
+        Ok(Self::from_signing_key(signing_key))
+    }
+
+    fn from_signing_key(signing_key: SigningKey) -> Self {
+        let address = derive_address(signing_key.verifying_key());
+        Self {
+            signing_key,
+            address,
+        }
+    }
+
+    /// The raw 32-byte private key, used as the PRF secret.
+    fn secret_bytes(&self) -> [u8; 32] {
+        self.signing_key.to_bytes().into()
+    }
+
+    /// Derive a deterministic PIR query subset seed for `(block_number, query_index)`.
+    ///
+    /// `seed = keccak256(secret || block_number || query_index)`, making a
+    /// user's query stream deterministic and recoverable from their
+    /// passphrase, rather than from an unreproducible plain counter.
+    pub fn derive_query_seed(&self, block_number: u64, query_index: u64) -> [u8; 32] {
+        let secret = self.secret_bytes();
+        keccak256(&[
+            &secret,
+            &block_number.to_le_bytes(),
+            &query_index.to_le_bytes(),
+        ])
+    }
+
+    /// Sign a message, hashing it with keccak256 first (as Ethereum does).
+    pub fn sign(&self, message: &[u8]) -> (Signature, RecoveryId) {
+        let digest = keccak256(&[message]);
+        self.signing_key
+            .sign_prehash_recoverable(&digest)
+            .expect("signing over a 32-byte prehash cannot fail")
+    }
+
+    /// Verify that `signature` over `message` was produced by `address`.
+    pub fn verify(address: &Address, message: &[u8], signature: &Signature, recid: RecoveryId) -> bool {
+        let digest = keccak256(&[message]);
+
+        let Ok(recovered) = VerifyingKey::recover_from_prehash(&digest, signature, recid) else {
+            return false;
+        };
+
+        if recovered.verify_prehash(&digest, signature).is_err() {
+            return false;
+        }
+
+        &derive_address(&recovered) == address
+    }
+}
+
+/// Derive the 20-byte Ethereum address from a public key: the last 20 bytes
+/// of `keccak256(uncompressed_pubkey[1..])` (dropping the 0x04 SEC1 prefix).
+fn derive_address(verifying_key: &VerifyingKey) -> Address {
+    let encoded = verifying_key.to_encoded_point(false);
+    let hash = keccak256(&[&encoded.as_bytes()[1..]]);
+    let mut address = [0u8; 20];
+    address.copy_from_slice(&hash[12..]);
+    address
+}
+
+fn keccak256(chunks: &[&[u8]]) -> [u8; 32] {
+    let mut hasher = Keccak::v256();
+    for chunk in chunks {
+        hasher.update(chunk);
+    }
+    let mut out = [0u8; 32];
+    hasher.finalize(&mut out);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_brain_wallet_deterministic() {
+        let a1 = Account::from_brain_wallet("correct horse battery staple", None);
+        let a2 = Account::from_brain_wallet("correct horse battery staple", None);
+        assert_eq!(a1.address, a2.address);
+    }
+
+    #[test]
+    fn test_brain_wallet_salt_changes_address() {
+        let a1 = Account::from_brain_wallet("my phrase", None);
+        let a2 = Account::from_brain_wallet("my phrase", Some(b"salt"));
+        assert_ne!(a1.address, a2.address);
+    }
+
+    #[test]
+    fn test_derive_query_seed_deterministic() {
+        let account = Account::from_brain_wallet("seed phrase", None);
+        let seed1 = account.derive_query_seed(100, 0);
+        let seed2 = account.derive_query_seed(100, 0);
+        assert_eq!(seed1, seed2);
+    }
+
+    #[test]
+    fn test_derive_query_seed_varies_by_index() {
+        let account = Account::from_brain_wallet("seed phrase", None);
+        let seed1 = account.derive_query_seed(100, 0);
+        let seed2 = account.derive_query_seed(100, 1);
+        assert_ne!(seed1, seed2);
+    }
+
+    #[test]
+    fn test_sign_and_verify() {
+        let account = Account::from_brain_wallet("signing test", None);
+        let message = b"hello pir lane";
+
+        let (signature, recid) = account.sign(message);
+        assert!(Account::verify(&account.address, message, &signature, recid));
+    }
+
+    #[test]
+    fn test_verify_rejects_wrong_address() {
+        let account = Account::from_brain_wallet("signing test", None);
+        let other = Account::from_brain_wallet("a different account", None);
+        let message = b"hello pir lane";
+
+        let (signature, recid) = account.sign(message);
+        assert!(!Account::verify(&other.address, message, &signature, recid));
+    }
+
+    #[test]
+    fn test_from_secret_roundtrip() {
+        let account = Account::generate();
+        let secret = account.secret_bytes();
+
+        let restored = Account::from_secret(&secret).unwrap();
+        assert_eq!(account.address, restored.address);
+    }
+}
@@ -10,11 +10,14 @@ use crate::AppState;
 pub struct WalletTemplate {
     pub pir_server_url: String,
     pub network: String,
+    /// Hex-encoded ("0x"-prefixed) address of the wallet's restored account
+    pub address: String,
 }
 
 pub async fn handler(State(state): State<Arc<AppState>>) -> WalletTemplate {
     WalletTemplate {
         pir_server_url: state.pir_server_url.clone(),
         network: state.network.clone(),
+        address: format!("0x{}", hex::encode(state.account.address)),
     }
 }
@@ -1,3 +1,4 @@
+mod account;
 mod handlers;
 
 use axum::{routing::get, Router};
@@ -5,9 +6,12 @@ use std::sync::Arc;
 use tower_http::{compression::CompressionLayer, services::ServeDir, trace::TraceLayer};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
+use account::Account;
+
 pub struct AppState {
     pub pir_server_url: String,
     pub network: String,
+    pub account: Account,
 }
 
 #[tokio::main]
@@ -24,9 +28,17 @@ async fn main() {
         std::env::var("PIR_SERVER_URL").unwrap_or_else(|_| "http://localhost:3001".to_string());
     let network = std::env::var("NETWORK").unwrap_or_else(|_| "sepolia".to_string());
 
+    // Restore the wallet's account from a brain-wallet passphrase if one is
+    // configured, otherwise generate a fresh random keypair.
+    let account = match std::env::var("WALLET_BRAIN_PHRASE") {
+        Ok(phrase) => Account::from_brain_wallet(&phrase, None),
+        Err(_) => Account::generate(),
+    };
+
     let state = Arc::new(AppState {
         pir_server_url,
         network,
+        account,
     });
 
     let app = Router::new()
@@ -10,19 +10,112 @@
 //! 2. Pick smallest range covering your sync gap
 //! 3. Fetch that range via HTTP Range request
 //! 4. Call `apply_range_delta()` with the merged delta
+//!
+//! ## Merkle-Verified Deltas
+//!
+//! `apply_delta`/`apply_range_delta` trust whatever counts the index server
+//! sends. `apply_delta_verified()` instead checks the delta's updates
+//! against the seeder's published `manifest.merkle_root` before mutating
+//! any state: the updates are hashed into a small local tree, and `proof`
+//! (the sibling path from that tree's root up to the manifest root) is
+//! folded to recompute `expected_root`. A curious or compromised index
+//! server can't skew bucket ranges without also forging that proof.
 
 use inspire_core::bucket_index::{
-    compute_bucket_id, compute_cumulative,
+    build_fenwick, compute_bucket_id, fenwick_prefix_sum, fenwick_update,
     range_delta::{RangeDeltaHeader, RangeEntry, HEADER_SIZE, RANGE_ENTRY_SIZE},
     BucketDelta as CoreDelta, NUM_BUCKETS,
 };
+use tiny_keccak::{Hasher, Keccak};
 use wasm_bindgen::prelude::*;
 
+/// Domain-separation prefix for delta-update leaves, so a leaf hash can
+/// never collide with a hash computed elsewhere in the protocol over the
+/// same raw bytes.
+const DELTA_LEAF_PREFIX: &[u8] = b"inspire-bucket-delta-leaf";
+
+/// One step of a delta-batch inclusion proof: the sibling hash to combine
+/// with the current node, and which side it belongs on.
+const PROOF_STEP_SIZE: usize = 33; // 1 side-flag byte + 32-byte sibling
+
+fn keccak256(chunks: &[&[u8]]) -> [u8; 32] {
+    let mut hasher = Keccak::v256();
+    for chunk in chunks {
+        hasher.update(chunk);
+    }
+    let mut out = [0u8; 32];
+    hasher.finalize(&mut out);
+    out
+}
+
+/// Hash a single bucket-delta update into a Merkle leaf.
+fn delta_leaf_hash(bucket_id: u32, count: u16) -> [u8; 32] {
+    keccak256(&[
+        DELTA_LEAF_PREFIX,
+        &bucket_id.to_le_bytes(),
+        &count.to_le_bytes(),
+    ])
+}
+
+/// Fold `updates` (already sorted by bucket id) into the root of the small
+/// local tree described by the delta itself, duplicating the last node at
+/// any odd-width level.
+fn delta_updates_root(updates: &[(u32, u16)]) -> [u8; 32] {
+    let mut level: Vec<[u8; 32]> = if updates.is_empty() {
+        vec![[0u8; 32]]
+    } else {
+        updates
+            .iter()
+            .map(|&(bucket_id, count)| delta_leaf_hash(bucket_id, count))
+            .collect()
+    };
+
+    while level.len() > 1 {
+        let mut next = Vec::with_capacity(level.len().div_ceil(2));
+        for pair in level.chunks(2) {
+            let left = pair[0];
+            let right = *pair.get(1).unwrap_or(&pair[0]);
+            next.push(keccak256(&[&left, &right]));
+        }
+        level = next;
+    }
+    level[0]
+}
+
+/// Fold a flat `sibling_is_left(1) || sibling(32)` proof, continuing from
+/// `current` up toward the root it's claimed to belong to.
+fn fold_proof(mut current: [u8; 32], proof: &[u8]) -> Result<[u8; 32], JsValue> {
+    if proof.len() % PROOF_STEP_SIZE != 0 {
+        return Err(JsValue::from_str(&format!(
+            "proof length {} is not a multiple of {}",
+            proof.len(),
+            PROOF_STEP_SIZE
+        )));
+    }
+
+    for step in proof.chunks(PROOF_STEP_SIZE) {
+        let sibling_is_left = step[0] != 0;
+        let sibling: [u8; 32] = step[1..33].try_into().unwrap();
+        current = if sibling_is_left {
+            keccak256(&[&sibling, &current])
+        } else {
+            keccak256(&[&current, &sibling])
+        };
+    }
+    Ok(current)
+}
+
 /// Bucket index for sparse PIR lookups (WASM-compatible)
 #[wasm_bindgen]
 pub struct BucketIndex {
     counts: Vec<u16>,
-    cumulative: Vec<u64>,
+    /// Fenwick tree (binary indexed tree) over `counts`, giving O(log N)
+    /// bucket-start prefix sums and O(log N) point updates in `apply_delta`/
+    /// `apply_delta_verified` (see [`inspire_core::bucket_index::build_fenwick`]),
+    /// rather than an O(1)-read, O(N)-rebuild flat cumulative array - this
+    /// matters once per-block deltas are streaming in continuously over the
+    /// websocket.
+    fenwick: Vec<u64>,
 }
 
 #[wasm_bindgen]
@@ -44,15 +137,37 @@ impl BucketIndex {
             counts.push(u16::from_le_bytes([chunk[0], chunk[1]]));
         }
 
-        let cumulative = compute_cumulative(&counts);
+        let fenwick = build_fenwick(&counts);
+
+        Ok(BucketIndex { counts, fenwick })
+    }
+
+    /// Load bucket index from a gzip-compressed `/index/raw` response.
+    ///
+    /// Inflates the single gzip member, checks the decoded length against
+    /// both the gzip footer's ISIZE and the expected `NUM_BUCKETS * 2`, then
+    /// recomputes CRC32 over the inflated bytes and compares it against the
+    /// footer's CRC. Any mismatch is treated as corruption/tampering and
+    /// rejected rather than trusted, since this data feeds PIR lookups.
+    pub fn from_compressed_bytes(data: &[u8]) -> Result<BucketIndex, JsValue> {
+        let inflated = inflate_gzip_member(data).map_err(JsValue::from_str)?;
+
+        let expected_len = NUM_BUCKETS * 2;
+        if inflated.len() != expected_len {
+            return Err(JsValue::from_str(&format!(
+                "Invalid bucket index size: expected {}, got {}",
+                expected_len,
+                inflated.len()
+            )));
+        }
 
-        Ok(BucketIndex { counts, cumulative })
+        BucketIndex::from_bytes(&inflated)
     }
 
     /// Get total number of entries across all buckets
     #[wasm_bindgen(getter)]
     pub fn total_entries(&self) -> u64 {
-        self.cumulative[NUM_BUCKETS]
+        fenwick_prefix_sum(&self.fenwick, NUM_BUCKETS)
     }
 
     /// Look up the bucket range for a (address, slot) pair
@@ -70,7 +185,7 @@ impl BucketIndex {
         let sl: [u8; 32] = slot.try_into().unwrap();
 
         let bucket_id = compute_bucket_id(&addr, &sl);
-        let start = self.cumulative[bucket_id];
+        let start = fenwick_prefix_sum(&self.fenwick, bucket_id);
         let count = self.counts[bucket_id] as u64;
 
         Ok(vec![bucket_id as u64, start, count])
@@ -83,24 +198,78 @@ impl BucketIndex {
 
     /// Get start index for a specific bucket
     pub fn bucket_start(&self, bucket_id: usize) -> u64 {
-        self.cumulative.get(bucket_id).copied().unwrap_or(0)
+        if bucket_id > NUM_BUCKETS {
+            return 0;
+        }
+        fenwick_prefix_sum(&self.fenwick, bucket_id)
     }
 
     /// Apply a delta update (from websocket)
     ///
     /// Delta format: block_num:8 + count:4 + (bucket_id:4 + count:2)*
+    /// Each updated bucket is applied as a single signed point-update to the
+    /// Fenwick tree (O(log N)) instead of rebuilding the whole cumulative
+    /// array (O(NUM_BUCKETS)), which matters once per-block deltas are
+    /// streaming in continuously over the websocket.
     /// Returns the block number from the delta.
     pub fn apply_delta(&mut self, data: &[u8]) -> Result<u64, JsValue> {
         let delta = CoreDelta::from_bytes(data).map_err(|e| JsValue::from_str(&e.to_string()))?;
 
         for &(bucket_id, new_count) in &delta.updates {
             if bucket_id < NUM_BUCKETS {
+                let d = new_count as i64 - self.counts[bucket_id] as i64;
                 self.counts[bucket_id] = new_count;
+                fenwick_update(&mut self.fenwick, bucket_id, d);
             }
         }
 
-        // Recompute cumulative sums
-        self.cumulative = compute_cumulative(&self.counts);
+        Ok(delta.block_number)
+    }
+
+    /// Apply a delta update only after verifying its updates against a
+    /// trusted Merkle root.
+    ///
+    /// `data` is the same `BucketDelta` wire format as `apply_delta`.
+    /// `proof` is a flat `sibling_is_left(1) || sibling(32)` byte string:
+    /// the sibling path from the root of the small tree built over `data`'s
+    /// own (sorted) updates, up to `expected_root` (the seeder's published
+    /// `manifest.merkle_root`). The updates are only applied if the
+    /// recomputed root matches `expected_root` exactly.
+    pub fn apply_delta_verified(
+        &mut self,
+        data: &[u8],
+        proof: &[u8],
+        expected_root: &[u8],
+    ) -> Result<u64, JsValue> {
+        if expected_root.len() != 32 {
+            return Err(JsValue::from_str("expected_root must be 32 bytes"));
+        }
+
+        let delta = CoreDelta::from_bytes(data).map_err(|e| JsValue::from_str(&e.to_string()))?;
+
+        let mut sorted_updates: Vec<(u32, u16)> = delta
+            .updates
+            .iter()
+            .map(|&(bucket_id, count)| (bucket_id as u32, count))
+            .collect();
+        sorted_updates.sort_by_key(|&(bucket_id, _)| bucket_id);
+
+        let leaf_root = delta_updates_root(&sorted_updates);
+        let recomputed_root = fold_proof(leaf_root, proof)?;
+
+        if recomputed_root.as_slice() != expected_root {
+            return Err(JsValue::from_str(
+                "delta Merkle proof does not match expected root",
+            ));
+        }
+
+        for &(bucket_id, new_count) in &delta.updates {
+            if bucket_id < NUM_BUCKETS {
+                let d = new_count as i64 - self.counts[bucket_id] as i64;
+                self.counts[bucket_id] = new_count;
+                fenwick_update(&mut self.fenwick, bucket_id, d);
+            }
+        }
 
         Ok(delta.block_number)
     }
@@ -116,6 +285,102 @@ impl BucketIndex {
     }
 }
 
+const GZIP_MIN_HEADER: usize = 10;
+const GZIP_FOOTER_SIZE: usize = 8;
+
+const FLAG_FHCRC: u8 = 0x02;
+const FLAG_FEXTRA: u8 = 0x04;
+const FLAG_FNAME: u8 = 0x08;
+const FLAG_FCOMMENT: u8 = 0x10;
+
+/// Inflate a single gzip member, verifying the footer's ISIZE and CRC32
+/// against the decoded bytes. Returns the inflated data on success, or a
+/// human-readable error describing which check failed.
+fn inflate_gzip_member(data: &[u8]) -> Result<Vec<u8>, String> {
+    if data.len() < GZIP_MIN_HEADER + GZIP_FOOTER_SIZE {
+        return Err("gzip data too short".to_string());
+    }
+    if data[0] != 0x1f || data[1] != 0x8b {
+        return Err("not a gzip stream (bad magic)".to_string());
+    }
+    if data[2] != 8 {
+        return Err(format!("unsupported gzip compression method {}", data[2]));
+    }
+
+    let flags = data[3];
+    let mut pos = GZIP_MIN_HEADER;
+
+    if flags & FLAG_FEXTRA != 0 {
+        if pos + 2 > data.len() {
+            return Err("truncated gzip FEXTRA length".to_string());
+        }
+        let xlen = u16::from_le_bytes([data[pos], data[pos + 1]]) as usize;
+        pos += 2 + xlen;
+    }
+    if flags & FLAG_FNAME != 0 {
+        pos += data[pos..]
+            .iter()
+            .position(|&b| b == 0)
+            .ok_or("truncated gzip FNAME")?
+            + 1;
+    }
+    if flags & FLAG_FCOMMENT != 0 {
+        pos += data[pos..]
+            .iter()
+            .position(|&b| b == 0)
+            .ok_or("truncated gzip FCOMMENT")?
+            + 1;
+    }
+    if flags & FLAG_FHCRC != 0 {
+        pos += 2;
+    }
+
+    if pos + GZIP_FOOTER_SIZE > data.len() {
+        return Err("gzip header consumed past end of data".to_string());
+    }
+
+    let footer = &data[data.len() - GZIP_FOOTER_SIZE..];
+    let expected_crc = u32::from_le_bytes(footer[0..4].try_into().unwrap());
+    let expected_isize = u32::from_le_bytes(footer[4..8].try_into().unwrap());
+
+    let deflate_body = &data[pos..data.len() - GZIP_FOOTER_SIZE];
+    let inflated = miniz_oxide::inflate::decompress_to_vec(deflate_body)
+        .map_err(|e| format!("gzip inflate failed: {e:?}"))?;
+
+    if (inflated.len() as u32) != expected_isize {
+        return Err(format!(
+            "gzip ISIZE mismatch: footer says {}, inflated to {}",
+            expected_isize,
+            inflated.len()
+        ));
+    }
+
+    let actual_crc = crc32(&inflated);
+    if actual_crc != expected_crc {
+        return Err(format!(
+            "gzip CRC32 mismatch: footer says {expected_crc:#010x}, computed {actual_crc:#010x}"
+        ));
+    }
+
+    Ok(inflated)
+}
+
+/// CRC-32 (reflected polynomial 0xEDB88320, init 0xFFFFFFFF, final XOR
+/// 0xFFFFFFFF) - the variant gzip uses for its footer checksum.
+fn crc32(data: &[u8]) -> u32 {
+    const POLY: u32 = 0xEDB88320;
+
+    let mut crc = 0xFFFFFFFFu32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (POLY & mask);
+        }
+    }
+    !crc
+}
+
 /// Range delta file info (returned by /index/deltas/info)
 #[wasm_bindgen]
 pub struct RangeDeltaInfo {
@@ -210,6 +475,133 @@ impl RangeDeltaInfo {
             .map(|r| r.blocks_covered)
             .unwrap_or(0)
     }
+
+    /// Plan which ranges to fetch and apply (in order) to close a sync gap
+    /// of `behind_blocks`, instead of giving up the moment no single range
+    /// spans the whole gap.
+    ///
+    /// Prefers the fewest requests: if one range alone covers the gap (the
+    /// smallest such range, to minimize bytes), the plan is just that one
+    /// range - same choice `select_range` would make. Otherwise it stitches
+    /// ranges together largest-first until their combined `blocks_covered`
+    /// reaches the gap. If every available range combined still doesn't
+    /// cover it, `RangePlan::covers_gap` is `false` and the caller should
+    /// fall back to a full `/index/raw` download instead.
+    pub fn plan_ranges(&self, behind_blocks: u64) -> RangePlan {
+        if behind_blocks == 0 {
+            return RangePlan {
+                range_indices: Vec::new(),
+                total_bytes: 0,
+                covers_gap: true,
+            };
+        }
+
+        // Fast path: the smallest single range that spans the whole gap
+        // costs one request and the fewest bytes of any covering range.
+        if let Some(i) = (0..self.ranges.len())
+            .filter(|&i| self.ranges[i].blocks_covered as u64 >= behind_blocks)
+            .min_by_key(|&i| self.ranges[i].blocks_covered)
+        {
+            return RangePlan {
+                range_indices: vec![i as u32],
+                total_bytes: self.ranges[i].size as u64,
+                covers_gap: true,
+            };
+        }
+
+        // No single range reaches far enough back - stitch the largest
+        // ranges together until they do, minimizing the request count.
+        let mut order: Vec<usize> = (0..self.ranges.len()).collect();
+        order.sort_by_key(|&i| std::cmp::Reverse(self.ranges[i].blocks_covered));
+
+        let mut covered = 0u64;
+        let mut range_indices = Vec::new();
+        let mut total_bytes = 0u64;
+        for i in order {
+            if covered >= behind_blocks {
+                break;
+            }
+            covered += self.ranges[i].blocks_covered as u64;
+            total_bytes += self.ranges[i].size as u64;
+            range_indices.push(i as u32);
+        }
+
+        RangePlan {
+            range_indices,
+            total_bytes,
+            covers_gap: covered >= behind_blocks,
+        }
+    }
+}
+
+/// A plan for closing a sync gap: which ranges to fetch and apply in order,
+/// their combined byte cost, and whether they actually close the gap.
+#[wasm_bindgen]
+pub struct RangePlan {
+    range_indices: Vec<u32>,
+    total_bytes: u64,
+    covers_gap: bool,
+}
+
+#[wasm_bindgen]
+impl RangePlan {
+    /// Range indices to fetch and apply, in order (oldest coverage first).
+    #[wasm_bindgen(getter)]
+    pub fn range_indices(&self) -> Vec<u32> {
+        self.range_indices.clone()
+    }
+
+    /// Total bytes that would be downloaded to execute this plan.
+    #[wasm_bindgen(getter)]
+    pub fn total_bytes(&self) -> u64 {
+        self.total_bytes
+    }
+
+    /// Whether the chosen ranges actually close the gap. `false` means no
+    /// combination of available ranges reaches far enough back, and the
+    /// caller should fetch a fresh full index instead.
+    #[wasm_bindgen(getter)]
+    pub fn covers_gap(&self) -> bool {
+        self.covers_gap
+    }
+}
+
+/// Reassembles a [`BucketDelta`] wire frame delivered as one or more
+/// chunked WebSocket binary messages (see `BucketDelta::to_chunks` in
+/// inspire-core). `transport::HttpClient::subscribe` hands the caller each
+/// frame's raw bytes as they arrive; feed them through `push` here and pass
+/// the result to `BucketIndex::apply_delta`/`apply_delta_verified` once a
+/// chunk completes a delta.
+#[wasm_bindgen]
+pub struct DeltaChunkReassembler {
+    inner: inspire_core::bucket_index::ChunkReassembler,
+}
+
+#[wasm_bindgen]
+impl DeltaChunkReassembler {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        Self {
+            inner: inspire_core::bucket_index::ChunkReassembler::new(),
+        }
+    }
+
+    /// Feed one chunk's raw bytes in. Returns `undefined` (via `None`)
+    /// while more chunks are still expected, or the reassembled delta's
+    /// bytes (ready for `apply_delta`) once the terminating chunk arrives.
+    pub fn push(&mut self, frame: &[u8]) -> Result<Option<Vec<u8>>, JsValue> {
+        let delta = self
+            .inner
+            .push(frame)
+            .map_err(|e| JsValue::from_str(&e.to_string()))?;
+        Ok(delta.map(|d| d.to_bytes()))
+    }
+}
+
+impl Default for DeltaChunkReassembler {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 #[cfg(test)]
@@ -219,6 +611,103 @@ mod tests {
 
     wasm_bindgen_test_configure!(run_in_browser);
 
+    fn make_range_delta_info(ranges: &[(u32, u32, u32)]) -> RangeDeltaInfo {
+        let header = RangeDeltaHeader {
+            version: 1,
+            current_block: 1_000_000,
+            num_ranges: ranges.len() as u32,
+        };
+        let mut data = header.to_bytes().to_vec();
+        for &(blocks_covered, offset, size) in ranges {
+            data.extend_from_slice(
+                &RangeEntry {
+                    blocks_covered,
+                    offset,
+                    size,
+                    entry_count: 0,
+                }
+                .to_bytes(),
+            );
+        }
+        RangeDeltaInfo::from_bytes(&data).unwrap()
+    }
+
+    #[wasm_bindgen_test]
+    fn test_plan_ranges_no_gap_is_empty() {
+        let info = make_range_delta_info(&[(1, 0, 100), (10, 100, 500)]);
+        let plan = info.plan_ranges(0);
+        assert!(plan.range_indices().is_empty());
+        assert_eq!(plan.total_bytes(), 0);
+        assert!(plan.covers_gap());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_plan_ranges_prefers_single_smallest_covering_range() {
+        let info = make_range_delta_info(&[(1, 0, 100), (10, 100, 500), (100, 600, 5000)]);
+        let plan = info.plan_ranges(5);
+        assert_eq!(plan.range_indices(), vec![1]);
+        assert_eq!(plan.total_bytes(), 500);
+        assert!(plan.covers_gap());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_plan_ranges_stitches_when_no_single_range_covers_gap() {
+        let info = make_range_delta_info(&[(1, 0, 10), (10, 10, 50), (100, 60, 500)]);
+        // Largest available range (100) doesn't cover a gap of 150, but
+        // 100 + 10 + 1 = 111 still doesn't reach 150 either - every range
+        // should end up in the plan and it still falls short.
+        let plan = info.plan_ranges(150);
+        assert_eq!(plan.range_indices().len(), 3);
+        assert!(!plan.covers_gap());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_plan_ranges_stitches_to_exactly_cover_gap() {
+        let info = make_range_delta_info(&[(1, 0, 10), (10, 10, 50), (100, 60, 500)]);
+        // 100 + 10 = 110 >= a gap of 105, and no single range alone covers it.
+        let plan = info.plan_ranges(105);
+        assert_eq!(plan.range_indices(), vec![2, 1]);
+        assert_eq!(plan.total_bytes(), 550);
+        assert!(plan.covers_gap());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_delta_chunk_reassembler_reassembles_split_frames() {
+        let delta = CoreDelta {
+            block_number: 42,
+            block_hash: [42u8; 32],
+            parent_hash: [41u8; 32],
+            updates: (0..500).map(|i| (i, 1)).collect(),
+        };
+        let chunks = delta.to_chunks(32);
+        assert!(chunks.len() > 1);
+
+        let mut reassembler = DeltaChunkReassembler::new();
+        let mut result = None;
+        for chunk in &chunks {
+            result = reassembler.push(chunk).unwrap();
+        }
+        let bytes = result.expect("terminating chunk should yield delta bytes");
+        let recovered = CoreDelta::from_bytes(&bytes).unwrap();
+        assert_eq!(recovered.block_number, 42);
+        assert_eq!(recovered.updates, delta.updates);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_delta_chunk_reassembler_none_before_final_chunk() {
+        let delta = CoreDelta {
+            block_number: 1,
+            block_hash: [0u8; 32],
+            parent_hash: [0u8; 32],
+            updates: (0..200).map(|i| (i, 1)).collect(),
+        };
+        let chunks = delta.to_chunks(32);
+        assert!(chunks.len() > 1);
+
+        let mut reassembler = DeltaChunkReassembler::new();
+        assert!(reassembler.push(&chunks[0]).unwrap().is_none());
+    }
+
     #[wasm_bindgen_test]
     fn test_bucket_id_deterministic() {
         let address = [0x42u8; 20];
@@ -245,6 +734,52 @@ mod tests {
         assert_eq!(index.bucket_start(2), 15);
     }
 
+    /// Wrap `payload` in a minimal single-member gzip stream (no extra
+    /// fields, no name/comment) for exercising `from_compressed_bytes`.
+    fn gzip_wrap(payload: &[u8]) -> Vec<u8> {
+        let mut out = vec![0x1f, 0x8b, 8, 0, 0, 0, 0, 0, 0, 0xff];
+        out.extend_from_slice(&miniz_oxide::deflate::compress_to_vec(payload, 6));
+        out.extend_from_slice(&crc32(payload).to_le_bytes());
+        out.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+        out
+    }
+
+    #[wasm_bindgen_test]
+    fn test_bucket_index_from_compressed_bytes() {
+        let mut data = vec![0u8; NUM_BUCKETS * 2];
+        data[0] = 10;
+        data[2] = 5;
+
+        let gzipped = gzip_wrap(&data);
+        let index = BucketIndex::from_compressed_bytes(&gzipped).unwrap();
+
+        assert_eq!(index.bucket_count(0), 10);
+        assert_eq!(index.bucket_count(1), 5);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_from_compressed_bytes_rejects_crc_mismatch() {
+        let data = vec![0u8; NUM_BUCKETS * 2];
+        let mut gzipped = gzip_wrap(&data);
+
+        let crc_offset = gzipped.len() - 8;
+        gzipped[crc_offset] ^= 0xff;
+
+        assert!(BucketIndex::from_compressed_bytes(&gzipped).is_err());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_from_compressed_bytes_rejects_bad_magic() {
+        let junk = vec![0u8; 32];
+        assert!(BucketIndex::from_compressed_bytes(&junk).is_err());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_crc32_known_vector() {
+        // "123456789" is the standard CRC-32/ISO-HDLC (gzip) test vector.
+        assert_eq!(crc32(b"123456789"), 0xCBF43926);
+    }
+
     #[wasm_bindgen_test]
     fn test_apply_delta() {
         let mut data = vec![0u8; NUM_BUCKETS * 2];
@@ -256,6 +791,8 @@ mod tests {
         // Create delta bytes
         let delta = CoreDelta {
             block_number: 42,
+            block_hash: [0u8; 32],
+            parent_hash: [0u8; 32],
             updates: vec![(0, 15)],
         };
         let delta_bytes = delta.to_bytes();
@@ -264,4 +801,100 @@ mod tests {
         assert_eq!(block, 42);
         assert_eq!(index.bucket_count(0), 15);
     }
+
+    #[wasm_bindgen_test]
+    fn test_apply_delta_shifts_downstream_bucket_starts() {
+        let mut data = vec![0u8; NUM_BUCKETS * 2];
+        data[0] = 10; // bucket 0 = 10
+        data[2] = 5; // bucket 1 = 5
+
+        let mut index = BucketIndex::from_bytes(&data).unwrap();
+        assert_eq!(index.bucket_start(1), 10);
+        assert_eq!(index.bucket_start(2), 15);
+        assert_eq!(index.total_entries(), 15);
+
+        // Growing bucket 0 should shift every later bucket's start, same as
+        // a full cumulative-array rebuild would.
+        let delta = CoreDelta {
+            block_number: 1,
+            block_hash: [0u8; 32],
+            parent_hash: [0u8; 32],
+            updates: vec![(0, 20)],
+        };
+        index.apply_delta(&delta.to_bytes()).unwrap();
+
+        assert_eq!(index.bucket_start(1), 20);
+        assert_eq!(index.bucket_start(2), 25);
+        assert_eq!(index.total_entries(), 25);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_apply_delta_verified_accepts_matching_root() {
+        let data = vec![0u8; NUM_BUCKETS * 2];
+        let mut index = BucketIndex::from_bytes(&data).unwrap();
+
+        let delta = CoreDelta {
+            block_number: 7,
+            block_hash: [0u8; 32],
+            parent_hash: [0u8; 32],
+            updates: vec![(3, 11), (1, 22)],
+        };
+        let delta_bytes = delta.to_bytes();
+
+        let sorted = vec![(1u32, 22u16), (3u32, 11u16)];
+        let root = delta_updates_root(&sorted);
+
+        let block = index
+            .apply_delta_verified(&delta_bytes, &[], &root)
+            .unwrap();
+        assert_eq!(block, 7);
+        assert_eq!(index.bucket_count(1), 22);
+        assert_eq!(index.bucket_count(3), 11);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_apply_delta_verified_folds_proof_above_local_root() {
+        let data = vec![0u8; NUM_BUCKETS * 2];
+        let mut index = BucketIndex::from_bytes(&data).unwrap();
+
+        let delta = CoreDelta {
+            block_number: 9,
+            block_hash: [0u8; 32],
+            parent_hash: [0u8; 32],
+            updates: vec![(5, 100)],
+        };
+        let delta_bytes = delta.to_bytes();
+
+        let local_root = delta_updates_root(&[(5u32, 100u16)]);
+        let sibling = [0x11u8; 32];
+        let mut proof = vec![0u8]; // sibling_is_left = false
+        proof.extend_from_slice(&sibling);
+        let expected_root = keccak256(&[&local_root, &sibling]);
+
+        let block = index
+            .apply_delta_verified(&delta_bytes, &proof, &expected_root)
+            .unwrap();
+        assert_eq!(block, 9);
+        assert_eq!(index.bucket_count(5), 100);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_apply_delta_verified_rejects_wrong_root() {
+        let data = vec![0u8; NUM_BUCKETS * 2];
+        let mut index = BucketIndex::from_bytes(&data).unwrap();
+
+        let delta = CoreDelta {
+            block_number: 1,
+            block_hash: [0u8; 32],
+            parent_hash: [0u8; 32],
+            updates: vec![(0, 5)],
+        };
+        let delta_bytes = delta.to_bytes();
+
+        let wrong_root = [0xffu8; 32];
+        let result = index.apply_delta_verified(&delta_bytes, &[], &wrong_root);
+        assert!(result.is_err());
+        // State must be untouched when verification fails.
+        assert_eq!(index.bucket_count(0), 0);
+    }
 }
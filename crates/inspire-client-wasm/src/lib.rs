@@ -8,6 +8,7 @@ mod client;
 mod error;
 mod security;
 mod slots;
+mod storage_layout;
 mod transport;
 mod ubt_index;
 
@@ -17,6 +18,7 @@ pub use error::PirError;
 pub use slots::{
     compute_balance_slot, compute_balance_slot_hex, mainnet_usdc, sepolia_usdc, TokenInfo,
 };
+pub use storage_layout::{SlotKey, SlotLocation, StorageLayout};
 pub use ubt_index::{compute_stem_js, compute_tree_key_js, get_subindex_js, StemIndex};
 
 use wasm_bindgen::prelude::*;
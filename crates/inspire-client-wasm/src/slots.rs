@@ -7,10 +7,15 @@
 //! Where:
 //! - key is left-padded to 32 bytes
 //! - mappingSlot is the slot number of the mapping variable, left-padded to 32 bytes
+//!
+//! This is the single-level mapping case of the more general rules in
+//! `storage_layout` (nested mappings, arrays, packed struct fields);
+//! `compute_balance_slot` is a thin wrapper over it.
 
-use tiny_keccak::{Hasher, Keccak};
 use wasm_bindgen::prelude::*;
 
+use crate::storage_layout;
+
 /// Compute the storage slot for an ERC-20 balance lookup
 ///
 /// For mappings like `mapping(address => uint256)`, the slot is:
@@ -28,22 +33,10 @@ pub fn compute_balance_slot(address: &[u8], slot_base: u32) -> Result<Vec<u8>, J
         return Err(JsValue::from_str("Address must be 20 bytes"));
     }
 
-    // abi.encode pads address to 32 bytes (left-padded with zeros)
-    let mut input = [0u8; 64];
-
-    // First 32 bytes: address left-padded to 32 bytes
-    input[12..32].copy_from_slice(address);
-
-    // Second 32 bytes: slot_base as uint256 (big-endian, left-padded)
-    input[60..64].copy_from_slice(&slot_base.to_be_bytes());
-
-    let mut hasher = Keccak::v256();
-    hasher.update(&input);
-
-    let mut slot = [0u8; 32];
-    hasher.finalize(&mut slot);
+    let mut addr = [0u8; 20];
+    addr.copy_from_slice(address);
 
-    Ok(slot.to_vec())
+    Ok(storage_layout::compute_balance_slot(addr, slot_base).to_vec())
 }
 
 /// Compute the storage slot for an ERC-20 balance lookup (hex string interface)
@@ -0,0 +1,281 @@
+//! Generalized Solidity storage-slot computation
+//!
+//! `compute_balance_slot` only covers a single-level `mapping(address =>
+//! uint256)`. Real contracts nest mappings (`allowance[owner][spender]`),
+//! use dynamic arrays, and pack multiple struct fields into one slot.
+//! [`StorageLayout`] follows the standard Solidity storage layout rules for
+//! all of these so a caller can target arbitrary state:
+//!
+//! - A fixed value at slot `p` lives at `p`.
+//! - `mapping(K => V)` at slot `p`: value for key `k` is at
+//!   `keccak256(h(k) || pad32(p))`, where `h(k)` is `k` left-padded to 32
+//!   bytes. Nesting composes this: `a[k1][k2]` resolves to
+//!   `keccak256(h(k2) || keccak256(h(k1) || pad32(p)))`.
+//! - A dynamic array at slot `p`: length is at `p`, element `i` is at
+//!   `keccak256(pad32(p)) + i * elemSizeWords` (mod 2^256).
+//! - Struct fields occupy consecutive slots from the struct's base, and a
+//!   value smaller than 32 bytes packs right-aligned within its slot.
+
+use tiny_keccak::{Hasher, Keccak};
+
+fn keccak256(data: &[u8]) -> [u8; 32] {
+    let mut hasher = Keccak::v256();
+    hasher.update(data);
+    let mut out = [0u8; 32];
+    hasher.finalize(&mut out);
+    out
+}
+
+/// Add `words` (as whole 32-byte slots) to a base slot, wrapping at 2^256 -
+/// matches Solidity's storage slot arithmetic for array elements.
+fn add_slot_words(base: [u8; 32], words: u128) -> [u8; 32] {
+    let mut result = base;
+    let mut carry = words;
+    for byte in result.iter_mut().rev() {
+        if carry == 0 {
+            break;
+        }
+        let sum = *byte as u128 + (carry & 0xff);
+        *byte = sum as u8;
+        carry = (carry >> 8) + (sum >> 8);
+    }
+    result
+}
+
+/// A mapping key, left-padded to 32 bytes the way `abi.encode` would for
+/// that type.
+#[derive(Debug, Clone, Copy)]
+pub enum SlotKey {
+    Address([u8; 20]),
+    Uint256([u8; 32]),
+    Bytes32([u8; 32]),
+}
+
+impl SlotKey {
+    fn pad32(&self) -> [u8; 32] {
+        match self {
+            SlotKey::Address(addr) => {
+                let mut padded = [0u8; 32];
+                padded[12..].copy_from_slice(addr);
+                padded
+            }
+            SlotKey::Uint256(v) | SlotKey::Bytes32(v) => *v,
+        }
+    }
+}
+
+/// A resolved storage location: the 32-byte slot, and (for a packed struct
+/// field) the byte range within it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SlotLocation {
+    pub slot: [u8; 32],
+    /// `(byte_offset, byte_width)` within `slot`, right-aligned per Solidity
+    /// packing, or `None` for a full 32-byte value.
+    pub packed: Option<(u8, u8)>,
+}
+
+/// Describes where a state variable's base slot comes from, before any
+/// struct-field offset/packing is applied.
+#[derive(Debug, Clone)]
+pub enum StorageLayout {
+    /// A variable declared directly at `slot`.
+    Fixed { slot: [u8; 32] },
+    /// `mapping(K => V)` declared at `base_slot`. `keys` has one entry for a
+    /// single-level mapping, or several (applied left to right) for nested
+    /// mappings, e.g. `[owner, spender]` for `allowance[owner][spender]`.
+    Mapping { base_slot: [u8; 32], keys: Vec<SlotKey> },
+    /// Element `index` of a dynamic array declared at `base_slot`, whose
+    /// elements are `elem_size_words` 32-byte slots wide.
+    ArrayElement {
+        base_slot: [u8; 32],
+        index: u64,
+        elem_size_words: u64,
+    },
+}
+
+impl StorageLayout {
+    /// Resolve this layout to its base slot (the slot a plain, unpacked
+    /// value of this layout would occupy).
+    pub fn resolve(&self) -> [u8; 32] {
+        match self {
+            StorageLayout::Fixed { slot } => *slot,
+            StorageLayout::Mapping { base_slot, keys } => {
+                let mut slot = *base_slot;
+                for key in keys {
+                    let mut input = [0u8; 64];
+                    input[..32].copy_from_slice(&key.pad32());
+                    input[32..].copy_from_slice(&slot);
+                    slot = keccak256(&input);
+                }
+                slot
+            }
+            StorageLayout::ArrayElement {
+                base_slot,
+                index,
+                elem_size_words,
+            } => {
+                let array_base = keccak256(base_slot);
+                add_slot_words(array_base, *index as u128 * *elem_size_words as u128)
+            }
+        }
+    }
+
+    /// The location of a plain, full-width value at this layout.
+    pub fn slot(&self) -> SlotLocation {
+        SlotLocation {
+            slot: self.resolve(),
+            packed: None,
+        }
+    }
+
+    /// The location of a struct field `slot_offset` words past this
+    /// layout's base slot. Pass `byte_width: 32` for a field that fills its
+    /// slot on its own; a narrower `byte_width` marks it as packed at
+    /// `byte_offset` bytes from the right (LSB) end of the slot, alongside
+    /// whatever else Solidity packed into that slot.
+    pub fn field(&self, slot_offset: u64, byte_offset: u8, byte_width: u8) -> SlotLocation {
+        SlotLocation {
+            slot: add_slot_words(self.resolve(), slot_offset as u128),
+            packed: if byte_width < 32 {
+                Some((byte_offset, byte_width))
+            } else {
+                None
+            },
+        }
+    }
+}
+
+/// Compute the storage slot for an ERC-20-style balance mapping
+/// `mapping(address => uint256)` at `slot_base` - a thin wrapper over the
+/// single-key [`StorageLayout::Mapping`] case.
+pub fn compute_balance_slot(address: [u8; 20], slot_base: u32) -> [u8; 32] {
+    let mut base_slot = [0u8; 32];
+    base_slot[28..].copy_from_slice(&slot_base.to_be_bytes());
+
+    StorageLayout::Mapping {
+        base_slot,
+        keys: vec![SlotKey::Address(address)],
+    }
+    .slot()
+    .slot
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn address(hex: &str) -> [u8; 20] {
+        let hex = hex.strip_prefix("0x").unwrap_or(hex);
+        let mut out = [0u8; 20];
+        for (i, byte) in out.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).unwrap();
+        }
+        out
+    }
+
+    fn hex_encode(bytes: &[u8]) -> String {
+        bytes.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    #[test]
+    fn test_compute_balance_slot_matches_dapper_labs_example() {
+        let addr = address("467d543e5e4e41aeddf3b6d1997350dd9820a173");
+        let slot = compute_balance_slot(addr, 9);
+        assert_eq!(
+            hex_encode(&slot),
+            "4065d4ec50c2a4fc400b75cca2760227b773c3e315ed2f2a7784cd505065cb07"
+        );
+    }
+
+    #[test]
+    fn test_nested_mapping_matches_sequential_single_mappings() {
+        let owner = address("1111111111111111111111111111111111111111");
+        let spender = address("2222222222222222222222222222222222222222");
+        let mut base_slot = [0u8; 32];
+        base_slot[31] = 1;
+
+        let nested = StorageLayout::Mapping {
+            base_slot,
+            keys: vec![SlotKey::Address(owner), SlotKey::Address(spender)],
+        }
+        .resolve();
+
+        let owner_slot = StorageLayout::Mapping {
+            base_slot,
+            keys: vec![SlotKey::Address(owner)],
+        }
+        .resolve();
+        let expected = StorageLayout::Mapping {
+            base_slot: owner_slot,
+            keys: vec![SlotKey::Address(spender)],
+        }
+        .resolve();
+
+        assert_eq!(nested, expected);
+    }
+
+    #[test]
+    fn test_array_element_slots_are_consecutive() {
+        let mut base_slot = [0u8; 32];
+        base_slot[31] = 5;
+
+        let elem0 = StorageLayout::ArrayElement {
+            base_slot,
+            index: 0,
+            elem_size_words: 1,
+        }
+        .resolve();
+        let elem1 = StorageLayout::ArrayElement {
+            base_slot,
+            index: 1,
+            elem_size_words: 1,
+        }
+        .resolve();
+
+        let mut expected_elem1 = elem0;
+        // elem0 + 1, big-endian
+        for byte in expected_elem1.iter_mut().rev() {
+            if *byte == 0xff {
+                *byte = 0;
+                continue;
+            }
+            *byte += 1;
+            break;
+        }
+        assert_eq!(elem1, expected_elem1);
+    }
+
+    #[test]
+    fn test_array_element_respects_multi_word_stride() {
+        let base_slot = [0u8; 32];
+        let elem0 = StorageLayout::ArrayElement {
+            base_slot,
+            index: 0,
+            elem_size_words: 3,
+        }
+        .resolve();
+        let elem1 = StorageLayout::ArrayElement {
+            base_slot,
+            index: 1,
+            elem_size_words: 3,
+        }
+        .resolve();
+
+        assert_eq!(add_slot_words(elem0, 3), elem1);
+    }
+
+    #[test]
+    fn test_struct_field_packing() {
+        let layout = StorageLayout::Fixed { slot: [0u8; 32] };
+
+        let unpacked = layout.field(1, 0, 32);
+        assert_eq!(unpacked.packed, None);
+        let mut expected_slot = [0u8; 32];
+        expected_slot[31] = 1;
+        assert_eq!(unpacked.slot, expected_slot);
+
+        let packed = layout.field(0, 20, 1);
+        assert_eq!(packed.packed, Some((20, 1)));
+        assert_eq!(packed.slot, [0u8; 32]);
+    }
+}
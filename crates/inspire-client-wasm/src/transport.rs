@@ -1,10 +1,29 @@
-//! Browser-compatible HTTP transport using fetch API
+//! Browser-compatible HTTP and WebSocket transport
+//!
+//! `HttpClient`'s request/response methods (`get`, `post_json`, ...) require
+//! polling to notice state changes. `subscribe` complements them with a
+//! WebSocket stream of [`LaneUpdate`]s so a caller can apply deltas as they
+//! happen instead of re-fetching. Mirrors the native `tokio-tungstenite`
+//! transport `inspire_client::BucketIndexSubscriber` uses, built on
+//! `gloo-net`'s WebSocket since this crate targets the browser.
 
+use futures_util::{Stream, StreamExt};
 use gloo_net::http::Request;
+use gloo_net::websocket::{futures::WebSocket, Message};
 use serde::{de::DeserializeOwned, Serialize};
 
 use crate::error::PirError;
 
+/// A single update received over a lane's live subscription.
+pub enum LaneUpdate {
+    /// Raw bytes of one chunked frame of a `BucketDelta` (see
+    /// `BucketDelta::to_chunks` in inspire-core) - a delta spanning many
+    /// buckets may arrive as several of these. Feed each one through a
+    /// `bucket_index::DeltaChunkReassembler` and only apply the result once
+    /// it yields a complete delta's bytes via `BucketIndex::apply_delta`.
+    BucketDelta(Vec<u8>),
+}
+
 pub struct HttpClient {
     base_url: String,
 }
@@ -87,11 +106,11 @@ impl HttpClient {
 
     pub async fn get_binary(&self, path: &str) -> Result<Vec<u8>, PirError> {
         let url = format!("{}{}", self.base_url, path);
-        
+
         let response = Request::get(&url)
             .send()
             .await?;
-        
+
         if !response.ok() {
             return Err(PirError::Network(format!(
                 "HTTP {} from {}",
@@ -99,8 +118,48 @@ impl HttpClient {
                 url
             )));
         }
-        
+
         let bytes = response.binary().await?;
         Ok(bytes)
     }
+
+    /// Subscribe to `path`'s websocket endpoint (e.g. a lane's
+    /// `/index/subscribe`), returning a stream of [`LaneUpdate`]s.
+    ///
+    /// Pass `since_block` (the last block this client has already applied)
+    /// to resume after a dropped connection - it's appended as `?since=`.
+    /// The `/index/subscribe` handler in `inspire-server` does not read the
+    /// parameter: it sends its Hello and then live deltas from its current
+    /// tip, the same as for a fresh connection. `None` omits the parameter.
+    pub fn subscribe(
+        &self,
+        path: &str,
+        since_block: Option<u64>,
+    ) -> Result<impl Stream<Item = Result<LaneUpdate, PirError>>, PirError> {
+        let url = self.websocket_url(path, since_block);
+        let ws = WebSocket::open(&url).map_err(|e| PirError::Network(e.to_string()))?;
+
+        Ok(ws.filter_map(|msg| async move {
+            match msg {
+                Ok(Message::Bytes(bytes)) => Some(Ok(LaneUpdate::BucketDelta(bytes))),
+                // The text Hello frame carries protocol version/current
+                // block but nothing to apply; skip it rather than surfacing
+                // it as an update.
+                Ok(Message::Text(_)) => None,
+                Err(e) => Some(Err(PirError::Network(e.to_string()))),
+            }
+        }))
+    }
+
+    fn websocket_url(&self, path: &str, since_block: Option<u64>) -> String {
+        let ws_base = self
+            .base_url
+            .replacen("https://", "wss://", 1)
+            .replacen("http://", "ws://", 1);
+
+        match since_block {
+            Some(block) => format!("{ws_base}{path}?since={block}"),
+            None => format!("{ws_base}{path}"),
+        }
+    }
 }
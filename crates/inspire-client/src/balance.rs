@@ -0,0 +1,52 @@
+//! ERC-20-style balance slot computation
+//!
+//! Mirrors `inspire-client-wasm`'s `storage_layout::compute_balance_slot`
+//! (duplicated rather than shared since the two crates have no dependency
+//! on each other): the storage slot for a `mapping(address => uint256)`
+//! balance mapping is `keccak256(pad32(address) ++ pad32(slot_base))`.
+
+use tiny_keccak::{Hasher, Keccak};
+
+use inspire_core::{Address, StorageKey};
+
+/// Compute the storage slot for an ERC-20-style balance mapping
+/// `mapping(address => uint256)` declared at `slot_base`.
+pub fn compute_balance_slot(address: &Address, slot_base: u32) -> StorageKey {
+    let mut preimage = [0u8; 64];
+    preimage[12..32].copy_from_slice(address);
+    preimage[60..64].copy_from_slice(&slot_base.to_be_bytes());
+
+    let mut hasher = Keccak::v256();
+    hasher.update(&preimage);
+    let mut slot = [0u8; 32];
+    hasher.finalize(&mut slot);
+    slot
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn address(hex: &str) -> Address {
+        let hex = hex.strip_prefix("0x").unwrap_or(hex);
+        let mut out = [0u8; 20];
+        for (i, byte) in out.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).unwrap();
+        }
+        out
+    }
+
+    fn hex_encode(bytes: &[u8]) -> String {
+        bytes.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    #[test]
+    fn test_compute_balance_slot_matches_wasm_crate() {
+        let addr = address("467d543e5e4e41aeddf3b6d1997350dd9820a173");
+        let slot = compute_balance_slot(&addr, 9);
+        assert_eq!(
+            hex_encode(&slot),
+            "4065d4ec50c2a4fc400b75cca2760227b773c3e315ed2f2a7784cd505065cb07"
+        );
+    }
+}
@@ -1,18 +1,26 @@
 //! Bucket Index for sparse client-side PIR index lookups
 //!
-//! Uses 256K buckets (18-bit hash prefix) for O(1) lookup of (address, slot) -> bucket range.
+//! Uses 256K buckets (18-bit hash prefix) for O(log N) lookup of (address, slot) -> bucket range.
 //! Client downloads ~150 KB compressed index once, then computes bucket ranges locally.
 //!
-//! ## Limitations
+//! ## Within-Bucket Resolution
 //!
-//! The bucket index returns a **range** (start_index, count), not an exact PIR index.
-//! To locate a specific entry within a bucket, additional structure is needed (e.g.,
-//! within-bucket manifest or secondary hash).
+//! `lookup_bucket` only narrows a query to a **range** (start_index, count).
+//! `lookup_exact` pins down the single matching entry using a per-bucket
+//! open-addressing slot array: each entry is placed at `tag % slots_in_bucket`
+//! (or the next free slot within `MAX_SEARCH` probes), where `tag` is a second,
+//! independent 16-bit hash of `(address, slot)` (see
+//! [`inspire_core::bucket_index::compute_slot_tag`]). The slot arrays are
+//! only carried in the compressed index format (see `to_compressed`); an
+//! index loaded from the plain `from_bytes` format, or one that has had a
+//! `BucketDelta` applied, has no slots and `lookup_exact` always returns
+//! `None`.
 //!
 //! ## DB Ordering Invariant
 //!
-//! **Critical**: The cumulative-sum scheme assumes the PIR database is physically ordered
-//! by bucket ID:
+//! **Critical**: The bucket-start scheme (backed by a Fenwick tree over
+//! per-bucket counts; see `apply_delta`) assumes the PIR database is
+//! physically ordered by bucket ID:
 //!
 //! ```text
 //! [bucket 0 entries][bucket 1 entries]...[bucket N entries]
@@ -26,24 +34,55 @@ use std::io::Read;
 
 // Re-export shared types from inspire-core
 pub use inspire_core::bucket_index::{
-    compute_bucket_id, compute_cumulative, BucketDelta, BucketRange, NUM_BUCKETS,
+    build_fenwick, compute_bucket_id, compute_slot_starts, compute_slot_tag, fenwick_prefix_sum,
+    fenwick_update, slots_for_count, BucketDelta, BucketRange, DEFAULT_MAX_SEARCH,
+    DEFAULT_SLOT_LOAD_FACTOR, NUM_BUCKETS,
 };
 
+/// Sentinel `local_offset` marking an empty open-addressing slot.
+const EMPTY_SLOT: u16 = u16::MAX;
+
+/// Magic bytes for the self-describing compressed index envelope.
+const ENVELOPE_MAGIC: &[u8; 4] = b"BIDX";
+
+/// Current compressed index envelope version.
+const ENVELOPE_VERSION: u32 = 2;
+
+/// `magic(4) + version(4) + load_factor_bits(8) + max_search(1) + has_slots(1)`
+const ENVELOPE_HEADER_SIZE: usize = 4 + 4 + 8 + 1 + 1;
+
 /// Bucket index for sparse PIR lookups
 ///
-/// Maps keccak256(address || slot) to a bucket, enabling O(1) bucket range lookup.
-/// Returns (start_index, count) for the bucket; exact within-bucket index requires
-/// additional structure (not yet implemented).
+/// Maps keccak256(address || slot) to a bucket, enabling O(log N) bucket
+/// range lookup via `lookup_bucket`, and (when the loaded index carries
+/// slot arrays) exact single-entry resolution via `lookup_exact`.
 #[derive(Debug, Clone)]
 pub struct BucketIndex {
     /// Count of entries in each bucket
     counts: Vec<u16>,
-    /// Cumulative sum for O(1) start index lookup
-    cumulative: Vec<u64>,
+    /// Fenwick tree (binary indexed tree) over `counts`, giving O(log N)
+    /// bucket-start prefix sums and O(log N) point updates in `apply_delta`
+    /// (see [`inspire_core::bucket_index::build_fenwick`]), rather than an
+    /// O(1)-read, O(N)-rebuild flat cumulative array.
+    fenwick: Vec<u64>,
+    /// Flattened per-bucket open-addressing slot arrays: `(tag, local_offset)`.
+    /// Empty when this index has no within-bucket structure.
+    slots: Vec<(u16, u16)>,
+    /// Cumulative slot-array offsets (see `compute_slot_starts`), the
+    /// slot-count counterpart of the bucket-start prefix sums. Empty iff
+    /// `slots` is empty.
+    slot_starts: Vec<u64>,
+    /// Load factor the slot arrays were sized with (see `slots_for_count`).
+    load_factor: f64,
+    /// Bound on how many consecutive slots `lookup_exact` probes.
+    max_search: usize,
 }
 
 impl BucketIndex {
     /// Load bucket index from uncompressed binary (512 KB)
+    ///
+    /// This format never carries slot arrays; `lookup_exact` on the result
+    /// always returns `None`.
     pub fn from_bytes(data: &[u8]) -> Result<Self, BucketIndexError> {
         if data.len() != NUM_BUCKETS * 2 {
             return Err(BucketIndexError::InvalidSize {
@@ -57,25 +96,123 @@ impl BucketIndex {
             counts.push(u16::from_le_bytes([chunk[0], chunk[1]]));
         }
 
-        let cumulative = compute_cumulative(&counts);
+        let fenwick = build_fenwick(&counts);
+
+        Ok(Self {
+            counts,
+            fenwick,
+            slots: Vec::new(),
+            slot_starts: Vec::new(),
+            load_factor: DEFAULT_SLOT_LOAD_FACTOR,
+            max_search: DEFAULT_MAX_SEARCH,
+        })
+    }
+
+    /// Build a bucket index directly from its parts, e.g. from a builder tool
+    /// that has already placed within-bucket slots. Pass an empty `slots` to
+    /// build a counts-only index.
+    pub fn from_parts(
+        counts: Vec<u16>,
+        slots: Vec<(u16, u16)>,
+        load_factor: f64,
+        max_search: usize,
+    ) -> Self {
+        let fenwick = build_fenwick(&counts);
+        let slot_starts = if slots.is_empty() {
+            Vec::new()
+        } else {
+            compute_slot_starts(&counts, load_factor)
+        };
 
-        Ok(Self { counts, cumulative })
+        Self {
+            counts,
+            fenwick,
+            slots,
+            slot_starts,
+            load_factor,
+            max_search,
+        }
     }
 
-    /// Load bucket index from zstd-compressed binary (~150 KB)
+    /// Load bucket index from a zstd-compressed envelope (~150 KB without
+    /// slots; larger when within-bucket slot arrays are included).
     pub fn from_compressed(data: &[u8]) -> Result<Self, BucketIndexError> {
         let decoder = zstd::Decoder::new(data)
             .map_err(|e| BucketIndexError::Decompression(e.to_string()))?;
-        const MAX_SIZE: u64 = (NUM_BUCKETS * 2 + 1) as u64;
+        // Counts are fixed-size; slots take 4 bytes each, about total
+        // entries divided by the load factor. 512 MiB fits counts plus slot
+        // arrays for roughly 100M entries at the default 0.75 load factor;
+        // a larger envelope (e.g. slot arrays for the ~2.7B-entry cold
+        // lane) is rejected as a decompression bomb.
+        const MAX_SIZE: u64 = 512 * 1024 * 1024;
         let mut limited = decoder.take(MAX_SIZE);
         let mut decompressed = Vec::with_capacity(NUM_BUCKETS * 2);
         limited.read_to_end(&mut decompressed)?;
-        if decompressed.len() > NUM_BUCKETS * 2 {
+        if decompressed.len() as u64 >= MAX_SIZE {
             return Err(BucketIndexError::DecompressionBomb {
                 size: decompressed.len(),
             });
         }
-        Self::from_bytes(&decompressed)
+        Self::from_envelope(&decompressed)
+    }
+
+    fn from_envelope(data: &[u8]) -> Result<Self, BucketIndexError> {
+        if data.len() < ENVELOPE_HEADER_SIZE || &data[0..4] != ENVELOPE_MAGIC {
+            return Err(BucketIndexError::InvalidEnvelope);
+        }
+
+        let version = u32::from_le_bytes(data[4..8].try_into().unwrap());
+        if version != ENVELOPE_VERSION {
+            return Err(BucketIndexError::UnsupportedVersion { version });
+        }
+
+        let load_factor = f64::from_bits(u64::from_le_bytes(data[8..16].try_into().unwrap()));
+        let max_search = data[16] as usize;
+        let has_slots = data[17] != 0;
+
+        let counts_start = ENVELOPE_HEADER_SIZE;
+        let counts_end = counts_start + NUM_BUCKETS * 2;
+        if data.len() < counts_end {
+            return Err(BucketIndexError::InvalidSize {
+                expected: counts_end,
+                actual: data.len(),
+            });
+        }
+
+        let mut counts = Vec::with_capacity(NUM_BUCKETS);
+        for chunk in data[counts_start..counts_end].chunks_exact(2) {
+            counts.push(u16::from_le_bytes([chunk[0], chunk[1]]));
+        }
+
+        if !has_slots {
+            return Ok(Self::from_parts(counts, Vec::new(), load_factor, max_search));
+        }
+
+        let slot_starts = compute_slot_starts(&counts, load_factor);
+        let total_slots = *slot_starts.last().unwrap() as usize;
+        let slots_end = counts_end + total_slots * 4;
+        if data.len() < slots_end {
+            return Err(BucketIndexError::InvalidSize {
+                expected: slots_end,
+                actual: data.len(),
+            });
+        }
+
+        let mut slots = Vec::with_capacity(total_slots);
+        for chunk in data[counts_end..slots_end].chunks_exact(4) {
+            let tag = u16::from_le_bytes([chunk[0], chunk[1]]);
+            let local_offset = u16::from_le_bytes([chunk[2], chunk[3]]);
+            slots.push((tag, local_offset));
+        }
+
+        Ok(Self {
+            fenwick: build_fenwick(&counts),
+            counts,
+            slots,
+            slot_starts,
+            load_factor,
+            max_search,
+        })
     }
 
     /// Look up the bucket range for a (address, slot) pair
@@ -83,12 +220,12 @@ impl BucketIndex {
     /// Returns (start_index, count) for the bucket containing this entry.
     /// **Note**: This returns a range, not an exact index. The client must either:
     /// - Query all entries in the range (privacy cost: multiple PIR queries)
-    /// - Use additional within-bucket structure (not yet implemented)
+    /// - Use `lookup_exact` if this index carries within-bucket slot arrays
     ///
     /// Assumes the PIR database is ordered by bucket ID (see module docs).
     pub fn lookup_bucket(&self, address: &[u8; 20], slot: &[u8; 32]) -> BucketRange {
         let bucket_id = compute_bucket_id(address, slot);
-        let start = self.cumulative[bucket_id];
+        let start = fenwick_prefix_sum(&self.fenwick, bucket_id);
         let count = self.counts[bucket_id] as u64;
 
         BucketRange {
@@ -98,9 +235,46 @@ impl BucketIndex {
         }
     }
 
+    /// Resolve the exact absolute PIR index for a (address, slot) pair using
+    /// the within-bucket open-addressing slot array.
+    ///
+    /// Linearly probes at most `max_search` consecutive slots starting from
+    /// `tag % slots_in_bucket`, stopping at the first empty slot. Returns
+    /// `None` if this index has no slot arrays (e.g. loaded via `from_bytes`,
+    /// or after `apply_delta`), if the bucket has no slots, or if no slot
+    /// within the probe window matches (collision/absent).
+    pub fn lookup_exact(&self, address: &[u8; 20], slot: &[u8; 32]) -> Option<u64> {
+        if self.slots.is_empty() {
+            return None;
+        }
+
+        let bucket_id = compute_bucket_id(address, slot);
+        let bucket_slot_start = self.slot_starts[bucket_id] as usize;
+        let bucket_slot_count = (self.slot_starts[bucket_id + 1] - self.slot_starts[bucket_id]) as usize;
+        if bucket_slot_count == 0 {
+            return None;
+        }
+
+        let tag = compute_slot_tag(address, slot);
+        let start = (tag as usize) % bucket_slot_count;
+
+        for probe in 0..self.max_search.min(bucket_slot_count) {
+            let slot_idx = bucket_slot_start + (start + probe) % bucket_slot_count;
+            let (slot_tag, local_offset) = self.slots[slot_idx];
+            if local_offset == EMPTY_SLOT {
+                return None;
+            }
+            if slot_tag == tag {
+                return Some(fenwick_prefix_sum(&self.fenwick, bucket_id) + local_offset as u64);
+            }
+        }
+
+        None
+    }
+
     /// Get total number of entries across all buckets
     pub fn total_entries(&self) -> u64 {
-        self.cumulative[NUM_BUCKETS]
+        fenwick_prefix_sum(&self.fenwick, NUM_BUCKETS)
     }
 
     /// Get count for a specific bucket
@@ -110,21 +284,36 @@ impl BucketIndex {
 
     /// Get start index for a specific bucket
     pub fn bucket_start(&self, bucket_id: usize) -> u64 {
-        self.cumulative.get(bucket_id).copied().unwrap_or(0)
+        if bucket_id > NUM_BUCKETS {
+            return 0;
+        }
+        fenwick_prefix_sum(&self.fenwick, bucket_id)
     }
 
     /// Apply a delta update (for websocket streaming)
+    ///
+    /// Each updated bucket is applied as a single signed point-update to the
+    /// Fenwick tree (O(log N)) instead of rebuilding the whole cumulative
+    /// array (O(NUM_BUCKETS)), which matters once per-block deltas are
+    /// streaming in continuously over the websocket.
+    ///
+    /// A bucket's local offsets are only well-defined relative to the slot
+    /// array built for its *current* count, so a delta (which changes counts
+    /// without replaying placement) invalidates every slot: `lookup_exact`
+    /// returns `None` until a fresh snapshot with rebuilt slots is loaded.
     pub fn apply_delta(&mut self, delta: &BucketDelta) {
         for &(bucket_id, new_count) in &delta.updates {
             if bucket_id < NUM_BUCKETS {
+                let d = new_count as i64 - self.counts[bucket_id] as i64;
                 self.counts[bucket_id] = new_count;
+                fenwick_update(&mut self.fenwick, bucket_id, d);
             }
         }
-        // Recompute cumulative sums
-        self.cumulative = compute_cumulative(&self.counts);
+        self.slots.clear();
+        self.slot_starts.clear();
     }
 
-    /// Serialize to bytes (uncompressed)
+    /// Serialize to bytes (uncompressed, counts only — never carries slots)
     pub fn to_bytes(&self) -> Vec<u8> {
         self.counts
             .iter()
@@ -132,11 +321,98 @@ impl BucketIndex {
             .collect()
     }
 
-    /// Serialize to compressed bytes
+    /// Serialize to a zstd-compressed envelope, including within-bucket slot
+    /// arrays if this index has them.
     pub fn to_compressed(&self) -> Result<Vec<u8>, BucketIndexError> {
-        let data = self.to_bytes();
-        Ok(zstd::encode_all(&data[..], 19)?)
+        let envelope = self.to_envelope();
+        Ok(zstd::encode_all(&envelope[..], 19)?)
+    }
+
+    fn to_envelope(&self) -> Vec<u8> {
+        let has_slots = !self.slots.is_empty();
+        let mut buf = Vec::with_capacity(
+            ENVELOPE_HEADER_SIZE + self.counts.len() * 2 + self.slots.len() * 4,
+        );
+
+        buf.extend_from_slice(ENVELOPE_MAGIC);
+        buf.extend_from_slice(&ENVELOPE_VERSION.to_le_bytes());
+        buf.extend_from_slice(&self.load_factor.to_bits().to_le_bytes());
+        buf.push(self.max_search.min(u8::MAX as usize) as u8);
+        buf.push(has_slots as u8);
+
+        for &count in &self.counts {
+            buf.extend_from_slice(&count.to_le_bytes());
+        }
+
+        if has_slots {
+            for &(tag, local_offset) in &self.slots {
+                buf.extend_from_slice(&tag.to_le_bytes());
+                buf.extend_from_slice(&local_offset.to_le_bytes());
+            }
+        }
+
+        buf
+    }
+}
+
+/// Build within-bucket open-addressing slots for a stream of entries.
+///
+/// `entries` must yield every `(address, slot)` pair in the same bucket-ID
+/// order the PIR database is physically laid out in (see module docs), so
+/// the `local_offset` assigned to each entry (its position within the
+/// bucket, in encounter order) matches the database. `counts` must already
+/// reflect the final per-bucket totals.
+///
+/// Returns [`BucketIndexError::SlotPlacementFailed`] if some bucket's
+/// entries can't all be placed within `max_search` probes at `load_factor` —
+/// the caller should retry with a lower load factor (more headroom) or a
+/// larger `max_search`.
+pub fn build_slots(
+    counts: &[u16],
+    entries: impl IntoIterator<Item = ([u8; 20], [u8; 32])>,
+    load_factor: f64,
+    max_search: usize,
+) -> Result<Vec<(u16, u16)>, BucketIndexError> {
+    let slot_starts = compute_slot_starts(counts, load_factor);
+    let total_slots = *slot_starts.last().unwrap_or(&0) as usize;
+    let mut slots = vec![(0u16, EMPTY_SLOT); total_slots];
+    let mut next_local_offset = vec![0u16; counts.len()];
+
+    for (address, slot) in entries {
+        let bucket_id = compute_bucket_id(&address, &slot);
+        let bucket_slot_start = slot_starts[bucket_id] as usize;
+        let bucket_slot_count = (slot_starts[bucket_id + 1] - slot_starts[bucket_id]) as usize;
+
+        let local_offset = next_local_offset[bucket_id];
+        next_local_offset[bucket_id] += 1;
+
+        if bucket_slot_count == 0 {
+            continue;
+        }
+
+        let tag = compute_slot_tag(&address, &slot);
+        let start = (tag as usize) % bucket_slot_count;
+
+        let placed = (0..max_search.min(bucket_slot_count)).find(|&probe| {
+            let slot_idx = bucket_slot_start + (start + probe) % bucket_slot_count;
+            slots[slot_idx].1 == EMPTY_SLOT
+        });
+
+        match placed {
+            Some(probe) => {
+                let slot_idx = bucket_slot_start + (start + probe) % bucket_slot_count;
+                slots[slot_idx] = (tag, local_offset);
+            }
+            None => {
+                return Err(BucketIndexError::SlotPlacementFailed {
+                    bucket_id,
+                    max_search,
+                })
+            }
+        }
     }
+
+    Ok(slots)
 }
 
 /// Errors for bucket index operations
@@ -156,6 +432,15 @@ pub enum BucketIndexError {
 
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
+
+    #[error("Invalid compressed index envelope")]
+    InvalidEnvelope,
+
+    #[error("Unsupported compressed index envelope version {version}")]
+    UnsupportedVersion { version: u32 },
+
+    #[error("Could not place an entry for bucket {bucket_id} within {max_search} probes; retry with a lower load factor or larger max_search")]
+    SlotPlacementFailed { bucket_id: usize, max_search: usize },
 }
 
 #[cfg(test)]
@@ -215,6 +500,8 @@ mod tests {
     fn test_bucket_delta() {
         let delta = BucketDelta {
             block_number: 12345,
+            block_hash: [7u8; 32],
+            parent_hash: [6u8; 32],
             updates: vec![(0, 15), (100, 20)],
         };
 
@@ -237,6 +524,8 @@ mod tests {
 
         let delta = BucketDelta {
             block_number: 1,
+            block_hash: [0u8; 32],
+            parent_hash: [0u8; 32],
             updates: vec![(0, 15)],
         };
 
@@ -244,6 +533,31 @@ mod tests {
         assert_eq!(index.bucket_count(0), 15);
     }
 
+    #[test]
+    fn test_apply_delta_shifts_downstream_bucket_starts() {
+        let mut data = vec![0u8; NUM_BUCKETS * 2];
+        data[0] = 10; // bucket 0 = 10
+        data[2] = 5; // bucket 1 = 5
+
+        let mut index = BucketIndex::from_bytes(&data).unwrap();
+        assert_eq!(index.bucket_start(1), 10);
+        assert_eq!(index.bucket_start(2), 15);
+        assert_eq!(index.total_entries(), 15);
+
+        // Growing bucket 0 should shift every later bucket's start, same as
+        // a full `compute_cumulative` rebuild would.
+        index.apply_delta(&BucketDelta {
+            block_number: 1,
+            block_hash: [0u8; 32],
+            parent_hash: [0u8; 32],
+            updates: vec![(0, 20)],
+        });
+
+        assert_eq!(index.bucket_start(1), 20);
+        assert_eq!(index.bucket_start(2), 25);
+        assert_eq!(index.total_entries(), 25);
+    }
+
     #[test]
     fn test_compression_roundtrip() {
         let mut data = vec![0u8; NUM_BUCKETS * 2];
@@ -265,9 +579,10 @@ mod tests {
 
     #[test]
     fn test_delta_huge_update_count_does_not_oom() {
-        let mut data = vec![0u8; 12];
-        data[0..8].copy_from_slice(&1u64.to_le_bytes()); // block_number
-        data[8..12].copy_from_slice(&u32::MAX.to_le_bytes()); // claims 4B updates
+        let mut data = vec![0u8; 77];
+        data[0] = inspire_core::bucket_index::DELTA_FORMAT_VERSION;
+        data[1..9].copy_from_slice(&1u64.to_le_bytes()); // block_number
+        data[73..77].copy_from_slice(&u32::MAX.to_le_bytes()); // claims 4B updates
 
         let result = BucketDelta::from_bytes(&data);
         assert!(result.is_err(), "Should reject delta with huge update_count");
@@ -277,10 +592,12 @@ mod tests {
     fn test_delta_truncated_updates() {
         let delta = BucketDelta {
             block_number: 1,
+            block_hash: [0u8; 32],
+            parent_hash: [0u8; 32],
             updates: vec![(0, 1), (1, 2), (2, 3), (3, 4), (4, 5)],
         };
         let mut bytes = delta.to_bytes();
-        bytes[8..12].copy_from_slice(&10u32.to_le_bytes()); // lie: claim 10 updates
+        bytes[73..77].copy_from_slice(&10u32.to_le_bytes()); // lie: claim 10 updates
 
         let result = BucketDelta::from_bytes(&bytes);
         assert!(result.is_err(), "Should reject delta with truncated updates");
@@ -288,7 +605,9 @@ mod tests {
 
     #[test]
     fn test_from_compressed_rejects_oversized() {
-        let oversized = vec![0u8; NUM_BUCKETS * 2 + 1000];
+        // Bigger than the 512 MiB envelope cap; zeros compress away to almost
+        // nothing so this stays a fast test.
+        let oversized = vec![0u8; 512 * 1024 * 1024 + 1000];
         let bomb = zstd::encode_all(&oversized[..], 1).unwrap();
 
         let result = BucketIndex::from_compressed(&bomb);
@@ -296,6 +615,100 @@ mod tests {
         assert!(matches!(result, Err(BucketIndexError::DecompressionBomb { .. })));
     }
 
+    #[test]
+    fn test_compressed_roundtrip_with_slots() {
+        let mut counts = vec![0u16; NUM_BUCKETS];
+        let addresses_slots: Vec<([u8; 20], [u8; 32])> = (0u8..20)
+            .map(|i| ([i; 20], [i; 32]))
+            .collect();
+        for (address, slot) in &addresses_slots {
+            counts[compute_bucket_id(address, slot)] += 1;
+        }
+
+        let slots = build_slots(
+            &counts,
+            addresses_slots.iter().copied(),
+            DEFAULT_SLOT_LOAD_FACTOR,
+            DEFAULT_MAX_SEARCH,
+        )
+        .unwrap();
+
+        let index = BucketIndex::from_parts(counts, slots, DEFAULT_SLOT_LOAD_FACTOR, DEFAULT_MAX_SEARCH);
+        let compressed = index.to_compressed().unwrap();
+        let recovered = BucketIndex::from_compressed(&compressed).unwrap();
+
+        for (address, slot) in &addresses_slots {
+            assert_eq!(
+                index.lookup_exact(address, slot),
+                recovered.lookup_exact(address, slot)
+            );
+            assert!(recovered.lookup_exact(address, slot).is_some());
+        }
+    }
+
+    #[test]
+    fn test_lookup_exact_resolves_each_entry_to_its_offset_within_its_bucket() {
+        let address = [0x42u8; 20];
+        let slots_data: Vec<[u8; 32]> = (0u8..50).map(|i| [i; 32]).collect();
+
+        let mut counts = vec![0u16; NUM_BUCKETS];
+        for slot in &slots_data {
+            counts[compute_bucket_id(&address, slot)] += 1;
+        }
+
+        let entries: Vec<([u8; 20], [u8; 32])> = slots_data.iter().map(|&s| (address, s)).collect();
+        let slots = build_slots(&counts, entries, DEFAULT_SLOT_LOAD_FACTOR, DEFAULT_MAX_SEARCH).unwrap();
+
+        let index = BucketIndex::from_parts(counts, slots, DEFAULT_SLOT_LOAD_FACTOR, DEFAULT_MAX_SEARCH);
+
+        // Local offsets count encounter order within each bucket.
+        let mut seen_in_bucket = vec![0u64; NUM_BUCKETS];
+        for slot in &slots_data {
+            let bucket = index.lookup_bucket(&address, slot);
+            let expected_offset = seen_in_bucket[bucket.bucket_id];
+            seen_in_bucket[bucket.bucket_id] += 1;
+            let resolved = index.lookup_exact(&address, slot).unwrap();
+            assert_eq!(resolved, bucket.start_index + expected_offset);
+        }
+    }
+
+    #[test]
+    fn test_lookup_exact_none_without_slots() {
+        let data = vec![0u8; NUM_BUCKETS * 2];
+        let index = BucketIndex::from_bytes(&data).unwrap();
+
+        assert_eq!(index.lookup_exact(&[0u8; 20], &[0u8; 32]), None);
+    }
+
+    #[test]
+    fn test_apply_delta_clears_slots() {
+        let address = [0x11u8; 20];
+        let slot = [0x22u8; 32];
+        let bucket_id = compute_bucket_id(&address, &slot);
+
+        let mut counts = vec![0u16; NUM_BUCKETS];
+        counts[bucket_id] = 1;
+        let slots = build_slots(
+            &counts,
+            std::iter::once((address, slot)),
+            DEFAULT_SLOT_LOAD_FACTOR,
+            DEFAULT_MAX_SEARCH,
+        )
+        .unwrap();
+
+        let mut index = BucketIndex::from_parts(counts, slots, DEFAULT_SLOT_LOAD_FACTOR, DEFAULT_MAX_SEARCH);
+        assert!(index.lookup_exact(&address, &slot).is_some());
+
+        index.apply_delta(&BucketDelta {
+            block_number: 1,
+            block_hash: [0u8; 32],
+            parent_hash: [0u8; 32],
+            updates: vec![(bucket_id, 1)],
+        });
+
+        assert_eq!(index.lookup_exact(&address, &slot), None);
+    }
+
     #[test]
     fn test_bucket_lookup_correctness() {
         let mut data = vec![0u8; NUM_BUCKETS * 2];
@@ -93,8 +93,10 @@ impl TwoLaneClient {
         );
 
         let _query_json = self.build_query(lane, &contract, &slot)?;
-        
-        todo!("Implement actual PIR query - requires inspire-rs integration")
+
+        Err(ClientError::NotImplemented(
+            "PIR query execution requires inspire-rs integration".to_string(),
+        ))
     }
 
     /// Build a PIR query for the given target
@@ -108,7 +110,9 @@ impl TwoLaneClient {
             })?,
         };
 
-        todo!("Build PIR query using inspire-rs")
+        Err(ClientError::NotImplemented(
+            "PIR query construction requires inspire-rs integration".to_string(),
+        ))
     }
 
     /// Send a query to the server
@@ -210,7 +214,21 @@ mod tests {
     fn test_hot_contract_count() {
         let router = LaneRouter::new(create_test_manifest());
         let client = TwoLaneClient::new(router, "http://localhost:3000".into());
-        
+
         assert_eq!(client.hot_contract_count(), 2);
     }
+
+    /// `query` used to reach an unconditional `todo!()` once CRS was
+    /// loaded, panicking every caller. With CRS loaded it must now return a
+    /// `NotImplemented` error instead of panicking.
+    #[tokio::test]
+    async fn test_query_returns_not_implemented_error_rather_than_panicking() {
+        let router = LaneRouter::new(create_test_manifest());
+        let mut client = TwoLaneClient::new(router, "http://localhost:3000".into());
+        client.hot_crs = Some("test-crs".to_string());
+        client.cold_crs = Some("test-crs".to_string());
+
+        let err = client.query([0x11u8; 20], [0u8; 32]).await.unwrap_err();
+        assert!(matches!(err, ClientError::NotImplemented(_)));
+    }
 }
@@ -21,6 +21,9 @@ pub enum ClientError {
 
     #[error("Core error: {0}")]
     Core(#[from] inspire_core::Error),
+
+    #[error("not implemented: {0}")]
+    NotImplemented(String),
 }
 
 pub type Result<T> = std::result::Result<T, ClientError>;
@@ -3,10 +3,24 @@
 //! Uses bucket index (~150 KB) for O(1) client-side index lookups.
 //! No manifest download required - clients compute indices locally.
 
+pub mod balance;
 pub mod bucket_index;
 pub mod client;
 pub mod error;
+pub mod mpt;
+pub mod range_sync;
+pub mod rlp;
+pub mod rpc;
+pub mod subscriber;
 
-pub use bucket_index::{compute_bucket_id, BucketDelta, BucketIndex, BucketRange};
+pub use balance::compute_balance_slot;
+pub use bucket_index::{
+    build_slots, compute_bucket_id, compute_slot_tag, BucketDelta, BucketIndex, BucketIndexError,
+    BucketRange, DEFAULT_MAX_SEARCH, DEFAULT_SLOT_LOAD_FACTOR,
+};
 pub use client::TwoLaneClient;
 pub use error::ClientError;
+pub use mpt::{verify_account_proof, verify_storage_proof, verify_storage_value, AccountState, MptError};
+pub use range_sync::{fetch_range_delta, RangeDeltaOutcome};
+pub use rpc::{RpcError, RpcServer};
+pub use subscriber::{BucketIndexSubscriber, SubscriberError};
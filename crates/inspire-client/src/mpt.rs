@@ -0,0 +1,520 @@
+//! Merkle-Patricia proof verification of PIR responses
+//!
+//! A PIR query returns a `StorageValue` that the client otherwise has no
+//! choice but to trust the server computed honestly. Given a trusted block
+//! header's state root, [`verify_storage_value`] checks the returned value
+//! against an `eth_getProof`-style Merkle-Patricia proof instead, so a
+//! malicious or buggy server can't lie about it.
+//!
+//! The account proof is an ordered list of RLP-encoded trie nodes from the
+//! state root down to the account leaf, keyed by path `keccak256(address)`.
+//! The leaf decodes to `RLP[nonce, balance, storage_root, code_hash]`. The
+//! storage proof is verified the same way against `storage_root`, keyed by
+//! `keccak256(slot)`; its leaf holds the RLP-encoded storage value, which
+//! must equal the claimed value (or be absent, for a claimed value of
+//! zero).
+
+use tiny_keccak::{Hasher, Keccak};
+
+use crate::rlp::{decode_item, RlpItem};
+use inspire_core::{Address, StorageKey, StorageValue};
+
+fn keccak256(data: &[u8]) -> [u8; 32] {
+    let mut hasher = Keccak::v256();
+    hasher.update(data);
+    let mut out = [0u8; 32];
+    hasher.finalize(&mut out);
+    out
+}
+
+/// An account's state as decoded from its trie leaf.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountState {
+    pub nonce: u64,
+    /// Minimal big-endian balance, as stored in the trie.
+    pub balance: Vec<u8>,
+    pub storage_root: [u8; 32],
+    pub code_hash: [u8; 32],
+}
+
+/// Errors from walking or verifying a Merkle-Patricia proof.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum MptError {
+    #[error("malformed trie node RLP: {0}")]
+    Rlp(#[from] crate::rlp::RlpError),
+
+    #[error("trie node is not a 2-item (leaf/extension) or 17-item (branch) list")]
+    InvalidNodeShape,
+
+    #[error("referenced node hash does not match the next proof node")]
+    HashMismatch,
+
+    #[error("proof ended before the path was fully consumed")]
+    IncompleteProof,
+
+    #[error("proof path diverged from the claimed key")]
+    PathMismatch,
+
+    #[error("proof claims the key is absent, but a non-zero value was claimed")]
+    UnexpectedExclusion,
+
+    #[error("leaf value does not match the claimed value")]
+    ValueMismatch,
+
+    #[error("account leaf RLP must be a 4-field list [nonce, balance, storage_root, code_hash]")]
+    InvalidAccountLeaf,
+}
+
+/// A trie node's child reference, as stored in a branch/extension item.
+enum NodeRef {
+    /// Referenced by its 32-byte keccak256 hash - the next proof node must
+    /// hash to this.
+    Hash([u8; 32]),
+    /// Embedded directly (the child node's own RLP, inlined because it's
+    /// under 32 bytes), requiring no further proof node.
+    Embedded(RlpItem),
+    /// No child at this branch slot.
+    Empty,
+}
+
+fn node_ref(item: &RlpItem) -> NodeRef {
+    match item {
+        RlpItem::String(bytes) if bytes.is_empty() => NodeRef::Empty,
+        RlpItem::String(bytes) if bytes.len() == 32 => {
+            let mut hash = [0u8; 32];
+            hash.copy_from_slice(bytes);
+            NodeRef::Hash(hash)
+        }
+        other => NodeRef::Embedded(other.clone()),
+    }
+}
+
+/// Split a compact hex-prefix encoded path (used by extension/leaf nodes)
+/// into `(is_leaf, nibbles)`.
+fn decode_hex_prefix(bytes: &[u8]) -> (bool, Vec<u8>) {
+    let Some(&first) = bytes.first() else {
+        return (false, Vec::new());
+    };
+    let is_leaf = first & 0x20 != 0;
+    let is_odd = first & 0x10 != 0;
+
+    let mut nibbles = Vec::with_capacity(bytes.len() * 2);
+    if is_odd {
+        nibbles.push(first & 0x0f);
+    }
+    for &b in &bytes[1..] {
+        nibbles.push(b >> 4);
+        nibbles.push(b & 0x0f);
+    }
+    (is_leaf, nibbles)
+}
+
+fn key_nibbles(key: &[u8; 32]) -> Vec<u8> {
+    key.iter().flat_map(|&b| [b >> 4, b & 0x0f]).collect()
+}
+
+/// Walk `proof` (root-to-leaf RLP-encoded trie nodes) along `path`,
+/// returning the leaf's value item, or `None` if the proof demonstrates the
+/// key is absent from the trie.
+fn walk_proof(
+    root: [u8; 32],
+    path: &[u8],
+    proof: &[Vec<u8>],
+) -> Result<Option<Vec<u8>>, MptError> {
+    let mut current = NodeRefOrRoot::Hash(root);
+    let mut proof_iter = proof.iter();
+    let mut path_index = 0usize;
+
+    loop {
+        let node_bytes: &[u8] = match current {
+            NodeRefOrRoot::Hash(expected_hash) => {
+                let node_bytes = proof_iter.next().ok_or(MptError::IncompleteProof)?;
+                if keccak256(node_bytes) != expected_hash {
+                    return Err(MptError::HashMismatch);
+                }
+                node_bytes
+            }
+            NodeRefOrRoot::Empty => return Ok(None),
+        };
+
+        let (item, _) = decode_item(node_bytes)?;
+        let RlpItem::List(fields) = item else {
+            return Err(MptError::InvalidNodeShape);
+        };
+
+        match fields.len() {
+            17 => {
+                if path_index == path.len() {
+                    // Value stored at this branch node itself.
+                    let RlpItem::String(value) = &fields[16] else {
+                        return Err(MptError::InvalidNodeShape);
+                    };
+                    return Ok(if value.is_empty() {
+                        None
+                    } else {
+                        Some(value.clone())
+                    });
+                }
+                let nibble = path[path_index] as usize;
+                path_index += 1;
+                current = match node_ref(&fields[nibble]) {
+                    NodeRef::Hash(h) => NodeRefOrRoot::Hash(h),
+                    NodeRef::Empty => NodeRefOrRoot::Empty,
+                    NodeRef::Embedded(item) => {
+                        return walk_embedded(item, &path[path_index..]);
+                    }
+                };
+            }
+            2 => {
+                let RlpItem::String(compact) = &fields[0] else {
+                    return Err(MptError::InvalidNodeShape);
+                };
+                let (is_leaf, nibbles) = decode_hex_prefix(compact);
+                let remaining = &path[path_index..];
+                if remaining.len() < nibbles.len() || remaining[..nibbles.len()] != nibbles[..] {
+                    return Err(MptError::PathMismatch);
+                }
+                path_index += nibbles.len();
+
+                if is_leaf {
+                    if path_index != path.len() {
+                        return Err(MptError::PathMismatch);
+                    }
+                    let RlpItem::String(value) = &fields[1] else {
+                        return Err(MptError::InvalidNodeShape);
+                    };
+                    return Ok(Some(value.clone()));
+                }
+
+                current = match node_ref(&fields[1]) {
+                    NodeRef::Hash(h) => NodeRefOrRoot::Hash(h),
+                    NodeRef::Empty => NodeRefOrRoot::Empty,
+                    NodeRef::Embedded(item) => {
+                        return walk_embedded(item, &path[path_index..]);
+                    }
+                };
+            }
+            _ => return Err(MptError::InvalidNodeShape),
+        }
+    }
+}
+
+enum NodeRefOrRoot {
+    Hash([u8; 32]),
+    Empty,
+}
+
+/// Continue walking into a small (<32-byte) node that was embedded inline
+/// in its parent rather than referenced by hash, so there's no further
+/// proof entry to consume for it.
+fn walk_embedded(item: RlpItem, remaining_path: &[u8]) -> Result<Option<Vec<u8>>, MptError> {
+    let RlpItem::List(fields) = item else {
+        return Err(MptError::InvalidNodeShape);
+    };
+
+    match fields.len() {
+        17 => {
+            if remaining_path.is_empty() {
+                let RlpItem::String(value) = &fields[16] else {
+                    return Err(MptError::InvalidNodeShape);
+                };
+                return Ok(if value.is_empty() {
+                    None
+                } else {
+                    Some(value.clone())
+                });
+            }
+            let nibble = remaining_path[0] as usize;
+            match node_ref(&fields[nibble]) {
+                NodeRef::Empty => Ok(None),
+                NodeRef::Embedded(inner) => walk_embedded(inner, &remaining_path[1..]),
+                NodeRef::Hash(_) => Err(MptError::IncompleteProof),
+            }
+        }
+        2 => {
+            let RlpItem::String(compact) = &fields[0] else {
+                return Err(MptError::InvalidNodeShape);
+            };
+            let (is_leaf, nibbles) = decode_hex_prefix(compact);
+            if remaining_path.len() < nibbles.len()
+                || remaining_path[..nibbles.len()] != nibbles[..]
+            {
+                return Err(MptError::PathMismatch);
+            }
+            let rest = &remaining_path[nibbles.len()..];
+            if is_leaf {
+                if !rest.is_empty() {
+                    return Err(MptError::PathMismatch);
+                }
+                let RlpItem::String(value) = &fields[1] else {
+                    return Err(MptError::InvalidNodeShape);
+                };
+                Ok(Some(value.clone()))
+            } else {
+                match node_ref(&fields[1]) {
+                    NodeRef::Empty => Ok(None),
+                    NodeRef::Embedded(inner) => walk_embedded(inner, rest),
+                    NodeRef::Hash(_) => Err(MptError::IncompleteProof),
+                }
+            }
+        }
+        _ => Err(MptError::InvalidNodeShape),
+    }
+}
+
+/// Verify an account proof against `state_root`, returning the decoded
+/// account state (or `None` if the proof demonstrates the account doesn't
+/// exist).
+pub fn verify_account_proof(
+    state_root: [u8; 32],
+    address: &Address,
+    proof: &[Vec<u8>],
+) -> Result<Option<AccountState>, MptError> {
+    let path = key_nibbles(&keccak256(address));
+    let leaf = walk_proof(state_root, &path, proof)?;
+
+    leaf.map(|rlp_bytes| {
+        let (item, _) = decode_item(&rlp_bytes)?;
+        let RlpItem::List(fields) = item else {
+            return Err(MptError::InvalidAccountLeaf);
+        };
+        let [nonce, balance, storage_root, code_hash] =
+            <[RlpItem; 4]>::try_from(fields).map_err(|_| MptError::InvalidAccountLeaf)?;
+
+        let (RlpItem::String(nonce), RlpItem::String(balance), RlpItem::String(storage_root), RlpItem::String(code_hash)) =
+            (nonce, balance, storage_root, code_hash)
+        else {
+            return Err(MptError::InvalidAccountLeaf);
+        };
+
+        let nonce = crate::rlp::decode_u64(&nonce).map_err(|_| MptError::InvalidAccountLeaf)?;
+        let storage_root: [u8; 32] = storage_root
+            .try_into()
+            .map_err(|_| MptError::InvalidAccountLeaf)?;
+        let code_hash: [u8; 32] = code_hash.try_into().map_err(|_| MptError::InvalidAccountLeaf)?;
+
+        Ok(AccountState {
+            nonce,
+            balance,
+            storage_root,
+            code_hash,
+        })
+    })
+    .transpose()
+}
+
+/// Verify that `claimed_value` is the value stored at `slot` under
+/// `storage_root`, per `proof`. A claimed value of all zeros is satisfied
+/// either by a leaf holding that value or by an exclusion proof (the slot
+/// is absent from the trie, which is how Ethereum represents a zero value).
+pub fn verify_storage_proof(
+    storage_root: [u8; 32],
+    slot: &StorageKey,
+    claimed_value: &StorageValue,
+    proof: &[Vec<u8>],
+) -> Result<(), MptError> {
+    let path = key_nibbles(&keccak256(slot));
+    let leaf = walk_proof(storage_root, &path, proof)?;
+
+    match leaf {
+        None => {
+            if claimed_value.iter().all(|&b| b == 0) {
+                Ok(())
+            } else {
+                Err(MptError::UnexpectedExclusion)
+            }
+        }
+        Some(rlp_bytes) => {
+            let (item, _) = decode_item(&rlp_bytes)?;
+            let RlpItem::String(value) = item else {
+                return Err(MptError::InvalidNodeShape);
+            };
+            let mut padded = [0u8; 32];
+            if value.len() > 32 {
+                return Err(MptError::InvalidNodeShape);
+            }
+            padded[32 - value.len()..].copy_from_slice(&value);
+            if &padded == claimed_value {
+                Ok(())
+            } else {
+                Err(MptError::ValueMismatch)
+            }
+        }
+    }
+}
+
+/// Verify that `claimed_value` is the value PIR returned for `(address,
+/// slot)`, against a trusted header `state_root`, using an `eth_getProof`
+/// account proof and storage proof.
+pub fn verify_storage_value(
+    state_root: [u8; 32],
+    address: &Address,
+    slot: &StorageKey,
+    claimed_value: &StorageValue,
+    account_proof: &[Vec<u8>],
+    storage_proof: &[Vec<u8>],
+) -> Result<(), MptError> {
+    let account = verify_account_proof(state_root, address, account_proof)?;
+
+    match account {
+        None => {
+            // No account at all: every slot reads as zero.
+            if claimed_value.iter().all(|&b| b == 0) {
+                Ok(())
+            } else {
+                Err(MptError::UnexpectedExclusion)
+            }
+        }
+        Some(account) => {
+            verify_storage_proof(account.storage_root, slot, claimed_value, storage_proof)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Build a minimal single-leaf trie: root is a leaf node directly
+    /// (no branch), keyed by the full 64-nibble path.
+    fn single_leaf_trie(path: &[u8; 32], value: &[u8]) -> ([u8; 32], Vec<Vec<u8>>) {
+        let nibbles = key_nibbles(path);
+        let mut compact = Vec::new();
+        let is_odd = nibbles.len() % 2 == 1;
+        let mut flagged = vec![0x20 | if is_odd { 0x10 } else { 0x00 }];
+        let mut rest = nibbles.clone();
+        if is_odd {
+            flagged[0] |= rest.remove(0);
+        }
+        compact.append(&mut flagged);
+        for pair in rest.chunks(2) {
+            compact.push((pair[0] << 4) | pair.get(1).copied().unwrap_or(0));
+        }
+
+        let node = encode_list(&[encode_bytes(&compact), encode_bytes(value)]);
+        let root = keccak256(&node);
+        (root, vec![node])
+    }
+
+    fn encode_bytes(data: &[u8]) -> Vec<u8> {
+        if data.len() == 1 && data[0] < 0x80 {
+            return vec![data[0]];
+        }
+        let mut out = encode_length(data.len(), 0x80);
+        out.extend_from_slice(data);
+        out
+    }
+
+    fn encode_list(items: &[Vec<u8>]) -> Vec<u8> {
+        let payload_len: usize = items.iter().map(|i| i.len()).sum();
+        let mut out = encode_length(payload_len, 0xc0);
+        for item in items {
+            out.extend_from_slice(item);
+        }
+        out
+    }
+
+    fn encode_length(len: usize, offset: u8) -> Vec<u8> {
+        if len < 56 {
+            vec![offset + len as u8]
+        } else {
+            let len_bytes: Vec<u8> = len
+                .to_be_bytes()
+                .into_iter()
+                .skip_while(|&b| b == 0)
+                .collect();
+            let mut out = vec![offset + 55 + len_bytes.len() as u8];
+            out.extend_from_slice(&len_bytes);
+            out
+        }
+    }
+
+    #[test]
+    fn test_single_leaf_storage_proof_verifies() {
+        let slot: StorageKey = [0x11u8; 32];
+        let key = keccak256(&slot);
+        let mut value = [0u8; 32];
+        value[31] = 42;
+
+        let (root, proof) = single_leaf_trie(&key, &encode_bytes(&[42]));
+
+        assert!(verify_storage_proof(root, &slot, &value, &proof).is_ok());
+    }
+
+    #[test]
+    fn test_single_leaf_storage_proof_rejects_wrong_value() {
+        let slot: StorageKey = [0x11u8; 32];
+        let key = keccak256(&slot);
+        let (root, proof) = single_leaf_trie(&key, &encode_bytes(&[42]));
+
+        let wrong = [0xffu8; 32];
+        assert_eq!(
+            verify_storage_proof(root, &slot, &wrong, &proof),
+            Err(MptError::ValueMismatch)
+        );
+    }
+
+    #[test]
+    fn test_exclusion_proof_accepts_zero_claim_on_absent_branch_slot() {
+        // A 17-item branch node with every slot empty: any key routes to an
+        // empty child, which is a valid exclusion proof for a zero value.
+        let empty_refs: Vec<Vec<u8>> = vec![encode_bytes(&[]); 17];
+        let node = encode_list(&empty_refs);
+        let root = keccak256(&node);
+
+        let slot: StorageKey = [0x22u8; 32];
+        let zero = [0u8; 32];
+        assert!(verify_storage_proof(root, &slot, &zero, &[node]).is_ok());
+    }
+
+    #[test]
+    fn test_exclusion_proof_rejects_nonzero_claim() {
+        let empty_refs: Vec<Vec<u8>> = vec![encode_bytes(&[]); 17];
+        let node = encode_list(&empty_refs);
+        let root = keccak256(&node);
+
+        let slot: StorageKey = [0x22u8; 32];
+        let mut nonzero = [0u8; 32];
+        nonzero[31] = 1;
+        assert_eq!(
+            verify_storage_proof(root, &slot, &nonzero, &[node]),
+            Err(MptError::UnexpectedExclusion)
+        );
+    }
+
+    #[test]
+    fn test_hash_mismatch_detected() {
+        let slot: StorageKey = [0x11u8; 32];
+        let key = keccak256(&slot);
+        let (root, _proof) = single_leaf_trie(&key, &encode_bytes(&[42]));
+
+        let other_node = encode_list(&[encode_bytes(&[0x20]), encode_bytes(&[99])]);
+        let value = [0u8; 32];
+        assert_eq!(
+            verify_storage_proof(root, &slot, &value, &[other_node]),
+            Err(MptError::HashMismatch)
+        );
+    }
+
+    #[test]
+    fn test_account_leaf_decodes_fields() {
+        let address: Address = [0xabu8; 20];
+        let key = keccak256(&address);
+
+        let nonce = encode_bytes(&[5]);
+        let balance = encode_bytes(&[1, 0]);
+        let storage_root = encode_bytes(&[0x33u8; 32]);
+        let code_hash = encode_bytes(&[0x44u8; 32]);
+        let account_rlp = encode_list(&[nonce, balance, storage_root, code_hash]);
+
+        let (root, proof) = single_leaf_trie(&key, &account_rlp);
+
+        let account = verify_account_proof(root, &address, &proof)
+            .unwrap()
+            .unwrap();
+        assert_eq!(account.nonce, 5);
+        assert_eq!(account.balance, vec![1, 0]);
+        assert_eq!(account.storage_root, [0x33u8; 32]);
+        assert_eq!(account.code_hash, [0x44u8; 32]);
+    }
+}
@@ -0,0 +1,55 @@
+//! HTTP range-request sync against a server's range-delta tiers
+//!
+//! `inspire-server`'s `/range-delta` route serves the smallest pre-merged
+//! delta tier covering a client's lag as a `206 Partial Content` byte range
+//! of `bucket-deltas.bin`. [`fetch_range_delta`] issues that single ranged
+//! GET and decodes the result, so a client only a few blocks behind doesn't
+//! have to re-download (or re-stream, block by block) the full index -
+//! see [`crate::subscriber::BucketIndexSubscriber`] for the alternative,
+//! always-resync-the-full-index path this complements.
+
+use inspire_core::bucket_index::BucketDelta;
+
+use crate::error::ClientError;
+
+/// Outcome of a range-delta fetch: either the merged delta to apply, or a
+/// signal that the client is too far behind for any tier to cover, so it
+/// should fall back to a full index download instead.
+pub enum RangeDeltaOutcome {
+    Delta(BucketDelta),
+    TooFarBehind,
+}
+
+/// Fetch the range-delta tier covering blocks since `since_block` from
+/// `{server_url}/range-delta`, and decode it into a [`BucketDelta`] ready
+/// for [`crate::bucket_index::BucketIndex::apply_delta`].
+pub async fn fetch_range_delta(
+    http: &reqwest::Client,
+    server_url: &str,
+    since_block: u64,
+) -> Result<RangeDeltaOutcome, ClientError> {
+    let url = format!(
+        "{}/range-delta?since_block={since_block}",
+        server_url.trim_end_matches('/')
+    );
+
+    let response = http.get(&url).send().await?;
+
+    if response.status() == reqwest::StatusCode::RANGE_NOT_SATISFIABLE {
+        return Ok(RangeDeltaOutcome::TooFarBehind);
+    }
+
+    if !response.status().is_success() && response.status() != reqwest::StatusCode::PARTIAL_CONTENT
+    {
+        return Err(ClientError::Server {
+            status: response.status().as_u16(),
+            message: response.text().await.unwrap_or_default(),
+        });
+    }
+
+    let bytes = response.bytes().await?;
+    let delta = BucketDelta::from_bytes(&bytes)
+        .map_err(|e| ClientError::InvalidResponse(format!("malformed range-delta body: {e}")))?;
+
+    Ok(RangeDeltaOutcome::Delta(delta))
+}
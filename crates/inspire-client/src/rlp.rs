@@ -0,0 +1,140 @@
+//! Minimal RLP (Recursive Length Prefix) decoding
+//!
+//! Just enough to decode the trie node and account-leaf payloads returned
+//! by `eth_getProof` for [`crate::mpt`]'s Merkle-Patricia verification.
+//! Decode-only: this crate never needs to produce RLP itself.
+
+/// A decoded RLP item: either a byte string or a list of items.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RlpItem {
+    String(Vec<u8>),
+    List(Vec<RlpItem>),
+}
+
+/// Errors produced while decoding RLP-encoded bytes.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum RlpError {
+    #[error("RLP: unexpected end of input")]
+    UnexpectedEof,
+    #[error("RLP: non-canonical length prefix")]
+    NonCanonicalLength,
+    #[error("RLP: invalid shape ({0})")]
+    InvalidShape(&'static str),
+}
+
+/// Decode one RLP item from the start of `data`, returning the item and
+/// the number of bytes consumed.
+pub fn decode_item(data: &[u8]) -> Result<(RlpItem, usize), RlpError> {
+    let first = *data.first().ok_or(RlpError::UnexpectedEof)?;
+
+    if first < 0x80 {
+        Ok((RlpItem::String(vec![first]), 1))
+    } else if first < 0xb8 {
+        let len = (first - 0x80) as usize;
+        let (body, consumed) = take_body(data, 1, len)?;
+        if len == 1 && body[0] < 0x80 {
+            return Err(RlpError::NonCanonicalLength);
+        }
+        Ok((RlpItem::String(body.to_vec()), consumed))
+    } else if first < 0xc0 {
+        let len_of_len = (first - 0xb7) as usize;
+        let (len, header_len) = read_long_length(data, 1, len_of_len)?;
+        let (body, consumed) = take_body(data, header_len, len)?;
+        Ok((RlpItem::String(body.to_vec()), consumed))
+    } else if first < 0xf8 {
+        let len = (first - 0xc0) as usize;
+        let (body, consumed) = take_body(data, 1, len)?;
+        Ok((RlpItem::List(decode_list_items(body)?), consumed))
+    } else {
+        let len_of_len = (first - 0xf7) as usize;
+        let (len, header_len) = read_long_length(data, 1, len_of_len)?;
+        let (body, consumed) = take_body(data, header_len, len)?;
+        Ok((RlpItem::List(decode_list_items(body)?), consumed))
+    }
+}
+
+fn take_body(data: &[u8], start: usize, len: usize) -> Result<(&[u8], usize), RlpError> {
+    let end = start.checked_add(len).ok_or(RlpError::UnexpectedEof)?;
+    if end > data.len() {
+        return Err(RlpError::UnexpectedEof);
+    }
+    Ok((&data[start..end], end))
+}
+
+fn read_long_length(
+    data: &[u8],
+    start: usize,
+    len_of_len: usize,
+) -> Result<(usize, usize), RlpError> {
+    let (len_bytes, consumed) = take_body(data, start, len_of_len)?;
+    if len_bytes.is_empty() || len_bytes[0] == 0 {
+        return Err(RlpError::NonCanonicalLength);
+    }
+    let mut buf = [0u8; 8];
+    buf[8 - len_bytes.len()..].copy_from_slice(len_bytes);
+    let len = u64::from_be_bytes(buf) as usize;
+    if len < 56 {
+        return Err(RlpError::NonCanonicalLength);
+    }
+    Ok((len, consumed))
+}
+
+fn decode_list_items(mut data: &[u8]) -> Result<Vec<RlpItem>, RlpError> {
+    let mut items = Vec::new();
+    while !data.is_empty() {
+        let (item, consumed) = decode_item(data)?;
+        items.push(item);
+        data = &data[consumed..];
+    }
+    Ok(items)
+}
+
+/// Decode a minimal big-endian byte string into a `u64`.
+pub fn decode_u64(bytes: &[u8]) -> Result<u64, RlpError> {
+    if bytes.len() > 8 || (bytes.len() > 1 && bytes[0] == 0) {
+        return Err(RlpError::NonCanonicalLength);
+    }
+    let mut buf = [0u8; 8];
+    buf[8 - bytes.len()..].copy_from_slice(bytes);
+    Ok(u64::from_be_bytes(buf))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip_short_string() {
+        let encoded = [0x83, b'c', b'a', b't'];
+        let (item, consumed) = decode_item(&encoded).unwrap();
+        assert_eq!(consumed, 4);
+        assert_eq!(item, RlpItem::String(b"cat".to_vec()));
+    }
+
+    #[test]
+    fn test_decode_list_of_strings() {
+        // ["cat", "dog"]
+        let encoded = [0xc8, 0x83, b'c', b'a', b't', 0x83, b'd', b'o', b'g'];
+        let (item, consumed) = decode_item(&encoded).unwrap();
+        assert_eq!(consumed, encoded.len());
+        assert_eq!(
+            item,
+            RlpItem::List(vec![
+                RlpItem::String(b"cat".to_vec()),
+                RlpItem::String(b"dog".to_vec()),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_decode_u64() {
+        assert_eq!(decode_u64(&[]).unwrap(), 0);
+        assert_eq!(decode_u64(&[0x01]).unwrap(), 1);
+        assert_eq!(decode_u64(&[0x01, 0x00]).unwrap(), 256);
+    }
+
+    #[test]
+    fn test_rejects_unexpected_eof() {
+        assert_eq!(decode_item(&[0x83, b'c']), Err(RlpError::UnexpectedEof));
+    }
+}
@@ -0,0 +1,302 @@
+//! Streaming client for the server's bucket-index delta broadcast
+//!
+//! The server documents a delta-over-websocket protocol (see
+//! `inspire-server::broadcast`) but nothing in this crate actually speaks it:
+//! `BucketIndex::apply_delta` exists, yet nothing subscribes to the stream,
+//! keeps track of which block it's applied up to, or notices a missed
+//! block. [`BucketIndexSubscriber`] closes that loop: it connects, applies
+//! deltas in order, and if an incoming delta's `block_number` isn't exactly
+//! one past the last one applied, it suspends streaming and re-downloads the
+//! full compressed index to resynchronize before resuming. It also chains
+//! deltas by hash via `ForkTracker`, so a reorg on the upstream chain is
+//! caught before a delta from the orphaned fork is ever applied, not just a
+//! gap in `block_number`.
+
+use futures_util::{SinkExt, StreamExt};
+use serde::Deserialize;
+use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite::Message;
+
+use inspire_core::bucket_index::{ChunkReassembler, ChunkReassemblyError, DeltaError, ForkTracker};
+
+use crate::bucket_index::{BucketDelta, BucketIndex, BucketIndexError};
+
+/// How many recently-applied blocks `ForkTracker` keeps on hand to identify
+/// a reorg's common ancestor. Generous relative to the handful of blocks a
+/// typical reorg unwinds, without holding unbounded history.
+const FORK_TRACKER_CAPACITY: usize = 256;
+
+/// Protocol version this subscriber understands. Must match
+/// `inspire_server::broadcast::PROTOCOL_VERSION` on the server side; kept as
+/// its own constant here rather than a dependency on `inspire-server`, since
+/// the server already depends on this crate for `BucketDelta`.
+const SUPPORTED_PROTOCOL_VERSION: u16 = 1;
+
+/// Mirrors `inspire_server::broadcast::WsHello`: the first message sent on a
+/// new subscription, carrying the protocol version and the block the index
+/// is current as of (`None` if the server hasn't ingested a block yet).
+#[derive(Debug, Clone, Deserialize)]
+struct WsHello {
+    version: u16,
+    block_number: Option<u64>,
+}
+
+/// Response from the resync endpoint: the full compressed index as of
+/// `block_number`, hex-encoded the same way `pir-client` hex-encodes binary
+/// payloads in JSON responses.
+#[derive(Debug, Clone, Deserialize)]
+struct ResyncResponse {
+    block_number: u64,
+    compressed_index: String,
+}
+
+/// Subscribes to a server's bucket-index delta broadcast, applying deltas to
+/// a local [`BucketIndex`] in order and resyncing over HTTP when a block is
+/// missed.
+///
+/// `run` drives one connection attempt to completion (or failure); it
+/// returns a [`SubscriberError`] whenever the websocket drops so the caller
+/// can decide whether and how to reconnect (e.g. with backoff), rather than
+/// retrying internally.
+pub struct BucketIndexSubscriber {
+    ws_url: String,
+    resync_url: String,
+    http: reqwest::Client,
+    index: BucketIndex,
+    last_applied_block: Option<u64>,
+    reassembler: ChunkReassembler,
+    fork_tracker: ForkTracker,
+}
+
+impl BucketIndexSubscriber {
+    /// Create a subscriber starting from `index`, already current as of
+    /// `last_applied_block` (`None` if `index` has never had a delta
+    /// applied). `ws_url` is the server's subscription endpoint (e.g.
+    /// `ws://host/index/subscribe`); `resync_url` must return a
+    /// [`ResyncResponse`]-shaped JSON body with the current compressed index.
+    pub fn new(
+        ws_url: impl Into<String>,
+        resync_url: impl Into<String>,
+        index: BucketIndex,
+        last_applied_block: Option<u64>,
+    ) -> Self {
+        Self {
+            ws_url: ws_url.into(),
+            resync_url: resync_url.into(),
+            http: reqwest::Client::new(),
+            index,
+            last_applied_block,
+            reassembler: ChunkReassembler::new(),
+            fork_tracker: ForkTracker::new(FORK_TRACKER_CAPACITY),
+        }
+    }
+
+    /// The index as of `last_applied_block`.
+    pub fn index(&self) -> &BucketIndex {
+        &self.index
+    }
+
+    /// The last block number applied to `index`, via either a delta or a
+    /// resync, or `None` if nothing has been applied yet.
+    pub fn last_applied_block(&self) -> Option<u64> {
+        self.last_applied_block
+    }
+
+    /// Connect, apply deltas as they arrive until the connection ends, and
+    /// send each newly-applied block number on `applied`. Resyncs
+    /// automatically on a detected gap (including right after connecting, if
+    /// the server's Hello reports a block the index hasn't caught up to).
+    ///
+    /// Returns once the connection drops, normally or otherwise - a
+    /// `Send` error on `applied` (receiver gone) is not treated as fatal,
+    /// since the caller may simply have stopped listening for progress
+    /// without wanting the subscription itself to stop.
+    pub async fn run(&mut self, applied: mpsc::Sender<u64>) -> Result<(), SubscriberError> {
+        let (ws_stream, _) = tokio_tungstenite::connect_async(&self.ws_url).await?;
+        let (mut write, mut read) = ws_stream.split();
+
+        let hello: WsHello = match read.next().await {
+            Some(Ok(Message::Text(text))) => serde_json::from_str(&text)?,
+            Some(Ok(_)) => return Err(SubscriberError::UnexpectedFirstMessage),
+            Some(Err(e)) => return Err(e.into()),
+            None => return Err(SubscriberError::ConnectionClosed),
+        };
+        if hello.version != SUPPORTED_PROTOCOL_VERSION {
+            return Err(SubscriberError::UnsupportedVersion {
+                version: hello.version,
+                expected: SUPPORTED_PROTOCOL_VERSION,
+            });
+        }
+
+        if self.last_applied_block != hello.block_number {
+            tracing::info!(
+                from = ?self.last_applied_block,
+                to = ?hello.block_number,
+                "resyncing bucket index on connect"
+            );
+            self.resync().await?;
+        }
+
+        while let Some(msg) = read.next().await {
+            match msg? {
+                Message::Binary(bytes) => {
+                    // Each block's delta may arrive as several chunked
+                    // frames (see `BucketDelta::to_chunks`); only the
+                    // terminating chunk yields a complete delta to apply.
+                    if let Some(delta) = self.reassembler.push(&bytes)? {
+                        self.apply(delta).await?;
+                        if let Some(block) = self.last_applied_block {
+                            let _ = applied.send(block).await;
+                        }
+                    }
+                }
+                Message::Ping(payload) => {
+                    write.send(Message::Pong(payload)).await?;
+                }
+                Message::Close(frame) => {
+                    let (code, reason) = frame
+                        .map(|f| (u16::from(f.code), f.reason.to_string()))
+                        .unwrap_or_default();
+                    return Err(SubscriberError::ServerClosed { code, reason });
+                }
+                _ => {}
+            }
+        }
+
+        Err(SubscriberError::ConnectionClosed)
+    }
+
+    /// Apply `delta` if it's the next block, otherwise resync first.
+    async fn apply(&mut self, delta: BucketDelta) -> Result<(), SubscriberError> {
+        if !is_contiguous(self.last_applied_block, delta.block_number) {
+            tracing::warn!(
+                last_applied = ?self.last_applied_block,
+                incoming = delta.block_number,
+                "missed block(s), resyncing bucket index"
+            );
+            self.resync().await?;
+
+            // The resync already landed on (or past) this delta's block, so
+            // there's nothing left to apply.
+            if !is_contiguous(self.last_applied_block, delta.block_number) {
+                return Ok(());
+            }
+        }
+
+        if let Err(DeltaError::ForkDetected { ancestor_block }) = self.fork_tracker.check(&delta) {
+            tracing::warn!(
+                ancestor_block,
+                incoming = delta.block_number,
+                "reorg detected, resyncing bucket index"
+            );
+            self.resync().await?;
+            return Ok(());
+        }
+
+        self.index.apply_delta(&delta);
+        self.last_applied_block = Some(delta.block_number);
+        self.fork_tracker
+            .record(delta.block_number, delta.block_hash);
+        Ok(())
+    }
+
+    /// Re-download the full compressed index and replace `self.index` with
+    /// it, bringing `last_applied_block` to whatever block the snapshot is
+    /// current as of.
+    async fn resync(&mut self) -> Result<(), SubscriberError> {
+        let resp: ResyncResponse = self
+            .http
+            .get(&self.resync_url)
+            .send()
+            .await?
+            .json()
+            .await?;
+
+        let bytes = hex::decode(&resp.compressed_index)
+            .map_err(|e| SubscriberError::InvalidResyncPayload(e.to_string()))?;
+        self.index = BucketIndex::from_compressed(&bytes)?;
+        self.last_applied_block = Some(resp.block_number);
+        // Discard any chunks buffered for a delta that predates the
+        // resync - it no longer applies cleanly on top of the fresh index.
+        self.reassembler = ChunkReassembler::new();
+        // The resync snapshot doesn't carry a block hash, so there's no
+        // chain to validate the next delta against until another one
+        // lands; start the tracker fresh rather than checking against
+        // stale pre-resync history.
+        self.fork_tracker = ForkTracker::new(FORK_TRACKER_CAPACITY);
+        Ok(())
+    }
+}
+
+/// Whether `incoming_block` can be applied directly on top of
+/// `last_applied` - true if there's nothing applied yet, or `incoming_block`
+/// is exactly one past it. Split out as a pure function so gap detection is
+/// testable without a live server.
+fn is_contiguous(last_applied: Option<u64>, incoming_block: u64) -> bool {
+    match last_applied {
+        None => true,
+        Some(last) => incoming_block == last + 1,
+    }
+}
+
+/// Errors from [`BucketIndexSubscriber::run`]. Every variant ends the
+/// subscription; the caller decides whether to reconnect.
+#[derive(Debug, thiserror::Error)]
+pub enum SubscriberError {
+    #[error("websocket error: {0}")]
+    WebSocket(#[from] tokio_tungstenite::tungstenite::Error),
+
+    #[error("resync request failed: {0}")]
+    Resync(#[from] reqwest::Error),
+
+    #[error("invalid resync payload: {0}")]
+    InvalidResyncPayload(String),
+
+    #[error("invalid hello message: {0}")]
+    InvalidHello(#[from] serde_json::Error),
+
+    #[error("first websocket message was not the Hello text frame")]
+    UnexpectedFirstMessage,
+
+    #[error("server hello used unsupported protocol version {version} (expected {expected})")]
+    UnsupportedVersion { version: u16, expected: u16 },
+
+    #[error(transparent)]
+    BucketIndex(#[from] BucketIndexError),
+
+    #[error("malformed delta chunk: {0}")]
+    Delta(#[from] ChunkReassemblyError),
+
+    #[error("server closed the connection (code {code}): {reason}")]
+    ServerClosed { code: u16, reason: String },
+
+    #[error("websocket connection closed")]
+    ConnectionClosed,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_contiguous_accepts_first_block_from_any_start() {
+        assert!(is_contiguous(None, 0));
+        assert!(is_contiguous(None, 42));
+    }
+
+    #[test]
+    fn test_is_contiguous_accepts_next_block() {
+        assert!(is_contiguous(Some(10), 11));
+    }
+
+    #[test]
+    fn test_is_contiguous_rejects_gap() {
+        assert!(!is_contiguous(Some(10), 12));
+    }
+
+    #[test]
+    fn test_is_contiguous_rejects_replay() {
+        assert!(!is_contiguous(Some(10), 10));
+        assert!(!is_contiguous(Some(10), 9));
+    }
+}
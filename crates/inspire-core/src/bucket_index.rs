@@ -16,12 +16,31 @@
 //! - Server maintains cumulative ranges: 1, 10, 100, 1000, 10000 blocks
 //! - Client downloads smallest range covering their sync gap
 //! - Single HTTP range request for minimal bandwidth
+//!
+//! ## Reorgs
+//!
+//! Deltas are assumed to apply along a single linear block progression, but
+//! the chain they're sourced from can reorg. [`BucketDelta::block_hash`] and
+//! [`BucketDelta::parent_hash`] let a caller chain-check incoming deltas with
+//! [`ForkTracker`] before applying them, and recover by resyncing from the
+//! detected common ancestor.
 
 use tiny_keccak::{Hasher, Keccak};
 
 /// Number of buckets (2^18 = 256K)
 pub const NUM_BUCKETS: usize = 262_144;
 
+/// Default open-addressing load factor for within-bucket slot arrays: slot
+/// arrays are sized to `ceil(count / DEFAULT_SLOT_LOAD_FACTOR)`, leaving
+/// headroom below 1.0 so tag collisions resolve within a short probe.
+pub const DEFAULT_SLOT_LOAD_FACTOR: f64 = 0.75;
+
+/// Default bound on how many consecutive slots `lookup_exact` will probe
+/// before giving up. Tune alongside `DEFAULT_SLOT_LOAD_FACTOR`: a lower load
+/// factor (more headroom) lets a smaller `MAX_SEARCH` still place every
+/// entry.
+pub const DEFAULT_MAX_SEARCH: usize = 8;
+
 /// Compute bucket ID from address and slot using keccak256
 ///
 /// Takes first 18 bits of keccak256(address || slot) as bucket ID.
@@ -39,6 +58,36 @@ pub fn compute_bucket_id(address: &[u8; 20], slot: &[u8; 32]) -> usize {
     bucket_id & (NUM_BUCKETS - 1)
 }
 
+/// Compute a second, independent 16-bit hash of (address, slot) for
+/// within-bucket open-addressing slots.
+///
+/// `compute_bucket_id` hashes `address || slot` and reads its first 18 bits;
+/// hashing the fields in the opposite order changes every output bit (unlike
+/// `compute_bucket_id`, which only ever reads the front of the digest), so
+/// reading the trailing 16 bits here gives an independent tag from the same
+/// primitive without pulling in a second hash function.
+pub fn compute_slot_tag(address: &[u8; 20], slot: &[u8; 32]) -> u16 {
+    let mut hasher = Keccak::v256();
+    hasher.update(slot);
+    hasher.update(address);
+
+    let mut hash = [0u8; 32];
+    hasher.finalize(&mut hash);
+
+    u16::from_le_bytes([hash[30], hash[31]])
+}
+
+/// Number of open-addressing slots to allocate for a bucket holding `count`
+/// entries at `load_factor`. Pure function of `(count, load_factor)` so it's
+/// never persisted, only recomputed identically at build and load time (like
+/// `compute_cumulative`).
+pub fn slots_for_count(count: u64, load_factor: f64) -> u64 {
+    if count == 0 {
+        return 0;
+    }
+    ((count as f64) / load_factor).ceil() as u64
+}
+
 /// Compute cumulative sums for O(1) start index lookup
 pub fn compute_cumulative(counts: &[u16]) -> Vec<u64> {
     let mut cumulative = Vec::with_capacity(NUM_BUCKETS + 1);
@@ -53,6 +102,72 @@ pub fn compute_cumulative(counts: &[u16]) -> Vec<u64> {
     cumulative
 }
 
+/// Compute cumulative within-bucket slot-array offsets, mirroring
+/// `compute_cumulative` but over `slots_for_count(count, load_factor)`
+/// instead of the raw counts.
+pub fn compute_slot_starts(counts: &[u16], load_factor: f64) -> Vec<u64> {
+    let mut starts = Vec::with_capacity(NUM_BUCKETS + 1);
+    starts.push(0);
+
+    let mut sum = 0u64;
+    for &count in counts {
+        sum += slots_for_count(count as u64, load_factor);
+        starts.push(sum);
+    }
+
+    starts
+}
+
+/// Build a Fenwick tree (binary indexed tree) over per-bucket `counts`,
+/// supporting O(log N) point updates and prefix-sum queries in place of
+/// recomputing the full cumulative array on every change (see
+/// `fenwick_update` / `fenwick_prefix_sum`).
+///
+/// Returned tree has length `counts.len() + 1` and is 1-indexed internally;
+/// callers always address it through `fenwick_update`/`fenwick_prefix_sum`
+/// using plain 0-indexed bucket ids.
+pub fn build_fenwick(counts: &[u16]) -> Vec<u64> {
+    let n = counts.len();
+    let mut tree = vec![0u64; n + 1];
+    for i in 1..=n {
+        tree[i] += counts[i - 1] as u64;
+        let parent = i + (i & i.wrapping_neg());
+        if parent <= n {
+            tree[parent] += tree[i];
+        }
+    }
+    tree
+}
+
+/// Apply a signed delta to bucket `index` (0-indexed) in a Fenwick tree built
+/// by `build_fenwick`. Walks `i = i + (i & -i)` from `index + 1`, adding
+/// `delta` at each node touched.
+pub fn fenwick_update(tree: &mut [u64], index: usize, delta: i64) {
+    let n = tree.len() - 1;
+    let mut i = index + 1;
+    while i <= n {
+        if delta >= 0 {
+            tree[i] += delta as u64;
+        } else {
+            tree[i] -= (-delta) as u64;
+        }
+        i += i & i.wrapping_neg();
+    }
+}
+
+/// Sum of `counts[0..index)` (i.e. the same quantity `compute_cumulative`
+/// would put at `cumulative[index]`), computed in O(log N) by walking
+/// `i = i - (i & -i)` from `index`.
+pub fn fenwick_prefix_sum(tree: &[u64], index: usize) -> u64 {
+    let mut i = index;
+    let mut sum = 0u64;
+    while i > 0 {
+        sum += tree[i];
+        i -= i & i.wrapping_neg();
+    }
+    sum
+}
+
 /// Range of indices within a bucket
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct BucketRange {
@@ -64,20 +179,39 @@ pub struct BucketRange {
     pub count: u64,
 }
 
-/// Delta update for streaming bucket index updates
+/// Delta update for streaming bucket index updates.
+///
+/// Each bucket's `new_count` in `updates` is an absolute count, not an
+/// increment - applying a delta is always a straight overwrite of the
+/// touched buckets. That invariant is what makes fork recovery cheap: once
+/// [`ForkTracker::check`] detects a reorg, the caller doesn't need to undo
+/// any bucket-by-bucket math, it just needs to apply a corrected delta
+/// chain from the common ancestor forward and the overwrite does the rest.
 #[derive(Debug, Clone)]
 pub struct BucketDelta {
     /// Block number this delta applies to
     pub block_number: u64,
+    /// Hash of the block this delta applies to
+    pub block_hash: [u8; 32],
+    /// Hash of `block_number`'s parent - chained against the local head's
+    /// `block_hash` by [`ForkTracker::check`] to detect reorgs.
+    pub parent_hash: [u8; 32],
     /// Updated bucket counts: (bucket_id, new_count)
     pub updates: Vec<(usize, u16)>,
 }
 
+/// Wire format version for [`BucketDelta::to_bytes`]/[`BucketDelta::from_bytes`].
+/// Bumped from the original hash-less format (implicitly version 1) when
+/// `block_hash`/`parent_hash` were added for reorg detection.
+pub const DELTA_FORMAT_VERSION: u8 = 2;
+
 /// Error type for bucket delta parsing
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum BucketDeltaError {
-    /// Delta header is too short (need at least 12 bytes)
+    /// Delta header is too short (need at least [`DELTA_HEADER_LEN`] bytes)
     HeaderTooShort { actual: usize },
+    /// Delta's version byte doesn't match [`DELTA_FORMAT_VERSION`]
+    UnsupportedVersion { version: u8 },
     /// Delta claims more updates than payload contains
     Truncated { expected: usize, actual: usize },
     /// Delta claims an excessive number of updates (potential DoS)
@@ -90,8 +224,15 @@ impl core::fmt::Display for BucketDeltaError {
             BucketDeltaError::HeaderTooShort { actual } => {
                 write!(
                     f,
-                    "Invalid delta: header too short (need 12 bytes, got {})",
-                    actual
+                    "Invalid delta: header too short (need {} bytes, got {})",
+                    DELTA_HEADER_LEN, actual
+                )
+            }
+            BucketDeltaError::UnsupportedVersion { version } => {
+                write!(
+                    f,
+                    "Invalid delta: unsupported format version {} (expected {})",
+                    version, DELTA_FORMAT_VERSION
                 )
             }
             BucketDeltaError::Truncated { expected, actual } => {
@@ -114,18 +255,28 @@ impl core::fmt::Display for BucketDeltaError {
 
 impl std::error::Error for BucketDeltaError {}
 
+/// Header length: version:1 + block_number:8 + block_hash:32 + parent_hash:32 + update_count:4
+const DELTA_HEADER_LEN: usize = 77;
+
 impl BucketDelta {
-    /// Create from bytes (simple format: block_num:8 + count:4 + (bucket_id:4 + count:2)*)
+    /// Create from bytes (format: version:1 + block_num:8 + block_hash:32 +
+    /// parent_hash:32 + count:4 + (bucket_id:4 + count:2)*)
     pub fn from_bytes(data: &[u8]) -> Result<Self, BucketDeltaError> {
-        const HEADER_LEN: usize = 12;
         const UPDATE_SIZE: usize = 6;
 
-        if data.len() < HEADER_LEN {
+        if data.len() < DELTA_HEADER_LEN {
             return Err(BucketDeltaError::HeaderTooShort { actual: data.len() });
         }
 
-        let block_number = u64::from_le_bytes(data[0..8].try_into().unwrap());
-        let update_count = u32::from_le_bytes(data[8..12].try_into().unwrap()) as usize;
+        let version = data[0];
+        if version != DELTA_FORMAT_VERSION {
+            return Err(BucketDeltaError::UnsupportedVersion { version });
+        }
+
+        let block_number = u64::from_le_bytes(data[1..9].try_into().unwrap());
+        let block_hash: [u8; 32] = data[9..41].try_into().unwrap();
+        let parent_hash: [u8; 32] = data[41..73].try_into().unwrap();
+        let update_count = u32::from_le_bytes(data[73..77].try_into().unwrap()) as usize;
 
         // Reject excessive update counts to prevent OOM on 32-bit targets (including WASM)
         if update_count > NUM_BUCKETS {
@@ -137,7 +288,7 @@ impl BucketDelta {
         // Use checked arithmetic to prevent overflow on 32-bit targets
         let payload_len = update_count
             .checked_mul(UPDATE_SIZE)
-            .and_then(|p| HEADER_LEN.checked_add(p))
+            .and_then(|p| DELTA_HEADER_LEN.checked_add(p))
             .ok_or(BucketDeltaError::TooManyUpdates {
                 count: update_count,
             })?;
@@ -150,7 +301,7 @@ impl BucketDelta {
         }
 
         let mut updates = Vec::with_capacity(update_count);
-        let mut offset = HEADER_LEN;
+        let mut offset = DELTA_HEADER_LEN;
 
         for _ in 0..update_count {
             let bucket_id =
@@ -162,14 +313,19 @@ impl BucketDelta {
 
         Ok(Self {
             block_number,
+            block_hash,
+            parent_hash,
             updates,
         })
     }
 
     /// Serialize to bytes
     pub fn to_bytes(&self) -> Vec<u8> {
-        let mut data = Vec::with_capacity(12 + self.updates.len() * 6);
+        let mut data = Vec::with_capacity(DELTA_HEADER_LEN + self.updates.len() * 6);
+        data.push(DELTA_FORMAT_VERSION);
         data.extend_from_slice(&self.block_number.to_le_bytes());
+        data.extend_from_slice(&self.block_hash);
+        data.extend_from_slice(&self.parent_hash);
         data.extend_from_slice(&(self.updates.len() as u32).to_le_bytes());
         for &(bucket_id, count) in &self.updates {
             data.extend_from_slice(&(bucket_id as u32).to_le_bytes());
@@ -177,6 +333,323 @@ impl BucketDelta {
         }
         data
     }
+
+    /// Split `to_bytes()` into chunks of at most `max_len` payload bytes
+    /// each, modeled on netapp's continuation-bit framing: every chunk is
+    /// prefixed with a 2-byte little-endian length whose high bit
+    /// ([`CONTINUATION_BIT`]) is set on every chunk but the last. A delta
+    /// touching tens of thousands of buckets can exceed practical
+    /// WebSocket/MTU frame sizes as one flat buffer; sending it as several
+    /// bounded frames instead avoids that without changing the delta
+    /// format itself. `max_len` must be at most `0x7fff` so it always fits
+    /// the 15 low bits alongside the continuation flag.
+    pub fn to_chunks(&self, max_len: usize) -> Vec<Vec<u8>> {
+        debug_assert!(max_len <= 0x7fff, "max_len must fit in 15 bits");
+        let bytes = self.to_bytes();
+        let mut chunks = Vec::with_capacity(bytes.len() / max_len.max(1) + 1);
+        let mut offset = 0;
+        loop {
+            let end = (offset + max_len).min(bytes.len());
+            let more = end < bytes.len();
+            chunks.push(encode_chunk(&bytes[offset..end], more));
+            offset = end;
+            if !more {
+                break;
+            }
+        }
+        chunks
+    }
+}
+
+/// Maximum chunk payload length used by [`BucketDelta::to_chunks`]'s
+/// default framing.
+pub const MAX_CHUNK_LEN: usize = 0x4000;
+
+/// Set on a chunk's 2-byte length header when more chunks follow.
+const CONTINUATION_BIT: u16 = 0x8000;
+
+/// Number of header bytes preceding each chunk's payload.
+const CHUNK_HEADER_SIZE: usize = 2;
+
+fn encode_chunk(payload: &[u8], more: bool) -> Vec<u8> {
+    let mut header = payload.len() as u16;
+    if more {
+        header |= CONTINUATION_BIT;
+    }
+    let mut frame = Vec::with_capacity(CHUNK_HEADER_SIZE + payload.len());
+    frame.extend_from_slice(&header.to_le_bytes());
+    frame.extend_from_slice(payload);
+    frame
+}
+
+/// Error reassembling a chunked [`BucketDelta`] via [`ChunkReassembler`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChunkReassemblyError {
+    /// A frame was shorter than the 2-byte header, or its continuation
+    /// header claimed a payload length the frame doesn't actually contain.
+    BadContinuation { frame_len: usize, claimed_len: usize },
+    /// The terminating chunk arrived, but the reassembled payload doesn't
+    /// parse as a [`BucketDelta`].
+    Truncated(BucketDeltaError),
+}
+
+impl core::fmt::Display for ChunkReassemblyError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ChunkReassemblyError::BadContinuation {
+                frame_len,
+                claimed_len,
+            } => write!(
+                f,
+                "bad chunk continuation header: frame was {} bytes but claimed {} payload bytes",
+                frame_len, claimed_len
+            ),
+            ChunkReassemblyError::Truncated(e) => {
+                write!(f, "reassembled delta is malformed: {}", e)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ChunkReassemblyError {}
+
+/// Stateful reassembler for [`BucketDelta::to_chunks`]'s wire framing.
+/// Accumulates chunk payloads as they arrive and yields the completed
+/// [`BucketDelta`] once the chunk whose continuation bit is clear arrives -
+/// so the sender and receiver don't need to agree up front on how many
+/// chunks a delta was split into.
+#[derive(Debug, Default)]
+pub struct ChunkReassembler {
+    buffer: Vec<u8>,
+}
+
+impl ChunkReassembler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feed one wire frame (2-byte header + payload) into the reassembler.
+    /// Returns `Ok(None)` while more chunks are still expected, or
+    /// `Ok(Some(delta))` once the terminating chunk completes a valid
+    /// delta. On error, the reassembler's partial buffer is left cleared
+    /// so a fresh delta can start from the next frame.
+    pub fn push(&mut self, frame: &[u8]) -> Result<Option<BucketDelta>, ChunkReassemblyError> {
+        if frame.len() < CHUNK_HEADER_SIZE {
+            self.buffer.clear();
+            return Err(ChunkReassemblyError::BadContinuation {
+                frame_len: frame.len(),
+                claimed_len: 0,
+            });
+        }
+        let header = u16::from_le_bytes(frame[0..CHUNK_HEADER_SIZE].try_into().unwrap());
+        let more = header & CONTINUATION_BIT != 0;
+        let claimed_len = (header & !CONTINUATION_BIT) as usize;
+        let payload = &frame[CHUNK_HEADER_SIZE..];
+
+        if payload.len() != claimed_len {
+            self.buffer.clear();
+            return Err(ChunkReassemblyError::BadContinuation {
+                frame_len: frame.len(),
+                claimed_len,
+            });
+        }
+        self.buffer.extend_from_slice(payload);
+
+        if more {
+            return Ok(None);
+        }
+
+        let result = BucketDelta::from_bytes(&self.buffer).map_err(ChunkReassemblyError::Truncated);
+        self.buffer.clear();
+        result.map(Some)
+    }
+}
+
+/// Out-of-order delta reassembly window, mirroring Solana's window service:
+/// buffers incoming [`BucketDelta`]s keyed by `block_number` until a
+/// contiguous run starting at `next_expected` is available. Reconnects,
+/// broadcast lag, and multiple upstream producers mean deltas delivered
+/// over `handle_index_subscription`'s WebSocket can arrive out of order or
+/// with gaps; applying them as received would corrupt client-side bucket
+/// counts, so callers buffer through a `DeltaWindow` instead. Lives in
+/// inspire-core (rather than inspire-client or inspire-client-wasm) so both
+/// the native and WASM clients share one reassembly implementation.
+pub struct DeltaWindow {
+    buffered: std::collections::BTreeMap<u64, BucketDelta>,
+    next_expected: u64,
+    max_gap: u64,
+}
+
+/// Signal from [`DeltaWindow::poll`] that the buffered window has fallen
+/// too far behind to keep buffering in memory. The caller should fetch a
+/// `range_delta` covering `from` and call [`DeltaWindow::resync`] with the
+/// block the range delta brought it current to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResyncNeeded {
+    pub from: u64,
+}
+
+impl DeltaWindow {
+    /// Create a window expecting `next_expected` as the first delta to
+    /// apply, buffering at most `max_gap` blocks ahead of it before
+    /// signalling [`ResyncNeeded`].
+    pub fn new(next_expected: u64, max_gap: u64) -> Self {
+        Self {
+            buffered: std::collections::BTreeMap::new(),
+            next_expected,
+            max_gap,
+        }
+    }
+
+    /// Buffer a newly-received delta. Drops it if it's already applied
+    /// (`block_number < next_expected`); ignores it if it's a duplicate of
+    /// an already-buffered block, rather than overwriting.
+    pub fn insert(&mut self, delta: BucketDelta) {
+        if delta.block_number < self.next_expected {
+            return;
+        }
+        self.buffered.entry(delta.block_number).or_insert(delta);
+    }
+
+    /// Pop the contiguous run of deltas starting at `next_expected`, in
+    /// order, advancing `next_expected` past them. Returns an empty `Vec`
+    /// if `next_expected` itself hasn't arrived yet.
+    ///
+    /// If the lowest still-buffered block is more than `max_gap` blocks
+    /// ahead of `next_expected`, returns [`ResyncNeeded`] instead so the
+    /// caller can fall back to a range-delta download rather than
+    /// buffering unbounded memory waiting for a gap that may never fill.
+    pub fn poll(&mut self) -> Result<Vec<BucketDelta>, ResyncNeeded> {
+        let mut ready = Vec::new();
+        while let Some(delta) = self.buffered.remove(&self.next_expected) {
+            self.next_expected += 1;
+            ready.push(delta);
+        }
+
+        if let Some(&lowest) = self.buffered.keys().next() {
+            if lowest > self.next_expected + self.max_gap {
+                return Err(ResyncNeeded {
+                    from: self.next_expected,
+                });
+            }
+        }
+
+        Ok(ready)
+    }
+
+    /// Reset the window after a caller-driven resync (e.g. a `range_delta`
+    /// download applied up through `caught_up_to`), discarding any
+    /// buffered deltas at or below it since they're now stale.
+    pub fn resync(&mut self, caught_up_to: u64) {
+        self.next_expected = caught_up_to + 1;
+        self.buffered.retain(|&block, _| block >= self.next_expected);
+    }
+
+    /// The next block number this window is waiting to apply.
+    pub fn next_expected(&self) -> u64 {
+        self.next_expected
+    }
+}
+
+/// Error from [`ForkTracker::check`]. Distinct from [`BucketDeltaError`],
+/// which is about malformed bytes rather than a chain mismatch between
+/// otherwise well-formed deltas.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeltaError {
+    /// The incoming delta's `parent_hash` doesn't match the tracked head.
+    /// `ancestor_block` is the most recent block the tracker can confirm is
+    /// still on the canonical chain - the caller should re-fetch and apply
+    /// a `range_delta` starting there. Because every `BucketDelta` update
+    /// carries an absolute bucket count rather than an increment, replaying
+    /// that range delta overwrites whatever the orphaned fork applied
+    /// without needing any separate rollback of bucket state.
+    ForkDetected { ancestor_block: u64 },
+}
+
+impl core::fmt::Display for DeltaError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            DeltaError::ForkDetected { ancestor_block } => {
+                write!(
+                    f,
+                    "fork detected: common ancestor at block {}",
+                    ancestor_block
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for DeltaError {}
+
+/// Tracks a bounded window of recently-applied `(block_number, block_hash)`
+/// pairs so an incoming [`BucketDelta`] can be checked against the chain
+/// actually applied so far, mirroring OpenEthereum's common-ancestor sync.
+/// A delta stream assumes deltas apply along a single linear block
+/// progression; a reorg breaks that assumption; `ForkTracker` is how a
+/// caller notices and recovers.
+pub struct ForkTracker {
+    applied: std::collections::VecDeque<(u64, [u8; 32])>,
+    capacity: usize,
+}
+
+impl ForkTracker {
+    /// Create a tracker retaining at most `capacity` recently-applied
+    /// blocks. A fork deeper than `capacity` blocks is reported against the
+    /// oldest block still in the ring rather than the true common ancestor.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            applied: std::collections::VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// Record a successfully-applied block, evicting the oldest entry once
+    /// `capacity` is exceeded.
+    pub fn record(&mut self, block_number: u64, block_hash: [u8; 32]) {
+        if self.applied.len() == self.capacity {
+            self.applied.pop_front();
+        }
+        self.applied.push_back((block_number, block_hash));
+    }
+
+    /// The hash of the most recently recorded block, or `None` if nothing
+    /// has been recorded yet.
+    pub fn head_hash(&self) -> Option<[u8; 32]> {
+        self.applied.back().map(|&(_, hash)| hash)
+    }
+
+    /// Check `delta` against the tracked chain before applying it.
+    ///
+    /// Returns `Ok(())` if there's no recorded history yet (nothing to
+    /// check against) or `delta.parent_hash` matches the current head.
+    /// Otherwise walks the ring backwards for a block whose hash matches
+    /// `delta.parent_hash` and reports it as the common ancestor; if none
+    /// is found within the window, reports the oldest recorded block since
+    /// that's as far back as this tracker can vouch for.
+    pub fn check(&self, delta: &BucketDelta) -> Result<(), DeltaError> {
+        match self.head_hash() {
+            None => Ok(()),
+            Some(head) if head == delta.parent_hash => Ok(()),
+            Some(_) => {
+                let ancestor_block = self
+                    .applied
+                    .iter()
+                    .rev()
+                    .find(|&&(_, hash)| hash == delta.parent_hash)
+                    .map(|&(block, _)| block)
+                    .unwrap_or_else(|| self.applied.front().map(|&(block, _)| block).unwrap_or(0));
+                Err(DeltaError::ForkDetected { ancestor_block })
+            }
+        }
+    }
+
+    /// Truncate the tracked history back to `ancestor_block`, discarding
+    /// everything recorded after it. Call this once a caller has decided
+    /// `ancestor_block` is the common ancestor to resume syncing from.
+    pub fn rollback_to(&mut self, ancestor_block: u64) {
+        self.applied.retain(|&(block, _)| block <= ancestor_block);
+    }
 }
 
 #[cfg(test)]
@@ -194,6 +667,67 @@ mod tests {
         assert!(id1 < NUM_BUCKETS);
     }
 
+    #[test]
+    fn test_slot_tag_is_deterministic() {
+        let address = [0x42u8; 20];
+        let slot = [0x01u8; 32];
+
+        let tag1 = compute_slot_tag(&address, &slot);
+        let tag2 = compute_slot_tag(&address, &slot);
+        assert_eq!(tag1, tag2);
+    }
+
+    #[test]
+    fn test_slots_for_count() {
+        assert_eq!(slots_for_count(0, 0.75), 0);
+        assert_eq!(slots_for_count(3, 0.75), 4); // ceil(3/0.75) = 4
+        assert_eq!(slots_for_count(6, 0.75), 8);
+    }
+
+    #[test]
+    fn test_compute_slot_starts() {
+        let counts = vec![3u16, 0, 6];
+        let starts = compute_slot_starts(&counts, 0.75);
+
+        assert_eq!(starts[0], 0);
+        assert_eq!(starts[1], 4);
+        assert_eq!(starts[2], 4);
+        assert_eq!(starts[3], 12);
+    }
+
+    #[test]
+    fn test_fenwick_matches_cumulative() {
+        let counts = vec![10u16, 5, 3, 0, 2];
+        let cumulative = compute_cumulative(&counts);
+        let fenwick = build_fenwick(&counts);
+
+        for i in 0..=counts.len() {
+            assert_eq!(fenwick_prefix_sum(&fenwick, i), cumulative[i]);
+        }
+    }
+
+    #[test]
+    fn test_fenwick_update_matches_rebuild() {
+        let mut counts = vec![10u16, 5, 3, 0, 2];
+        let mut fenwick = build_fenwick(&counts);
+
+        // Bucket 2: 3 -> 8 (delta +5)
+        fenwick_update(&mut fenwick, 2, 5);
+        counts[2] = 8;
+        let expected = compute_cumulative(&counts);
+        for i in 0..=counts.len() {
+            assert_eq!(fenwick_prefix_sum(&fenwick, i), expected[i]);
+        }
+
+        // Bucket 0: 10 -> 4 (delta -6)
+        fenwick_update(&mut fenwick, 0, -6);
+        counts[0] = 4;
+        let expected = compute_cumulative(&counts);
+        for i in 0..=counts.len() {
+            assert_eq!(fenwick_prefix_sum(&fenwick, i), expected[i]);
+        }
+    }
+
     #[test]
     fn test_compute_cumulative() {
         let counts = vec![10u16, 5, 3, 0, 2];
@@ -211,6 +745,8 @@ mod tests {
     fn test_bucket_delta_roundtrip() {
         let delta = BucketDelta {
             block_number: 12345,
+            block_hash: [7u8; 32],
+            parent_hash: [6u8; 32],
             updates: vec![(0, 15), (100, 20)],
         };
 
@@ -218,16 +754,31 @@ mod tests {
         let recovered = BucketDelta::from_bytes(&bytes).unwrap();
 
         assert_eq!(recovered.block_number, 12345);
+        assert_eq!(recovered.block_hash, [7u8; 32]);
+        assert_eq!(recovered.parent_hash, [6u8; 32]);
         assert_eq!(recovered.updates.len(), 2);
         assert_eq!(recovered.updates[0], (0, 15));
         assert_eq!(recovered.updates[1], (100, 20));
     }
 
+    #[test]
+    fn test_delta_rejects_unsupported_version() {
+        let mut data = vec![0u8; DELTA_HEADER_LEN];
+        data[0] = DELTA_FORMAT_VERSION + 1;
+
+        let result = BucketDelta::from_bytes(&data);
+        assert!(matches!(
+            result,
+            Err(BucketDeltaError::UnsupportedVersion { version }) if version == DELTA_FORMAT_VERSION + 1
+        ));
+    }
+
     #[test]
     fn test_delta_huge_update_count_rejected() {
-        let mut data = vec![0u8; 12];
-        data[0..8].copy_from_slice(&1u64.to_le_bytes());
-        data[8..12].copy_from_slice(&u32::MAX.to_le_bytes());
+        let mut data = vec![0u8; DELTA_HEADER_LEN];
+        data[0] = DELTA_FORMAT_VERSION;
+        data[1..9].copy_from_slice(&1u64.to_le_bytes());
+        data[73..77].copy_from_slice(&u32::MAX.to_le_bytes());
 
         let result = BucketDelta::from_bytes(&data);
         assert!(matches!(
@@ -238,9 +789,10 @@ mod tests {
 
     #[test]
     fn test_delta_exceeds_num_buckets_rejected() {
-        let mut data = vec![0u8; 12];
-        data[0..8].copy_from_slice(&1u64.to_le_bytes());
-        data[8..12].copy_from_slice(&((NUM_BUCKETS + 1) as u32).to_le_bytes());
+        let mut data = vec![0u8; DELTA_HEADER_LEN];
+        data[0] = DELTA_FORMAT_VERSION;
+        data[1..9].copy_from_slice(&1u64.to_le_bytes());
+        data[73..77].copy_from_slice(&((NUM_BUCKETS + 1) as u32).to_le_bytes());
 
         let result = BucketDelta::from_bytes(&data);
         assert!(
@@ -252,10 +804,12 @@ mod tests {
     fn test_delta_truncated_rejected() {
         let delta = BucketDelta {
             block_number: 1,
+            block_hash: [0u8; 32],
+            parent_hash: [0u8; 32],
             updates: vec![(0, 1), (1, 2), (2, 3)],
         };
         let mut bytes = delta.to_bytes();
-        bytes[8..12].copy_from_slice(&10u32.to_le_bytes()); // lie about count
+        bytes[73..77].copy_from_slice(&10u32.to_le_bytes()); // lie about count
 
         let result = BucketDelta::from_bytes(&bytes);
         assert!(matches!(result, Err(BucketDeltaError::Truncated { .. })));
@@ -263,7 +817,7 @@ mod tests {
 
     #[test]
     fn test_delta_header_too_short() {
-        let data = vec![0u8; 8]; // only 8 bytes, need 12
+        let data = vec![0u8; 8]; // far short of the 77-byte header
 
         let result = BucketDelta::from_bytes(&data);
         assert!(matches!(
@@ -271,6 +825,245 @@ mod tests {
             Err(BucketDeltaError::HeaderTooShort { actual: 8 })
         ));
     }
+
+    #[test]
+    fn test_to_chunks_single_chunk_when_under_max_len() {
+        let delta = BucketDelta {
+            block_number: 7,
+            block_hash: [0u8; 32],
+            parent_hash: [0u8; 32],
+            updates: vec![(1, 2), (3, 4)],
+        };
+        let chunks = delta.to_chunks(MAX_CHUNK_LEN);
+        assert_eq!(chunks.len(), 1);
+        // Single chunk must clear the continuation bit.
+        let header = u16::from_le_bytes(chunks[0][0..2].try_into().unwrap());
+        assert_eq!(header & CONTINUATION_BIT, 0);
+    }
+
+    #[test]
+    fn test_to_chunks_splits_large_delta_and_reassembles() {
+        let delta = BucketDelta {
+            block_number: 99,
+            block_hash: [9u8; 32],
+            parent_hash: [8u8; 32],
+            updates: (0..10_000).map(|i| (i, (i % 65536) as u16)).collect(),
+        };
+        let chunks = delta.to_chunks(64);
+        assert!(chunks.len() > 1);
+
+        // Every chunk but the last carries the continuation bit.
+        for chunk in &chunks[..chunks.len() - 1] {
+            let header = u16::from_le_bytes(chunk[0..2].try_into().unwrap());
+            assert_ne!(header & CONTINUATION_BIT, 0);
+        }
+        let last_header = u16::from_le_bytes(chunks.last().unwrap()[0..2].try_into().unwrap());
+        assert_eq!(last_header & CONTINUATION_BIT, 0);
+
+        let mut reassembler = ChunkReassembler::new();
+        let mut reassembled = None;
+        for chunk in &chunks {
+            reassembled = reassembler.push(chunk).unwrap();
+        }
+        let reassembled = reassembled.expect("terminating chunk should yield a delta");
+        assert_eq!(reassembled.block_number, delta.block_number);
+        assert_eq!(reassembled.block_hash, delta.block_hash);
+        assert_eq!(reassembled.parent_hash, delta.parent_hash);
+        assert_eq!(reassembled.updates, delta.updates);
+    }
+
+    #[test]
+    fn test_chunk_reassembler_returns_none_before_terminating_chunk() {
+        let delta = BucketDelta {
+            block_number: 1,
+            block_hash: [0u8; 32],
+            parent_hash: [0u8; 32],
+            updates: (0..100).map(|i| (i, 1)).collect(),
+        };
+        let chunks = delta.to_chunks(16);
+        assert!(chunks.len() > 1);
+
+        let mut reassembler = ChunkReassembler::new();
+        assert!(reassembler.push(&chunks[0]).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_chunk_reassembler_rejects_frame_shorter_than_header() {
+        let mut reassembler = ChunkReassembler::new();
+        let err = reassembler.push(&[0u8]).unwrap_err();
+        assert!(matches!(err, ChunkReassemblyError::BadContinuation { .. }));
+    }
+
+    #[test]
+    fn test_chunk_reassembler_rejects_length_mismatch() {
+        let mut reassembler = ChunkReassembler::new();
+        // Header claims 10 payload bytes but only 2 are present.
+        let frame = vec![10u8, 0u8, 1u8, 2u8];
+        let err = reassembler.push(&frame).unwrap_err();
+        assert!(matches!(err, ChunkReassemblyError::BadContinuation { .. }));
+    }
+
+    #[test]
+    fn test_chunk_reassembler_rejects_malformed_reassembled_delta() {
+        // A single non-continuation chunk whose payload is too short to be
+        // a valid BucketDelta header.
+        let frame = encode_chunk(&[0u8; 4], false);
+        let mut reassembler = ChunkReassembler::new();
+        let err = reassembler.push(&frame).unwrap_err();
+        assert!(matches!(err, ChunkReassemblyError::Truncated(_)));
+    }
+
+    fn delta_for(block_number: u64) -> BucketDelta {
+        BucketDelta {
+            block_number,
+            block_hash: [0u8; 32],
+            parent_hash: [0u8; 32],
+            updates: vec![(0, 1)],
+        }
+    }
+
+    #[test]
+    fn test_delta_window_applies_in_order_arrivals_immediately() {
+        let mut window = DeltaWindow::new(0, 100);
+        window.insert(delta_for(0));
+        let ready = window.poll().unwrap();
+        assert_eq!(ready.len(), 1);
+        assert_eq!(ready[0].block_number, 0);
+        assert_eq!(window.next_expected(), 1);
+    }
+
+    #[test]
+    fn test_delta_window_buffers_until_gap_fills() {
+        let mut window = DeltaWindow::new(0, 100);
+        window.insert(delta_for(2));
+        window.insert(delta_for(1));
+        // block 0 still missing, nothing is ready yet
+        assert!(window.poll().unwrap().is_empty());
+        assert_eq!(window.next_expected(), 0);
+
+        window.insert(delta_for(0));
+        let ready = window.poll().unwrap();
+        let blocks: Vec<u64> = ready.iter().map(|d| d.block_number).collect();
+        assert_eq!(blocks, vec![0, 1, 2]);
+        assert_eq!(window.next_expected(), 3);
+    }
+
+    #[test]
+    fn test_delta_window_drops_already_applied_blocks() {
+        let mut window = DeltaWindow::new(5, 100);
+        window.insert(delta_for(3)); // already applied, should be dropped
+        assert!(window.poll().unwrap().is_empty());
+        assert_eq!(window.next_expected(), 5);
+    }
+
+    #[test]
+    fn test_delta_window_ignores_duplicate_inserts() {
+        let mut window = DeltaWindow::new(0, 100);
+        window.insert(BucketDelta {
+            block_number: 0,
+            block_hash: [0u8; 32],
+            parent_hash: [0u8; 32],
+            updates: vec![(1, 1)],
+        });
+        window.insert(BucketDelta {
+            block_number: 0,
+            block_hash: [0u8; 32],
+            parent_hash: [0u8; 32],
+            updates: vec![(2, 2)], // duplicate block_number, different payload
+        });
+        let ready = window.poll().unwrap();
+        assert_eq!(ready.len(), 1);
+        assert_eq!(ready[0].updates, vec![(1, 1)]); // first insert wins
+    }
+
+    #[test]
+    fn test_delta_window_signals_resync_beyond_max_gap() {
+        let mut window = DeltaWindow::new(0, 10);
+        window.insert(delta_for(50));
+        let err = window.poll().unwrap_err();
+        assert_eq!(err, ResyncNeeded { from: 0 });
+    }
+
+    #[test]
+    fn test_delta_window_resync_clears_stale_buffer_and_advances() {
+        let mut window = DeltaWindow::new(0, 10);
+        window.insert(delta_for(5));
+        window.insert(delta_for(50));
+        window.resync(49);
+        assert_eq!(window.next_expected(), 50);
+
+        let ready = window.poll().unwrap();
+        assert_eq!(ready.len(), 1);
+        assert_eq!(ready[0].block_number, 50);
+        assert_eq!(window.next_expected(), 51);
+    }
+
+    fn delta_with_chain(block_number: u64, block_hash: [u8; 32], parent_hash: [u8; 32]) -> BucketDelta {
+        BucketDelta {
+            block_number,
+            block_hash,
+            parent_hash,
+            updates: vec![(0, 1)],
+        }
+    }
+
+    #[test]
+    fn test_fork_tracker_allows_anything_with_no_history() {
+        let tracker = ForkTracker::new(8);
+        let delta = delta_with_chain(0, [0u8; 32], [9u8; 32]);
+        assert_eq!(tracker.check(&delta), Ok(()));
+    }
+
+    #[test]
+    fn test_fork_tracker_accepts_matching_parent_hash() {
+        let mut tracker = ForkTracker::new(8);
+        tracker.record(10, [10u8; 32]);
+        let delta = delta_with_chain(11, [11u8; 32], [10u8; 32]);
+        assert_eq!(tracker.check(&delta), Ok(()));
+    }
+
+    #[test]
+    fn test_fork_tracker_detects_fork_and_finds_ancestor_in_ring() {
+        let mut tracker = ForkTracker::new(8);
+        tracker.record(10, [10u8; 32]);
+        tracker.record(11, [11u8; 32]);
+        tracker.record(12, [12u8; 32]);
+
+        // Competing block 13 whose parent is 11, not 12 - a one-block reorg.
+        let delta = delta_with_chain(13, [13u8; 32], [11u8; 32]);
+        assert_eq!(
+            tracker.check(&delta),
+            Err(DeltaError::ForkDetected { ancestor_block: 11 })
+        );
+    }
+
+    #[test]
+    fn test_fork_tracker_falls_back_to_oldest_entry_when_ancestor_predates_ring() {
+        let mut tracker = ForkTracker::new(2);
+        tracker.record(10, [10u8; 32]);
+        tracker.record(11, [11u8; 32]);
+        tracker.record(12, [12u8; 32]); // evicts block 10
+
+        let delta = delta_with_chain(13, [13u8; 32], [99u8; 32]); // unknown parent
+        assert_eq!(
+            tracker.check(&delta),
+            Err(DeltaError::ForkDetected { ancestor_block: 11 })
+        );
+    }
+
+    #[test]
+    fn test_fork_tracker_rollback_to_truncates_ring() {
+        let mut tracker = ForkTracker::new(8);
+        tracker.record(10, [10u8; 32]);
+        tracker.record(11, [11u8; 32]);
+        tracker.record(12, [12u8; 32]);
+
+        tracker.rollback_to(11);
+        assert_eq!(tracker.head_hash(), Some([11u8; 32]));
+
+        let delta = delta_with_chain(12, [12u8; 32], [11u8; 32]);
+        assert_eq!(tracker.check(&delta), Ok(()));
+    }
 }
 
 /// Range-based delta file for efficient sync
@@ -399,14 +1192,33 @@ pub mod range_delta {
     /// Cumulative delta: merge multiple BucketDeltas into one
     ///
     /// When the same bucket appears in multiple deltas, keep only the latest count.
+    /// The merged delta represents the whole range as a single hash-chain hop:
+    /// `block_hash` comes from the newest input delta (highest `block_number`)
+    /// and `parent_hash` comes from the oldest (lowest `block_number`), so
+    /// `ForkTracker::check` can validate it the same way it would a single
+    /// block's delta.
     pub fn merge_deltas(deltas: &[BucketDelta]) -> BucketDelta {
         use std::collections::HashMap;
 
         let mut latest: HashMap<usize, u16> = HashMap::new();
-        let mut max_block = 0u64;
+        let mut newest: Option<&BucketDelta> = None;
+        let mut oldest: Option<&BucketDelta> = None;
 
         for delta in deltas {
-            max_block = max_block.max(delta.block_number);
+            let is_newest = match newest {
+                Some(n) => delta.block_number > n.block_number,
+                None => true,
+            };
+            if is_newest {
+                newest = Some(delta);
+            }
+            let is_oldest = match oldest {
+                Some(o) => delta.block_number < o.block_number,
+                None => true,
+            };
+            if is_oldest {
+                oldest = Some(delta);
+            }
             for &(bucket_id, count) in &delta.updates {
                 latest.insert(bucket_id, count);
             }
@@ -416,7 +1228,9 @@ pub mod range_delta {
         updates.sort_by_key(|(id, _)| *id);
 
         BucketDelta {
-            block_number: max_block,
+            block_number: newest.map(|d| d.block_number).unwrap_or(0),
+            block_hash: newest.map(|d| d.block_hash).unwrap_or([0u8; 32]),
+            parent_hash: oldest.map(|d| d.parent_hash).unwrap_or([0u8; 32]),
             updates,
         }
     }
@@ -442,15 +1256,21 @@ pub mod range_delta {
         fn test_merge_deltas() {
             let d1 = BucketDelta {
                 block_number: 100,
+                block_hash: [100u8; 32],
+                parent_hash: [99u8; 32],
                 updates: vec![(0, 10), (1, 20)],
             };
             let d2 = BucketDelta {
                 block_number: 101,
+                block_hash: [101u8; 32],
+                parent_hash: [100u8; 32],
                 updates: vec![(1, 25), (2, 30)], // bucket 1 updated
             };
 
             let merged = merge_deltas(&[d1, d2]);
             assert_eq!(merged.block_number, 101);
+            assert_eq!(merged.block_hash, [101u8; 32]); // from the newest delta
+            assert_eq!(merged.parent_hash, [99u8; 32]); // from the oldest delta
             assert_eq!(merged.updates.len(), 3);
             // Bucket 1 should have the latest value (25)
             assert!(merged.updates.contains(&(1, 25)));
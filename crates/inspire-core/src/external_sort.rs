@@ -0,0 +1,347 @@
+//! External (on-disk) k-way merge sort for fixed-size record streams.
+//!
+//! A full Ethereum state has billions of fixed-size entries - far more than
+//! fits in a `Vec` in memory at once. This module streams the input in
+//! bounded-size chunks, sorts each chunk in memory, and spills it to a temp
+//! file as a sorted "run". The runs are then merged with a binary min-heap
+//! keyed on each run's next entry, so only one entry per run is ever held in
+//! memory at a time. If there are more runs than can comfortably stay open
+//! at once, they're merged down in batches before the final pass.
+//!
+//! This is the algorithm behind `scripts/resort-state.rs`, lifted here so it
+//! can be tested and reused without going through the standalone script.
+
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+
+use tempfile::NamedTempFile;
+
+/// Maximum number of sorted runs merged in a single pass. Bounds the number
+/// of simultaneously open file descriptors well under typical OS limits,
+/// even when a database spills many thousands of runs.
+pub const MAX_OPEN_RUNS: usize = 256;
+
+/// Error performing an external merge sort.
+#[derive(Debug)]
+pub enum ExternalSortError {
+    Io(io::Error),
+    /// The number of entries actually read or written didn't match the
+    /// caller-supplied `entry_count` - a sign of a truncated input or a bug
+    /// in the merge itself, not something to silently paper over.
+    EntryCountMismatch { expected: u64, actual: u64 },
+}
+
+impl core::fmt::Display for ExternalSortError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ExternalSortError::Io(e) => write!(f, "I/O error during external sort: {e}"),
+            ExternalSortError::EntryCountMismatch { expected, actual } => write!(
+                f,
+                "external sort produced {actual} entries, expected {expected}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ExternalSortError {}
+
+impl From<io::Error> for ExternalSortError {
+    fn from(e: io::Error) -> Self {
+        ExternalSortError::Io(e)
+    }
+}
+
+/// Sort `entry_count` fixed-size `entry_size`-byte records read from `input`
+/// and write them in order to `output`, without ever holding more than
+/// `chunk_entries` records in memory at once.
+///
+/// `sort_key_of` derives the 32-byte key each record is ordered by; ties are
+/// broken by original (read) order, since [`Vec::sort_by`] is stable.
+///
+/// Returns the number of entries written, which always equals `entry_count`
+/// on success.
+pub fn external_merge_sort<R: Read, W: Write>(
+    mut input: R,
+    mut output: W,
+    entry_count: u64,
+    entry_size: usize,
+    chunk_entries: usize,
+    sort_key_of: impl Fn(&[u8]) -> [u8; 32],
+) -> Result<u64, ExternalSortError> {
+    assert!(chunk_entries > 0, "chunk_entries must be positive");
+
+    let mut runs = Vec::new();
+    let mut buf = vec![0u8; entry_size];
+    let mut remaining = entry_count;
+
+    while remaining > 0 {
+        let this_chunk = chunk_entries.min(remaining as usize);
+        let mut chunk: Vec<([u8; 32], Vec<u8>)> = Vec::with_capacity(this_chunk);
+
+        for _ in 0..this_chunk {
+            input.read_exact(&mut buf)?;
+            let key = sort_key_of(&buf);
+            chunk.push((key, buf.clone()));
+        }
+        chunk.sort_by(|a, b| a.0.cmp(&b.0));
+
+        runs.push(spill_run(&chunk)?);
+        remaining -= this_chunk as u64;
+    }
+
+    // Merge down in bounded-width batches until few enough runs remain open
+    // at once for the final pass.
+    while runs.len() > MAX_OPEN_RUNS {
+        let mut next_runs = Vec::new();
+        for batch in runs.chunks(MAX_OPEN_RUNS) {
+            let merged = NamedTempFile::new()?;
+            {
+                let mut writer = BufWriter::new(merged.reopen()?);
+                merge_runs(batch, entry_size, &mut writer)?;
+            }
+            next_runs.push(merged);
+        }
+        runs = next_runs;
+    }
+
+    let written = merge_runs(&runs, entry_size, &mut output)?;
+    output.flush()?;
+
+    if written != entry_count {
+        return Err(ExternalSortError::EntryCountMismatch {
+            expected: entry_count,
+            actual: written,
+        });
+    }
+    Ok(written)
+}
+
+/// Write a single already-sorted chunk to a fresh temp file, keyed data only
+/// (the key isn't re-derivable cheaply from `data` alone in the general
+/// case, so each run stores `key || data` per entry).
+fn spill_run(chunk: &[([u8; 32], Vec<u8>)]) -> io::Result<NamedTempFile> {
+    let run = NamedTempFile::new()?;
+    let mut writer = BufWriter::new(run.reopen()?);
+    for (key, data) in chunk {
+        writer.write_all(key)?;
+        writer.write_all(data)?;
+    }
+    writer.flush()?;
+    Ok(run)
+}
+
+/// Merge already-sorted runs via a binary min-heap over each run's next
+/// entry, writing the merged stream to `output`. Returns the number of
+/// entries written.
+fn merge_runs<W: Write>(
+    runs: &[NamedTempFile],
+    entry_size: usize,
+    output: &mut W,
+) -> Result<u64, ExternalSortError> {
+    struct RunCursor {
+        reader: BufReader<File>,
+    }
+
+    let mut cursors: Vec<RunCursor> = runs
+        .iter()
+        .map(|run| -> io::Result<RunCursor> {
+            Ok(RunCursor {
+                reader: BufReader::new(run.reopen()?),
+            })
+        })
+        .collect::<io::Result<_>>()?;
+
+    let mut heap: BinaryHeap<HeapEntry> = BinaryHeap::with_capacity(cursors.len());
+    for (run_index, cursor) in cursors.iter_mut().enumerate() {
+        if let Some(entry) = read_run_entry(&mut cursor.reader, entry_size)? {
+            heap.push(HeapEntry { run_index, ..entry });
+        }
+    }
+
+    let mut written = 0u64;
+    while let Some(top) = heap.pop() {
+        output.write_all(&top.data)?;
+        written += 1;
+
+        if let Some(entry) = read_run_entry(&mut cursors[top.run_index].reader, entry_size)? {
+            heap.push(HeapEntry {
+                run_index: top.run_index,
+                ..entry
+            });
+        }
+    }
+
+    Ok(written)
+}
+
+/// One run's next `key || data` entry, read lazily so at most one entry per
+/// run is ever resident in memory during the merge.
+struct HeapEntry {
+    sort_key: [u8; 32],
+    data: Vec<u8>,
+    run_index: usize,
+}
+
+impl Eq for HeapEntry {}
+
+impl PartialEq for HeapEntry {
+    fn eq(&self, other: &Self) -> bool {
+        self.sort_key == other.sort_key && self.run_index == other.run_index
+    }
+}
+
+impl Ord for HeapEntry {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Reversed so `BinaryHeap` (a max-heap) pops the *smallest* key;
+        // equal keys pop from the earliest run first, keeping ties in read
+        // order across runs.
+        other
+            .sort_key
+            .cmp(&self.sort_key)
+            .then_with(|| other.run_index.cmp(&self.run_index))
+    }
+}
+
+impl PartialOrd for HeapEntry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+fn read_run_entry(
+    reader: &mut BufReader<File>,
+    entry_size: usize,
+) -> io::Result<Option<HeapEntry>> {
+    let mut key = [0u8; 32];
+    match reader.read_exact(&mut key) {
+        Ok(()) => {}
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e),
+    }
+    let mut data = vec![0u8; entry_size];
+    reader.read_exact(&mut data)?;
+    Ok(Some(HeapEntry {
+        sort_key: key,
+        data,
+        run_index: 0,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key_of(entry: &[u8]) -> [u8; 32] {
+        let mut key = [0u8; 32];
+        key[28..32].copy_from_slice(&entry[0..4]);
+        key
+    }
+
+    fn entries(values: &[u32], entry_size: usize) -> Vec<u8> {
+        let mut out = Vec::with_capacity(values.len() * entry_size);
+        for v in values {
+            let mut entry = vec![0u8; entry_size];
+            entry[0..4].copy_from_slice(&v.to_be_bytes());
+            out.extend_from_slice(&entry);
+        }
+        out
+    }
+
+    #[test]
+    fn test_sorts_across_multiple_chunks() {
+        let entry_size = 16;
+        let values: Vec<u32> = (0..500).rev().collect(); // descending input
+        let input = entries(&values, entry_size);
+
+        let mut output = Vec::new();
+        let written = external_merge_sort(
+            &input[..],
+            &mut output,
+            values.len() as u64,
+            entry_size,
+            50, // force many small chunks/runs
+            key_of,
+        )
+        .unwrap();
+
+        assert_eq!(written, values.len() as u64);
+        let sorted_keys: Vec<u32> = output
+            .chunks(entry_size)
+            .map(|chunk| u32::from_be_bytes(chunk[0..4].try_into().unwrap()))
+            .collect();
+        let mut expected = values.clone();
+        expected.sort_unstable();
+        assert_eq!(sorted_keys, expected);
+    }
+
+    #[test]
+    fn test_single_chunk_no_spill_needed() {
+        let entry_size = 8;
+        let values = vec![3u32, 1, 4, 1, 5, 9, 2, 6];
+        let input = entries(&values, entry_size);
+
+        let mut output = Vec::new();
+        external_merge_sort(
+            &input[..],
+            &mut output,
+            values.len() as u64,
+            entry_size,
+            1_000,
+            key_of,
+        )
+        .unwrap();
+
+        let sorted_keys: Vec<u32> = output
+            .chunks(entry_size)
+            .map(|chunk| u32::from_be_bytes(chunk[0..4].try_into().unwrap()))
+            .collect();
+        let mut expected = values;
+        expected.sort_unstable();
+        assert_eq!(sorted_keys, expected);
+    }
+
+    #[test]
+    fn test_bounded_run_width_forces_multi_pass_merge() {
+        let entry_size = 8;
+        // chunk_entries=1 turns every entry into its own run, so this
+        // produces (MAX_OPEN_RUNS + 44) runs - genuinely more than
+        // MAX_OPEN_RUNS, exercising the `while runs.len() > MAX_OPEN_RUNS`
+        // merge-down pass at line 96 rather than just the single-pass merge
+        // the other tests already cover.
+        let values: Vec<u32> = (0..(MAX_OPEN_RUNS as u32 + 44)).rev().collect();
+        let input = entries(&values, entry_size);
+
+        let mut output = Vec::new();
+        let written = external_merge_sort(
+            &input[..],
+            &mut output,
+            values.len() as u64,
+            entry_size,
+            1,
+            key_of,
+        )
+        .unwrap();
+
+        assert_eq!(written, values.len() as u64);
+        let sorted_keys: Vec<u32> = output
+            .chunks(entry_size)
+            .map(|chunk| u32::from_be_bytes(chunk[0..4].try_into().unwrap()))
+            .collect();
+        let mut expected = values;
+        expected.sort_unstable();
+        assert_eq!(sorted_keys, expected);
+    }
+
+    #[test]
+    fn test_entry_count_mismatch_is_detected() {
+        // Fewer entries in the input than claimed -> read_exact hits EOF.
+        let entry_size = 8;
+        let input = entries(&[1, 2, 3], entry_size);
+
+        let mut output = Vec::new();
+        let result = external_merge_sort(&input[..], &mut output, 10, entry_size, 2, key_of);
+        assert!(result.is_err());
+    }
+}
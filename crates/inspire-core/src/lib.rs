@@ -43,24 +43,43 @@ mod balance;
 pub mod bucket_index;
 mod config;
 mod error;
+pub mod external_sort;
 mod indexing;
 mod lane;
 mod manifest;
+pub mod nodemap;
 mod params;
 mod routing;
+pub mod segment;
 pub mod state_format;
+pub mod stem_checkpoint;
+pub mod ubt;
+pub mod ubt_commitment;
 
 pub use balance::{BalanceDbMetadata, BalanceRecord, BALANCE_RECORD_SIZE};
+pub use bucket_index::{
+    build_fenwick, compute_bucket_id, compute_cumulative, compute_slot_starts, compute_slot_tag,
+    fenwick_prefix_sum, fenwick_update, slots_for_count, BucketDelta, BucketDeltaError,
+    BucketRange, ChunkReassembler, ChunkReassemblyError, DeltaError, DeltaWindow, ForkTracker,
+    ResyncNeeded, DELTA_FORMAT_VERSION, DEFAULT_MAX_SEARCH, DEFAULT_SLOT_LOAD_FACTOR,
+    MAX_CHUNK_LEN, NUM_BUCKETS,
+};
 pub use config::{TwoLaneConfig, PROTOCOL_VERSION};
 pub use error::Error;
+pub use external_sort::{external_merge_sort, ExternalSortError, MAX_OPEN_RUNS};
 pub use indexing::{cold_index, hot_index, slot_to_offset};
 pub use lane::Lane;
 pub use manifest::{HotContract, HotLaneManifest};
+pub use nodemap::NodeMap;
 pub use params::{CrsMetadata, ParamsVersionError, PirParams, PIR_PARAMS, PIR_PARAMS_VERSION};
 pub use routing::{LaneRouter, QueryTarget, RoutedQuery};
+pub use segment::{compute_storage_value, Codec, Segment, SegmentBuilder, SegmentError};
 pub use state_format::{
     StateFormatError, StateHeader, StorageEntry, STATE_ENTRY_SIZE, STATE_HEADER_SIZE, STATE_MAGIC,
 };
+pub use stem_checkpoint::{CheckpointedStemTable, RewindError};
+pub use ubt::{Stem, TreeIndex, TreeKey};
+pub use ubt_commitment::{InclusionProof, UbtCommitment};
 
 pub type Result<T> = std::result::Result<T, Error>;
 
@@ -0,0 +1,666 @@
+//! Persistent nibble-radix nodemap for stem -> db-index lookups
+//!
+//! `ubt::compute_db_index` does a `binary_search_by_key` over a flat,
+//! fully-materialized `&[(Stem, u64)]` table, which forces the whole stem
+//! offset table to be rebuilt and re-sorted in memory whenever new stems
+//! appear. This module provides an alternative keyed on the nibbles of the
+//! 31-byte [`Stem`]: a 16-ary radix tree modeled on Mercurial's append-only
+//! "nodemap", where each internal node is a block of 16 slots that is either
+//! empty, a pointer to a child block, or a leaf holding a stem's
+//! `(full_stem, start_index)`.
+//!
+//! ## Persistence
+//!
+//! Every insert is copy-on-write: the block at the changed slot, and every
+//! ancestor block on the path to it, is appended as a brand-new block rather
+//! than mutated in place, so [`NodeMap::flush`] only ever appends newly
+//! created blocks to the backing file before writing the new root offset
+//! last. Existing on-disk data is never rewritten, so a crash mid-flush
+//! leaves the previous root (and the tree it describes) intact.
+//!
+//! ## Sealing
+//!
+//! [`NodeMap::seal_stem`] replaces a stem's leaf with its retained
+//! `UbtCommitment` subtree hash instead of removing it outright: the stem
+//! still occupies its slot (so the tree shape and any sibling stems are
+//! undisturbed), but [`NodeMap::lookup`] - and therefore `compute_db_index`
+//! - now treats it as absent, since its individual values have been pruned
+//! from the queryable PIR database. The retained hash lets the commitment
+//! layer still fold the sealed stem's subtree into the trie root via
+//! [`NodeMap::lookup_sealed`].
+
+use crate::ubt::{compute_stem, get_subindex, Stem, TreeIndex};
+use crate::Address;
+use std::io::Write;
+use std::path::Path;
+
+/// Number of children per radix block (one per nibble value).
+const NUM_SLOTS: usize = 16;
+/// Tag + 31-byte stem + 32-byte payload (a `u64` start_index for a live leaf,
+/// or a retained commitment hash for a sealed one) is the widest slot.
+const SLOT_SIZE: usize = 1 + 31 + 32;
+/// On-disk size of one block (16 fixed-width slots).
+const BLOCK_SIZE: usize = NUM_SLOTS * SLOT_SIZE;
+/// Trailing bytes holding the root block index, written after all blocks.
+const ROOT_TRAILER_SIZE: usize = 8;
+
+const SLOT_TAG_EMPTY: u8 = 0;
+const SLOT_TAG_CHILD: u8 = 1;
+const SLOT_TAG_LEAF: u8 = 2;
+const SLOT_TAG_SEALED: u8 = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Slot {
+    Empty,
+    Child(u64),
+    Leaf(Stem, u64),
+    /// A stem whose individual values have been pruned from the queryable
+    /// PIR set; the `[u8; 32]` is its retained `UbtCommitment` subtree hash.
+    Sealed(Stem, [u8; 32]),
+}
+
+#[derive(Debug, Clone)]
+struct Block {
+    slots: [Slot; NUM_SLOTS],
+}
+
+impl Block {
+    fn empty() -> Self {
+        Self {
+            slots: [Slot::Empty; NUM_SLOTS],
+        }
+    }
+
+    fn to_bytes(&self) -> [u8; BLOCK_SIZE] {
+        let mut out = [0u8; BLOCK_SIZE];
+        for (i, slot) in self.slots.iter().enumerate() {
+            let base = i * SLOT_SIZE;
+            match slot {
+                Slot::Empty => out[base] = SLOT_TAG_EMPTY,
+                Slot::Child(block_idx) => {
+                    out[base] = SLOT_TAG_CHILD;
+                    out[base + 1..base + 9].copy_from_slice(&block_idx.to_le_bytes());
+                }
+                Slot::Leaf(stem, start_index) => {
+                    out[base] = SLOT_TAG_LEAF;
+                    out[base + 1..base + 32].copy_from_slice(stem);
+                    out[base + 32..base + 40].copy_from_slice(&start_index.to_le_bytes());
+                }
+                Slot::Sealed(stem, commitment_hash) => {
+                    out[base] = SLOT_TAG_SEALED;
+                    out[base + 1..base + 32].copy_from_slice(stem);
+                    out[base + 32..base + 64].copy_from_slice(commitment_hash);
+                }
+            }
+        }
+        out
+    }
+
+    fn from_bytes(data: &[u8]) -> Self {
+        let mut slots = [Slot::Empty; NUM_SLOTS];
+        for (i, slot) in slots.iter_mut().enumerate() {
+            let base = i * SLOT_SIZE;
+            *slot = match data[base] {
+                SLOT_TAG_CHILD => {
+                    let block_idx = u64::from_le_bytes(data[base + 1..base + 9].try_into().unwrap());
+                    Slot::Child(block_idx)
+                }
+                SLOT_TAG_LEAF => {
+                    let stem: Stem = data[base + 1..base + 32].try_into().unwrap();
+                    let start_index =
+                        u64::from_le_bytes(data[base + 32..base + 40].try_into().unwrap());
+                    Slot::Leaf(stem, start_index)
+                }
+                SLOT_TAG_SEALED => {
+                    let stem: Stem = data[base + 1..base + 32].try_into().unwrap();
+                    let commitment_hash: [u8; 32] = data[base + 32..base + 64].try_into().unwrap();
+                    Slot::Sealed(stem, commitment_hash)
+                }
+                _ => Slot::Empty,
+            };
+        }
+        Self { slots }
+    }
+}
+
+/// Extract the `pos`-th nibble (4 bits) of a 31-byte stem, most significant
+/// nibble first.
+fn nibble_at(stem: &Stem, pos: usize) -> usize {
+    let byte = stem[pos / 2];
+    (if pos % 2 == 0 { byte >> 4 } else { byte & 0x0f }) as usize
+}
+
+/// A 16-ary radix tree mapping stems to their PIR database start index.
+///
+/// See the module docs for the append-only persistence model.
+#[derive(Debug, Clone)]
+pub struct NodeMap {
+    /// All blocks ever allocated. Index `i` is this block's position once
+    /// persisted (byte offset `i * BLOCK_SIZE` in the backing file).
+    blocks: Vec<Block>,
+    /// Index of the current root block.
+    root: u64,
+    /// Number of blocks already written by a previous `flush`.
+    flushed: usize,
+}
+
+impl Default for NodeMap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NodeMap {
+    /// An empty nodemap with a single empty root block.
+    pub fn new() -> Self {
+        Self {
+            blocks: vec![Block::empty()],
+            root: 0,
+            flushed: 0,
+        }
+    }
+
+    /// Insert (or update) a stem's starting database index.
+    ///
+    /// Descends one nibble of `stem` at a time; on reaching an empty slot the
+    /// leaf is placed there, on reaching a leaf for the same stem its
+    /// `start_index` is updated, and on reaching a leaf for a *different*
+    /// stem (an ambiguous shared prefix) both stems are pushed down via
+    /// [`NodeMap::branch`] until their nibbles diverge. Every block touched
+    /// along the way is copied rather than mutated, so already-flushed blocks
+    /// are never rewritten.
+    pub fn insert_stem(&mut self, stem: Stem, start_index: u64) {
+        self.root = self.insert_at(self.root, &stem, 0, start_index);
+    }
+
+    fn insert_at(&mut self, block_idx: u64, stem: &Stem, nibble_pos: usize, start_index: u64) -> u64 {
+        let nibble = nibble_at(stem, nibble_pos);
+        let mut block = self.blocks[block_idx as usize].clone();
+
+        block.slots[nibble] = match block.slots[nibble] {
+            Slot::Empty => Slot::Leaf(*stem, start_index),
+            Slot::Leaf(existing_stem, _) if existing_stem == *stem => Slot::Leaf(*stem, start_index),
+            // A sealed stem is immutable: re-inserting it is a no-op rather
+            // than un-sealing it.
+            Slot::Sealed(existing_stem, hash) if existing_stem == *stem => {
+                Slot::Sealed(existing_stem, hash)
+            }
+            existing @ (Slot::Leaf(..) | Slot::Sealed(..)) => {
+                let child = self.branch(existing, Slot::Leaf(*stem, start_index), nibble_pos + 1);
+                Slot::Child(child)
+            }
+            Slot::Child(child_idx) => {
+                Slot::Child(self.insert_at(child_idx, stem, nibble_pos + 1, start_index))
+            }
+        };
+
+        self.push_block(block)
+    }
+
+    /// The stem a `Leaf` or `Sealed` slot is keyed on.
+    fn slot_stem(slot: &Slot) -> Stem {
+        match slot {
+            Slot::Leaf(stem, _) | Slot::Sealed(stem, _) => *stem,
+            _ => unreachable!("branch is only called with Leaf or Sealed slots"),
+        }
+    }
+
+    /// Build a fresh chain of blocks separating two differing `Leaf`/`Sealed`
+    /// slots from `nibble_pos` onward, recursing one nibble deeper for as
+    /// long as they still agree (the ambiguous-prefix case), and returning
+    /// the index of the top block in the new chain.
+    fn branch(&mut self, slot_a: Slot, slot_b: Slot, nibble_pos: usize) -> u64 {
+        let nibble_a = nibble_at(&Self::slot_stem(&slot_a), nibble_pos);
+        let nibble_b = nibble_at(&Self::slot_stem(&slot_b), nibble_pos);
+
+        let mut block = Block::empty();
+        if nibble_a == nibble_b {
+            let child = self.branch(slot_a, slot_b, nibble_pos + 1);
+            block.slots[nibble_a] = Slot::Child(child);
+        } else {
+            block.slots[nibble_a] = slot_a;
+            block.slots[nibble_b] = slot_b;
+        }
+
+        self.push_block(block)
+    }
+
+    fn push_block(&mut self, block: Block) -> u64 {
+        let idx = self.blocks.len() as u64;
+        self.blocks.push(block);
+        idx
+    }
+
+    /// Remove a stem, if present. Like insertion, this copies rather than
+    /// mutates every block on the path to the removed leaf, so already
+    /// flushed blocks are never rewritten. Returns whether `stem` was found.
+    pub fn remove(&mut self, stem: &Stem) -> bool {
+        let (new_root, removed) = self.remove_at(self.root, stem, 0);
+        if removed {
+            self.root = new_root;
+        }
+        removed
+    }
+
+    fn remove_at(&mut self, block_idx: u64, stem: &Stem, nibble_pos: usize) -> (u64, bool) {
+        let nibble = nibble_at(stem, nibble_pos);
+        let mut block = self.blocks[block_idx as usize].clone();
+
+        let removed = match block.slots[nibble] {
+            Slot::Empty => false,
+            Slot::Leaf(leaf_stem, _) | Slot::Sealed(leaf_stem, _) => {
+                if leaf_stem == *stem {
+                    block.slots[nibble] = Slot::Empty;
+                    true
+                } else {
+                    false
+                }
+            }
+            Slot::Child(child_idx) => {
+                let (new_child, removed) = self.remove_at(child_idx, stem, nibble_pos + 1);
+                if removed {
+                    block.slots[nibble] = Slot::Child(new_child);
+                }
+                removed
+            }
+        };
+
+        if removed {
+            (self.push_block(block), true)
+        } else {
+            (block_idx, false)
+        }
+    }
+
+    /// Look up a stem's starting database index, descending one nibble at a
+    /// time until a leaf or an empty slot is reached. Returns `None` if the
+    /// reached leaf's full stem does not match (the shared-prefix,
+    /// different-stem case) or if the stem has been [`sealed`](Self::seal_stem)
+    /// - a sealed stem's values are no longer part of the queryable PIR set.
+    pub fn lookup(&self, stem: &Stem) -> Option<u64> {
+        let mut block_idx = self.root;
+        let mut nibble_pos = 0;
+
+        loop {
+            let nibble = nibble_at(stem, nibble_pos);
+            match self.blocks[block_idx as usize].slots[nibble] {
+                Slot::Empty => return None,
+                Slot::Leaf(leaf_stem, start_index) => {
+                    return (leaf_stem == *stem).then_some(start_index);
+                }
+                Slot::Sealed(_, _) => return None,
+                Slot::Child(child_idx) => {
+                    block_idx = child_idx;
+                    nibble_pos += 1;
+                }
+            }
+        }
+    }
+
+    /// Seal a stem: replace its leaf with its retained `UbtCommitment`
+    /// subtree hash so [`NodeMap::lookup`] (and therefore `compute_db_index`)
+    /// treats it as absent, while [`NodeMap::lookup_sealed`] can still
+    /// recover the hash for folding into the commitment root. A no-op,
+    /// returning `false`, if `stem` is absent or already sealed.
+    pub fn seal_stem(&mut self, stem: &Stem, commitment_hash: [u8; 32]) -> bool {
+        let (new_root, sealed) = self.seal_at(self.root, stem, 0, commitment_hash);
+        if sealed {
+            self.root = new_root;
+        }
+        sealed
+    }
+
+    fn seal_at(
+        &mut self,
+        block_idx: u64,
+        stem: &Stem,
+        nibble_pos: usize,
+        commitment_hash: [u8; 32],
+    ) -> (u64, bool) {
+        let nibble = nibble_at(stem, nibble_pos);
+        let mut block = self.blocks[block_idx as usize].clone();
+
+        let sealed = match block.slots[nibble] {
+            Slot::Leaf(leaf_stem, _) if leaf_stem == *stem => {
+                block.slots[nibble] = Slot::Sealed(*stem, commitment_hash);
+                true
+            }
+            Slot::Child(child_idx) => {
+                let (new_child, sealed) = self.seal_at(child_idx, stem, nibble_pos + 1, commitment_hash);
+                if sealed {
+                    block.slots[nibble] = Slot::Child(new_child);
+                }
+                sealed
+            }
+            _ => false,
+        };
+
+        if sealed {
+            (self.push_block(block), true)
+        } else {
+            (block_idx, false)
+        }
+    }
+
+    /// Look up a sealed stem's retained commitment hash. Returns `None` if
+    /// `stem` is absent or present but not sealed.
+    pub fn lookup_sealed(&self, stem: &Stem) -> Option<[u8; 32]> {
+        let mut block_idx = self.root;
+        let mut nibble_pos = 0;
+
+        loop {
+            let nibble = nibble_at(stem, nibble_pos);
+            match self.blocks[block_idx as usize].slots[nibble] {
+                Slot::Empty | Slot::Leaf(_, _) => return None,
+                Slot::Sealed(leaf_stem, hash) => return (leaf_stem == *stem).then_some(hash),
+                Slot::Child(child_idx) => {
+                    block_idx = child_idx;
+                    nibble_pos += 1;
+                }
+            }
+        }
+    }
+
+    /// Append all blocks created since the last flush, then write the
+    /// current root block index as an 8-byte little-endian trailer - last,
+    /// so a reader sees a consistent root only once every block it could
+    /// point to has already landed on disk.
+    pub fn flush<W: Write>(&mut self, writer: &mut W) -> std::io::Result<()> {
+        for block in &self.blocks[self.flushed..] {
+            writer.write_all(&block.to_bytes())?;
+        }
+        writer.write_all(&self.root.to_le_bytes())?;
+        writer.flush()?;
+        self.flushed = self.blocks.len();
+        Ok(())
+    }
+
+    /// Load a nodemap previously written by [`NodeMap::flush`]: fixed-size
+    /// blocks followed by an 8-byte root block index.
+    pub fn load(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let data = std::fs::read(path)?;
+        Self::decode(&data)
+    }
+
+    /// Decode a nodemap from an in-memory buffer with the same layout as
+    /// [`NodeMap::flush`] writes: fixed-size blocks followed by an 8-byte
+    /// root block index.
+    pub fn decode(data: &[u8]) -> std::io::Result<Self> {
+        if data.len() < ROOT_TRAILER_SIZE {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::UnexpectedEof,
+                "nodemap file too small for root trailer",
+            ));
+        }
+
+        let trailer_pos = data.len() - ROOT_TRAILER_SIZE;
+        let root = u64::from_le_bytes(data[trailer_pos..].try_into().unwrap());
+
+        let num_blocks = trailer_pos / BLOCK_SIZE;
+        let mut blocks = Vec::with_capacity(num_blocks);
+        for i in 0..num_blocks {
+            let start = i * BLOCK_SIZE;
+            blocks.push(Block::from_bytes(&data[start..start + BLOCK_SIZE]));
+        }
+
+        Ok(Self {
+            blocks,
+            root,
+            flushed: num_blocks,
+        })
+    }
+
+    /// Number of blocks allocated (for tests and capacity planning).
+    pub fn block_count(&self) -> usize {
+        self.blocks.len()
+    }
+}
+
+/// `compute_db_index` variant that consults a [`NodeMap`] instead of a flat,
+/// sorted stem offset table.
+pub fn compute_db_index(address: &Address, tree_index: &TreeIndex, nodemap: &NodeMap) -> Option<u64> {
+    let stem = compute_stem(address, tree_index);
+    let subindex = get_subindex(tree_index) as u64;
+    nodemap.lookup(&stem).map(|start| start + subindex)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stem_from_byte(b: u8) -> Stem {
+        [b; 31]
+    }
+
+    #[test]
+    fn test_insert_and_lookup_single_stem() {
+        let mut map = NodeMap::new();
+        let stem = stem_from_byte(0x42);
+        map.insert_stem(stem, 1000);
+
+        assert_eq!(map.lookup(&stem), Some(1000));
+    }
+
+    #[test]
+    fn test_lookup_missing_stem_returns_none() {
+        let map = NodeMap::new();
+        assert_eq!(map.lookup(&stem_from_byte(0x01)), None);
+    }
+
+    #[test]
+    fn test_update_existing_stem() {
+        let mut map = NodeMap::new();
+        let stem = stem_from_byte(0x7f);
+        map.insert_stem(stem, 10);
+        map.insert_stem(stem, 20);
+
+        assert_eq!(map.lookup(&stem), Some(20));
+    }
+
+    #[test]
+    fn test_shared_prefix_stems_disambiguate() {
+        // Two stems sharing every nibble except the very last one.
+        let mut stem_a = [0u8; 31];
+        let mut stem_b = [0u8; 31];
+        stem_a[30] = 0x0a;
+        stem_b[30] = 0x0b;
+
+        let mut map = NodeMap::new();
+        map.insert_stem(stem_a, 100);
+        map.insert_stem(stem_b, 200);
+
+        assert_eq!(map.lookup(&stem_a), Some(100));
+        assert_eq!(map.lookup(&stem_b), Some(200));
+    }
+
+    #[test]
+    fn test_stems_differing_only_in_first_nibble() {
+        // Stems differing only in their very first nibble should still
+        // resolve correctly (exercises a shallow branch).
+        let mut stem_a = [0x11u8; 31];
+        let mut stem_b = stem_a;
+        stem_b[0] = 0x21;
+
+        let mut map = NodeMap::new();
+        map.insert_stem(stem_a, 1);
+        map.insert_stem(stem_b, 2);
+
+        assert_eq!(map.lookup(&stem_a), Some(1));
+        assert_eq!(map.lookup(&stem_b), Some(2));
+
+        // Mutate stem_a in place to double check no aliasing between leaves
+        stem_a[30] = 0xff;
+        assert_eq!(map.lookup(&stem_a), None);
+    }
+
+    #[test]
+    fn test_flush_and_load_roundtrip() {
+        let mut map = NodeMap::new();
+        for i in 0u8..20 {
+            map.insert_stem(stem_from_byte(i), 1000 + i as u64);
+        }
+
+        let mut buf = Vec::new();
+        map.flush(&mut buf).unwrap();
+
+        let loaded = NodeMap::decode(&buf).unwrap();
+        for i in 0u8..20 {
+            assert_eq!(
+                loaded.lookup(&stem_from_byte(i)),
+                Some(1000 + i as u64),
+                "stem {i}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_sealed_slot_survives_flush_and_load_roundtrip() {
+        let mut map = NodeMap::new();
+        let stem = stem_from_byte(0x09);
+        map.insert_stem(stem, 1000);
+        map.seal_stem(&stem, [0x77u8; 32]);
+
+        let mut buf = Vec::new();
+        map.flush(&mut buf).unwrap();
+
+        let loaded = NodeMap::decode(&buf).unwrap();
+        assert_eq!(loaded.lookup(&stem), None);
+        assert_eq!(loaded.lookup_sealed(&stem), Some([0x77u8; 32]));
+    }
+
+    #[test]
+    fn test_flush_only_appends_new_blocks() {
+        let mut map = NodeMap::new();
+        map.insert_stem(stem_from_byte(1), 1);
+
+        let mut first = Vec::new();
+        map.flush(&mut first).unwrap();
+        let first_len = first.len();
+
+        map.insert_stem(stem_from_byte(2), 2);
+        let mut second = Vec::new();
+        map.flush(&mut second).unwrap();
+
+        // The second flush only contains blocks/root added since the first.
+        assert!(second.len() < first_len + BLOCK_SIZE);
+        assert!(!second.is_empty());
+    }
+
+    #[test]
+    fn test_compute_db_index_via_nodemap() {
+        let address = [0x42u8; 20];
+        let tree_index = crate::ubt::compute_storage_tree_index(&[0u8; 32]); // slot 0
+        let stem = compute_stem(&address, &tree_index);
+
+        let mut map = NodeMap::new();
+        map.insert_stem(stem, 1000);
+
+        let index = compute_db_index(&address, &tree_index, &map);
+        // slot 0 has subindex 64, so index = 1000 + 64
+        assert_eq!(index, Some(1000 + 64));
+    }
+
+    #[test]
+    fn test_compute_db_index_not_found() {
+        let address = [0x42u8; 20];
+        let tree_index = [0x01u8; 32];
+        let map = NodeMap::new();
+
+        assert_eq!(compute_db_index(&address, &tree_index, &map), None);
+    }
+
+    #[test]
+    fn test_remove_present_stem() {
+        let mut map = NodeMap::new();
+        let stem = stem_from_byte(0x55);
+        map.insert_stem(stem, 42);
+
+        assert!(map.remove(&stem));
+        assert_eq!(map.lookup(&stem), None);
+    }
+
+    #[test]
+    fn test_remove_missing_stem_returns_false() {
+        let mut map = NodeMap::new();
+        assert!(!map.remove(&stem_from_byte(0x01)));
+    }
+
+    #[test]
+    fn test_remove_does_not_disturb_sibling_stems() {
+        let mut stem_a = [0u8; 31];
+        let mut stem_b = [0u8; 31];
+        stem_a[30] = 0x0a;
+        stem_b[30] = 0x0b;
+
+        let mut map = NodeMap::new();
+        map.insert_stem(stem_a, 100);
+        map.insert_stem(stem_b, 200);
+
+        assert!(map.remove(&stem_a));
+        assert_eq!(map.lookup(&stem_a), None);
+        assert_eq!(map.lookup(&stem_b), Some(200));
+    }
+
+    #[test]
+    fn test_seal_stem_hides_it_from_lookup_but_keeps_the_hash() {
+        let mut map = NodeMap::new();
+        let stem = stem_from_byte(0x42);
+        map.insert_stem(stem, 1000);
+
+        let commitment_hash = [0xabu8; 32];
+        assert!(map.seal_stem(&stem, commitment_hash));
+
+        assert_eq!(map.lookup(&stem), None);
+        assert_eq!(map.lookup_sealed(&stem), Some(commitment_hash));
+    }
+
+    #[test]
+    fn test_seal_missing_stem_is_a_no_op() {
+        let mut map = NodeMap::new();
+        assert!(!map.seal_stem(&stem_from_byte(0x01), [0u8; 32]));
+        assert_eq!(map.lookup_sealed(&stem_from_byte(0x01)), None);
+    }
+
+    #[test]
+    fn test_sealing_is_immutable_to_reinsertion() {
+        let mut map = NodeMap::new();
+        let stem = stem_from_byte(0x7f);
+        map.insert_stem(stem, 10);
+        map.seal_stem(&stem, [0xcdu8; 32]);
+
+        // Re-inserting a sealed stem must not un-seal it.
+        map.insert_stem(stem, 20);
+
+        assert_eq!(map.lookup(&stem), None);
+        assert_eq!(map.lookup_sealed(&stem), Some([0xcdu8; 32]));
+    }
+
+    #[test]
+    fn test_seal_does_not_disturb_sibling_stems() {
+        let mut stem_a = [0u8; 31];
+        let mut stem_b = [0u8; 31];
+        stem_a[30] = 0x0a;
+        stem_b[30] = 0x0b;
+
+        let mut map = NodeMap::new();
+        map.insert_stem(stem_a, 100);
+        map.insert_stem(stem_b, 200);
+
+        assert!(map.seal_stem(&stem_a, [0x11u8; 32]));
+        assert_eq!(map.lookup(&stem_a), None);
+        assert_eq!(map.lookup(&stem_b), Some(200));
+    }
+
+    #[test]
+    fn test_compute_db_index_skips_sealed_stem() {
+        let address = [0x42u8; 20];
+        let tree_index = crate::ubt::compute_storage_tree_index(&[0u8; 32]); // slot 0
+        let stem = compute_stem(&address, &tree_index);
+
+        let mut map = NodeMap::new();
+        map.insert_stem(stem, 1000);
+        map.seal_stem(&stem, [0x99u8; 32]);
+
+        assert_eq!(compute_db_index(&address, &tree_index, &map), None);
+    }
+}
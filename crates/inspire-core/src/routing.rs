@@ -2,6 +2,7 @@
 
 use crate::{Address, HotLaneManifest, Lane, StorageKey};
 use crate::indexing::{hot_index, cold_index};
+use metrics::{counter, gauge};
 use std::collections::HashSet;
 
 /// Routes queries to the appropriate lane based on contract address
@@ -20,6 +21,8 @@ impl LaneRouter {
     /// Create a router with known cold lane total entries
     pub fn with_cold_entries(manifest: HotLaneManifest, cold_total_entries: u64) -> Self {
         let hot_addresses = manifest.address_set();
+        gauge!("lane_router_hot_contract_count").set(hot_addresses.len() as f64);
+        gauge!("lane_router_cold_total_entries").set(cold_total_entries as f64);
         Self {
             hot_addresses,
             manifest,
@@ -30,15 +33,25 @@ impl LaneRouter {
     /// Set the cold lane total entries (for cold lane index calculation)
     pub fn set_cold_entries(&mut self, total: u64) {
         self.cold_total_entries = total;
+        gauge!("lane_router_cold_total_entries").set(total as f64);
     }
 
-    /// Route a query to the appropriate lane
+    /// Route a query to the appropriate lane, recording a per-lane hit
+    /// counter (`lane_router_hits_total{lane="hot"|"cold"}`) so operators
+    /// can see the hot/cold routing ratio.
     pub fn route(&self, contract: &Address) -> Lane {
-        if self.hot_addresses.contains(contract) {
+        let lane = if self.hot_addresses.contains(contract) {
             Lane::Hot
         } else {
             Lane::Cold
+        };
+
+        match lane {
+            Lane::Hot => counter!("lane_router_hits_total", "lane" => "hot").increment(1),
+            Lane::Cold => counter!("lane_router_hits_total", "lane" => "cold").increment(1),
         }
+
+        lane
     }
 
     /// Get the index within the hot lane database for a (contract, slot) pair.
@@ -63,6 +76,33 @@ impl LaneRouter {
         cold_index(contract, slot, self.cold_total_entries)
     }
 
+    /// Resolve many `(contract, slot)` targets at once, computing each
+    /// one's lane and in-lane index. Lets a caller with several targets
+    /// (e.g. a wallet's balance, allowance, and nonce) group the results
+    /// by lane and issue one batched PIR pass per lane instead of one
+    /// round trip per target.
+    ///
+    /// A target with no resolvable index for its lane (see
+    /// `get_hot_index`/`get_cold_index`) is omitted from the result, same
+    /// as a `None` return from those single-target lookups.
+    pub fn route_batch(&self, targets: &[QueryTarget]) -> Vec<RoutedQuery> {
+        targets
+            .iter()
+            .filter_map(|target| {
+                let lane = self.route(&target.contract);
+                let index = match lane {
+                    Lane::Hot => self.get_hot_index(&target.contract, &target.slot),
+                    Lane::Cold => self.get_cold_index(&target.contract, &target.slot),
+                }?;
+                Some(RoutedQuery {
+                    target: target.clone(),
+                    lane,
+                    index,
+                })
+            })
+            .collect()
+    }
+
     /// Get the manifest
     pub fn manifest(&self) -> &HotLaneManifest {
         &self.manifest
@@ -189,6 +229,29 @@ mod tests {
         assert_eq!(router.get_cold_index(&contract, &slot), None);
     }
 
+    #[test]
+    fn test_route_batch_groups_by_lane() {
+        let router = LaneRouter::with_cold_entries(create_test_manifest(), 1_000_000_000);
+        let targets = vec![
+            QueryTarget::new([0x11u8; 20], [0x42u8; 32]), // hot (USDC)
+            QueryTarget::new([0x33u8; 20], [0x44u8; 32]), // cold (unknown contract)
+        ];
+
+        let routed = router.route_batch(&targets);
+
+        assert_eq!(routed.len(), 2);
+        assert_eq!(routed[0].lane, Lane::Hot);
+        assert_eq!(routed[1].lane, Lane::Cold);
+    }
+
+    #[test]
+    fn test_route_batch_omits_unresolvable_cold_target() {
+        let router = LaneRouter::new(create_test_manifest()); // cold_total_entries == 0
+        let targets = vec![QueryTarget::new([0x33u8; 20], [0x44u8; 32])];
+
+        assert!(router.route_batch(&targets).is_empty());
+    }
+
     #[test]
     fn test_cold_index_different_inputs() {
         let router = LaneRouter::with_cold_entries(create_test_manifest(), 1_000_000_000);
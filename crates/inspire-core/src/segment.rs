@@ -0,0 +1,461 @@
+//! Compressed, checksummed block-segment format for the tree_key-ordered PIR database
+//!
+//! [`crate::ubt`] documents that "the PIR database must be ordered by
+//! tree_key (lexicographically)" but there was no on-disk representation of
+//! that ordering. This module is an LSM-style segment file: the sorted
+//! `(TreeKey, [u8; 32])` stream is partitioned into fixed-size blocks, each
+//! block is compressed with a selectable [`Codec`], and a sparse index of
+//! each block's first `TreeKey` lets a lookup binary-search straight to the
+//! one candidate block instead of scanning the whole (potentially
+//! multi-gigabyte) database. A block's checksum is verified before it is
+//! decompressed, catching on-disk corruption early.
+//!
+//! ## File layout
+//!
+//! ```text
+//! [SegmentHeader]
+//! [sparse index: num_blocks * (TreeKey[32] | block_offset[8] | block_len[4])]
+//! [block data: concatenated (BlockHeader | compressed payload) entries]
+//! ```
+//!
+//! Each block's decompressed payload is its entries packed back-to-back as
+//! `TreeKey[32] || value[32]`, still in sorted order, so a hit within a block
+//! is itself a binary search.
+
+use crate::ubt::{compute_storage_tree_key, TreeKey};
+use crate::{Address, StorageKey};
+
+const MAGIC: [u8; 4] = *b"SEG1";
+const VERSION: u8 = 1;
+
+/// Default number of `(TreeKey, value)` entries packed into each block
+/// before compression (64 bytes/entry -> 64 KiB decompressed per block).
+pub const DEFAULT_ENTRIES_PER_BLOCK: usize = 1024;
+
+const ENTRY_SIZE: usize = 64; // 32-byte TreeKey + 32-byte value
+const HEADER_SIZE: usize = 4 + 1 + 4 + 8 + 4; // magic + version + entries_per_block + num_entries + num_blocks
+const INDEX_ENTRY_SIZE: usize = 32 + 8 + 4; // first TreeKey + block offset + block length
+const BLOCK_HEADER_SIZE: usize = 1 + 1 + 4 + 4 + 8; // codec tag + codec param + decompressed len + compressed len + checksum
+
+const CODEC_TAG_NONE: u8 = 0;
+const CODEC_TAG_LZ4: u8 = 1;
+const CODEC_TAG_DEFLATE: u8 = 2;
+
+/// Compression codec applied to each block's packed entries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Codec {
+    None,
+    Lz4,
+    /// A deflate/miniz compression level, 0 (fastest) to 10 (smallest).
+    Deflate { level: u8 },
+}
+
+impl Codec {
+    fn tag(self) -> u8 {
+        match self {
+            Codec::None => CODEC_TAG_NONE,
+            Codec::Lz4 => CODEC_TAG_LZ4,
+            Codec::Deflate { .. } => CODEC_TAG_DEFLATE,
+        }
+    }
+
+    fn param(self) -> u8 {
+        match self {
+            Codec::Deflate { level } => level,
+            _ => 0,
+        }
+    }
+
+    fn compress(self, data: &[u8]) -> Vec<u8> {
+        match self {
+            Codec::None => data.to_vec(),
+            Codec::Lz4 => lz4_flex::compress(data),
+            Codec::Deflate { level } => miniz_oxide::deflate::compress_to_vec(data, level),
+        }
+    }
+}
+
+fn decompress(tag: u8, param: u8, data: &[u8], decompressed_len: usize) -> Result<Vec<u8>, SegmentError> {
+    match tag {
+        CODEC_TAG_NONE => Ok(data.to_vec()),
+        CODEC_TAG_LZ4 => lz4_flex::decompress(data, decompressed_len)
+            .map_err(|_| SegmentError::DecompressFailed),
+        CODEC_TAG_DEFLATE => {
+            let _ = param;
+            miniz_oxide::inflate::decompress_to_vec(data).map_err(|_| SegmentError::DecompressFailed)
+        }
+        other => Err(SegmentError::UnknownCodec(other)),
+    }
+}
+
+/// Error parsing or reading a segment file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SegmentError {
+    BadMagic,
+    UnsupportedVersion(u8),
+    Truncated { expected: usize, actual: usize },
+    UnknownCodec(u8),
+    ChecksumMismatch { block_offset: u64 },
+    DecompressFailed,
+    /// A sparse-index `offset`/`len` read from disk overflowed when combined
+    /// with another offset - a corrupted or malicious segment file, since a
+    /// well-formed one never produces an absolute offset near `usize::MAX`.
+    Overflow,
+}
+
+impl core::fmt::Display for SegmentError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            SegmentError::BadMagic => write!(f, "not a segment file (bad magic)"),
+            SegmentError::UnsupportedVersion(v) => write!(f, "unsupported segment version {v}"),
+            SegmentError::Truncated { expected, actual } => {
+                write!(f, "segment truncated (expected {expected} bytes, got {actual})")
+            }
+            SegmentError::UnknownCodec(tag) => write!(f, "unknown block codec tag {tag}"),
+            SegmentError::ChecksumMismatch { block_offset } => {
+                write!(f, "checksum mismatch for block at offset {block_offset}")
+            }
+            SegmentError::DecompressFailed => write!(f, "failed to decompress block"),
+            SegmentError::Overflow => write!(f, "segment index offset/length overflowed"),
+        }
+    }
+}
+
+impl std::error::Error for SegmentError {}
+
+/// Build a segment file from a sorted `(TreeKey, value)` stream.
+pub struct SegmentBuilder {
+    codec: Codec,
+    entries_per_block: usize,
+}
+
+impl SegmentBuilder {
+    pub fn new(codec: Codec) -> Self {
+        Self {
+            codec,
+            entries_per_block: DEFAULT_ENTRIES_PER_BLOCK,
+        }
+    }
+
+    pub fn with_entries_per_block(mut self, entries_per_block: usize) -> Self {
+        self.entries_per_block = entries_per_block.max(1);
+        self
+    }
+
+    /// Build the segment file bytes. `entries` must already be sorted by
+    /// `TreeKey` (the database ordering this format exists to serve).
+    pub fn build(&self, entries: &[(TreeKey, [u8; 32])]) -> Vec<u8> {
+        let chunks: Vec<&[(TreeKey, [u8; 32])]> = entries.chunks(self.entries_per_block).collect();
+
+        let mut block_data = Vec::new();
+        let mut index = Vec::with_capacity(chunks.len());
+
+        for chunk in &chunks {
+            let first_key = chunk[0].0;
+            let block_offset = block_data.len() as u64;
+
+            let mut raw = Vec::with_capacity(chunk.len() * ENTRY_SIZE);
+            for (tree_key, value) in *chunk {
+                raw.extend_from_slice(tree_key);
+                raw.extend_from_slice(value);
+            }
+
+            let compressed = self.codec.compress(&raw);
+            let checksum = xxhash_rust::xxh3::xxh3_64(&compressed);
+
+            block_data.extend_from_slice(&[self.codec.tag(), self.codec.param()]);
+            block_data.extend_from_slice(&(raw.len() as u32).to_le_bytes());
+            block_data.extend_from_slice(&(compressed.len() as u32).to_le_bytes());
+            block_data.extend_from_slice(&checksum.to_le_bytes());
+            block_data.extend_from_slice(&compressed);
+
+            let block_len = (BLOCK_HEADER_SIZE + compressed.len()) as u32;
+            index.push((first_key, block_offset, block_len));
+        }
+
+        let mut out = Vec::with_capacity(HEADER_SIZE + index.len() * INDEX_ENTRY_SIZE + block_data.len());
+        out.extend_from_slice(&MAGIC);
+        out.push(VERSION);
+        out.extend_from_slice(&(self.entries_per_block as u32).to_le_bytes());
+        out.extend_from_slice(&(entries.len() as u64).to_le_bytes());
+        out.extend_from_slice(&(index.len() as u32).to_le_bytes());
+
+        for (first_key, offset, len) in &index {
+            out.extend_from_slice(first_key);
+            out.extend_from_slice(&offset.to_le_bytes());
+            out.extend_from_slice(&len.to_le_bytes());
+        }
+
+        out.extend_from_slice(&block_data);
+        out
+    }
+}
+
+/// A parsed, queryable segment file.
+pub struct Segment {
+    data: Vec<u8>,
+    /// (first_key, absolute byte offset of block header, block total length)
+    index: Vec<(TreeKey, usize, usize)>,
+}
+
+impl Segment {
+    /// Parse a segment's header and sparse index. Block bodies are read and
+    /// decompressed lazily by [`Segment::get`].
+    pub fn open(data: Vec<u8>) -> Result<Self, SegmentError> {
+        if data.len() < HEADER_SIZE {
+            return Err(SegmentError::Truncated {
+                expected: HEADER_SIZE,
+                actual: data.len(),
+            });
+        }
+        if data[0..4] != MAGIC {
+            return Err(SegmentError::BadMagic);
+        }
+        let version = data[4];
+        if version != VERSION {
+            return Err(SegmentError::UnsupportedVersion(version));
+        }
+
+        // header layout: magic(4) | version(1) | entries_per_block(4) | num_entries(8) | num_blocks(4)
+        let num_blocks = u32::from_le_bytes(data[17..21].try_into().unwrap()) as usize;
+
+        let index_start = HEADER_SIZE;
+        let index_end = index_start + num_blocks * INDEX_ENTRY_SIZE;
+        if data.len() < index_end {
+            return Err(SegmentError::Truncated {
+                expected: index_end,
+                actual: data.len(),
+            });
+        }
+
+        let mut index = Vec::with_capacity(num_blocks);
+        for i in 0..num_blocks {
+            let base = index_start + i * INDEX_ENTRY_SIZE;
+            let first_key: TreeKey = data[base..base + 32].try_into().unwrap();
+            let offset = u64::from_le_bytes(data[base + 32..base + 40].try_into().unwrap()) as usize;
+            let len = u32::from_le_bytes(data[base + 40..base + 44].try_into().unwrap()) as usize;
+            let block_offset = index_end.checked_add(offset).ok_or(SegmentError::Overflow)?;
+            index.push((first_key, block_offset, len));
+        }
+
+        Ok(Self { data, index })
+    }
+
+    /// Binary-search the sparse index for `tree_key`, verify and decompress
+    /// the candidate block, then scan within it for an exact match.
+    pub fn get(&self, tree_key: &TreeKey) -> Result<Option<[u8; 32]>, SegmentError> {
+        let Some(block_pos) = self.candidate_block(tree_key) else {
+            return Ok(None);
+        };
+
+        let (_, offset, len) = self.index[block_pos];
+        let block_end = offset.checked_add(len).ok_or(SegmentError::Overflow)?;
+        if self.data.len() < block_end {
+            return Err(SegmentError::Truncated {
+                expected: block_end,
+                actual: self.data.len(),
+            });
+        }
+
+        let header = &self.data[offset..offset + BLOCK_HEADER_SIZE];
+        let codec_tag = header[0];
+        let codec_param = header[1];
+        let decompressed_len = u32::from_le_bytes(header[2..6].try_into().unwrap()) as usize;
+        let compressed_len = u32::from_le_bytes(header[6..10].try_into().unwrap()) as usize;
+        let checksum = u64::from_le_bytes(header[10..18].try_into().unwrap());
+
+        let payload_start = offset + BLOCK_HEADER_SIZE;
+        let payload = &self.data[payload_start..payload_start + compressed_len];
+
+        if xxhash_rust::xxh3::xxh3_64(payload) != checksum {
+            return Err(SegmentError::ChecksumMismatch {
+                block_offset: offset as u64,
+            });
+        }
+
+        let raw = decompress(codec_tag, codec_param, payload, decompressed_len)?;
+
+        let entry_count = raw.len() / ENTRY_SIZE;
+        let mut lo = 0usize;
+        let mut hi = entry_count;
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            let base = mid * ENTRY_SIZE;
+            match raw[base..base + 32].cmp(tree_key.as_slice()) {
+                std::cmp::Ordering::Equal => {
+                    let mut value = [0u8; 32];
+                    value.copy_from_slice(&raw[base + 32..base + 64]);
+                    return Ok(Some(value));
+                }
+                std::cmp::Ordering::Less => lo = mid + 1,
+                std::cmp::Ordering::Greater => hi = mid,
+            }
+        }
+        Ok(None)
+    }
+
+    /// Index of the last block whose first key is `<= tree_key`, i.e. the
+    /// only block that could contain it, or `None` if `tree_key` precedes
+    /// every block.
+    fn candidate_block(&self, tree_key: &TreeKey) -> Option<usize> {
+        let split = self.index.partition_point(|(first_key, _, _)| first_key <= tree_key);
+        split.checked_sub(1)
+    }
+
+    /// Number of blocks in the segment (for tests and diagnostics).
+    pub fn block_count(&self) -> usize {
+        self.index.len()
+    }
+}
+
+/// Resolve a storage slot's value through a [`Segment`], combining
+/// `compute_storage_tree_key` with [`Segment::get`] so the caller never
+/// needs the full sorted database in memory.
+pub fn compute_storage_value(
+    segment: &Segment,
+    address: &Address,
+    slot: &StorageKey,
+) -> Result<Option<[u8; 32]>, SegmentError> {
+    let tree_key = compute_storage_tree_key(address, slot);
+    segment.get(&tree_key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entries(n: u32) -> Vec<(TreeKey, [u8; 32])> {
+        (0..n)
+            .map(|i| {
+                let mut key = [0u8; 32];
+                key[28..32].copy_from_slice(&i.to_be_bytes());
+                let mut value = [0u8; 32];
+                value[0..4].copy_from_slice(&i.to_le_bytes());
+                (key, value)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_roundtrip_uncompressed() {
+        let data = entries(5_000);
+        let bytes = SegmentBuilder::new(Codec::None)
+            .with_entries_per_block(100)
+            .build(&data);
+
+        let segment = Segment::open(bytes).unwrap();
+        assert!(segment.block_count() > 1);
+
+        for (key, value) in &data {
+            assert_eq!(segment.get(key).unwrap(), Some(*value));
+        }
+    }
+
+    #[test]
+    fn test_roundtrip_lz4() {
+        let data = entries(2_000);
+        let bytes = SegmentBuilder::new(Codec::Lz4)
+            .with_entries_per_block(128)
+            .build(&data);
+
+        let segment = Segment::open(bytes).unwrap();
+        for (key, value) in data.iter().step_by(37) {
+            assert_eq!(segment.get(key).unwrap(), Some(*value));
+        }
+    }
+
+    #[test]
+    fn test_roundtrip_deflate() {
+        let data = entries(2_000);
+        let bytes = SegmentBuilder::new(Codec::Deflate { level: 6 })
+            .with_entries_per_block(128)
+            .build(&data);
+
+        let segment = Segment::open(bytes).unwrap();
+        for (key, value) in data.iter().step_by(41) {
+            assert_eq!(segment.get(key).unwrap(), Some(*value));
+        }
+    }
+
+    #[test]
+    fn test_missing_key_returns_none() {
+        let data = entries(100);
+        let bytes = SegmentBuilder::new(Codec::None).build(&data);
+        let segment = Segment::open(bytes).unwrap();
+
+        let mut missing_key = [0xffu8; 32];
+        missing_key[28..32].copy_from_slice(&9999u32.to_be_bytes());
+        assert_eq!(segment.get(&missing_key).unwrap(), None);
+    }
+
+    #[test]
+    fn test_key_before_first_block_returns_none() {
+        let data = entries(100);
+        let bytes = SegmentBuilder::new(Codec::None)
+            .with_entries_per_block(10)
+            .build(&data);
+        let segment = Segment::open(bytes).unwrap();
+
+        assert_eq!(segment.candidate_block(&[0u8; 32]), None);
+    }
+
+    #[test]
+    fn test_rejects_bad_magic() {
+        let junk = vec![0u8; 64];
+        assert_eq!(Segment::open(junk), Err(SegmentError::BadMagic));
+    }
+
+    #[test]
+    fn test_detects_corrupted_block() {
+        let data = entries(200);
+        let mut bytes = SegmentBuilder::new(Codec::None)
+            .with_entries_per_block(50)
+            .build(&data);
+
+        // Flip a byte inside the last block's compressed payload (the
+        // block holding entry 150).
+        let corrupt_at = bytes.len() - 10;
+        bytes[corrupt_at] ^= 0xff;
+
+        let segment = Segment::open(bytes).unwrap();
+        let mut key = [0u8; 32];
+        key[28..32].copy_from_slice(&150u32.to_be_bytes());
+        assert!(matches!(
+            segment.get(&key),
+            Err(SegmentError::ChecksumMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_compute_storage_value_through_segment() {
+        use crate::ubt::compute_storage_tree_key;
+
+        let address = [0x42u8; 20];
+        let slot = [0u8; 32];
+        let tree_key = compute_storage_tree_key(&address, &slot);
+        let value = [0x99u8; 32];
+
+        let bytes = SegmentBuilder::new(Codec::Lz4).build(&[(tree_key, value)]);
+        let segment = Segment::open(bytes).unwrap();
+
+        assert_eq!(
+            compute_storage_value(&segment, &address, &slot).unwrap(),
+            Some(value)
+        );
+    }
+
+    #[test]
+    fn test_overflowing_index_offset_is_rejected_not_wrapped() {
+        let data = entries(10);
+        let mut bytes = SegmentBuilder::new(Codec::None).build(&data);
+
+        // Corrupt the first index entry's block offset (at
+        // HEADER_SIZE + 32..+40) to a value that overflows when combined
+        // with index_end, instead of a value that would merely point past
+        // the end of the file.
+        let offset_field = HEADER_SIZE + 32;
+        bytes[offset_field..offset_field + 8].copy_from_slice(&u64::MAX.to_le_bytes());
+
+        assert_eq!(Segment::open(bytes), Err(SegmentError::Overflow));
+    }
+}
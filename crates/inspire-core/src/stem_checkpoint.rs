@@ -0,0 +1,273 @@
+//! Checkpoint/rewind support for the stem offset table
+//!
+//! As an ExEx this crate processes canonical blocks, but a plain
+//! [`NodeMap`] has no way to roll back when the chain reorgs and a
+//! previously-applied block is undone. [`CheckpointedStemTable`] wraps a
+//! `NodeMap` with a BridgeTree-style checkpoint/rewind log: each newly
+//! allocated `(Stem, start_index)` is appended to an insertion log as it's
+//! applied, `checkpoint` records a marker once a block is fully applied, and
+//! `rewind_to` pops the log back to a marker, removing those stems from the
+//! nodemap and reclaiming their index range so the next canonical block
+//! reuses it. Only a bounded ring of the most recent checkpoints is
+//! retained; rewinding past the oldest one is an error.
+
+use crate::nodemap::NodeMap;
+use crate::ubt::{constants::STEM_SUBTREE_WIDTH, Stem};
+use std::collections::VecDeque;
+
+/// Default number of recent checkpoints retained before the oldest is
+/// evicted and can no longer be rewound to.
+pub const DEFAULT_MAX_CHECKPOINTS: usize = 256;
+
+/// Error returned by [`CheckpointedStemTable::rewind_to`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RewindError {
+    /// `requested` predates the oldest retained checkpoint.
+    TooOld { requested: u64, oldest_retained: u64 },
+    /// No checkpoint has been recorded for `requested`.
+    NotFound { requested: u64 },
+}
+
+impl core::fmt::Display for RewindError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            RewindError::TooOld {
+                requested,
+                oldest_retained,
+            } => write!(
+                f,
+                "cannot rewind to block {requested}: oldest retained checkpoint is block {oldest_retained}"
+            ),
+            RewindError::NotFound { requested } => {
+                write!(f, "no checkpoint recorded for block {requested}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for RewindError {}
+
+/// A checkpoint marker: the table's state once `block_number` was fully
+/// applied.
+#[derive(Debug, Clone, Copy)]
+struct Checkpoint {
+    block_number: u64,
+    /// Number of insertion-log entries applied as of this checkpoint.
+    log_len: usize,
+    /// `next_index` as of this checkpoint.
+    next_index: u64,
+}
+
+/// A stem insertion recorded so it can be undone by `rewind_to`.
+struct LogEntry {
+    stem: Stem,
+    start_index: u64,
+}
+
+/// A [`NodeMap`] stem offset table with reorg-safe checkpoint/rewind.
+pub struct CheckpointedStemTable {
+    nodemap: NodeMap,
+    next_index: u64,
+    log: Vec<LogEntry>,
+    checkpoints: VecDeque<Checkpoint>,
+    max_checkpoints: usize,
+}
+
+impl Default for CheckpointedStemTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CheckpointedStemTable {
+    pub fn new() -> Self {
+        Self::with_max_checkpoints(DEFAULT_MAX_CHECKPOINTS)
+    }
+
+    pub fn with_max_checkpoints(max_checkpoints: usize) -> Self {
+        Self {
+            nodemap: NodeMap::new(),
+            next_index: 0,
+            log: Vec::new(),
+            checkpoints: VecDeque::new(),
+            max_checkpoints: max_checkpoints.max(1),
+        }
+    }
+
+    /// Allocate a fresh `STEM_SUBTREE_WIDTH`-wide index range for `stem` and
+    /// record the insertion against the block currently being applied, so a
+    /// later `rewind_to` can undo it. A stem already present is left
+    /// untouched (no reallocation, no log entry).
+    pub fn insert_stem(&mut self, stem: Stem) -> u64 {
+        if let Some(existing) = self.nodemap.lookup(&stem) {
+            return existing;
+        }
+
+        let start_index = self.next_index;
+        self.next_index += STEM_SUBTREE_WIDTH;
+        self.nodemap.insert_stem(stem, start_index);
+        self.log.push(LogEntry { stem, start_index });
+        start_index
+    }
+
+    pub fn lookup(&self, stem: &Stem) -> Option<u64> {
+        self.nodemap.lookup(stem)
+    }
+
+    /// Record that every stem inserted so far belongs to `block_number`,
+    /// evicting the oldest checkpoint once more than `max_checkpoints` are
+    /// retained.
+    pub fn checkpoint(&mut self, block_number: u64) {
+        self.checkpoints.push_back(Checkpoint {
+            block_number,
+            log_len: self.log.len(),
+            next_index: self.next_index,
+        });
+        while self.checkpoints.len() > self.max_checkpoints {
+            self.checkpoints.pop_front();
+        }
+    }
+
+    /// Undo every stem insertion made after `block_number`'s checkpoint,
+    /// removing those stems from the nodemap and rewinding `next_index` so
+    /// their index range is reused by the next canonical block.
+    pub fn rewind_to(&mut self, block_number: u64) -> Result<(), RewindError> {
+        let target_pos = self
+            .checkpoints
+            .iter()
+            .position(|c| c.block_number == block_number);
+
+        let Some(target_pos) = target_pos else {
+            return match self.checkpoints.front() {
+                Some(oldest) if block_number < oldest.block_number => Err(RewindError::TooOld {
+                    requested: block_number,
+                    oldest_retained: oldest.block_number,
+                }),
+                _ => Err(RewindError::NotFound {
+                    requested: block_number,
+                }),
+            };
+        };
+
+        let target = self.checkpoints[target_pos];
+        while self.log.len() > target.log_len {
+            let entry = self.log.pop().expect("log_len invariant");
+            self.nodemap.remove(&entry.stem);
+        }
+        self.next_index = target.next_index;
+        self.checkpoints.truncate(target_pos + 1);
+
+        Ok(())
+    }
+
+    /// The most recently recorded checkpoint's block number, if any.
+    pub fn latest_checkpoint(&self) -> Option<u64> {
+        self.checkpoints.back().map(|c| c.block_number)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stem_from_byte(b: u8) -> Stem {
+        [b; 31]
+    }
+
+    #[test]
+    fn test_insert_then_checkpoint_then_lookup() {
+        let mut table = CheckpointedStemTable::new();
+        let stem = stem_from_byte(1);
+        table.insert_stem(stem);
+        table.checkpoint(100);
+
+        assert_eq!(table.lookup(&stem), Some(0));
+        assert_eq!(table.latest_checkpoint(), Some(100));
+    }
+
+    #[test]
+    fn test_rewind_removes_stems_after_checkpoint() {
+        let mut table = CheckpointedStemTable::new();
+
+        table.insert_stem(stem_from_byte(1));
+        table.checkpoint(100);
+
+        let stem2 = stem_from_byte(2);
+        table.insert_stem(stem2);
+        table.checkpoint(101);
+
+        table.rewind_to(100).unwrap();
+
+        assert_eq!(table.lookup(&stem_from_byte(1)), Some(0));
+        assert_eq!(table.lookup(&stem2), None);
+        assert_eq!(table.latest_checkpoint(), Some(100));
+    }
+
+    #[test]
+    fn test_rewind_reclaims_index_range() {
+        let mut table = CheckpointedStemTable::new();
+
+        table.insert_stem(stem_from_byte(1));
+        table.checkpoint(100);
+
+        let reorged_index = table.insert_stem(stem_from_byte(2));
+        table.checkpoint(101);
+        assert_eq!(reorged_index, STEM_SUBTREE_WIDTH);
+
+        table.rewind_to(100).unwrap();
+
+        // Re-applying a (possibly different) block reuses the reclaimed range.
+        let reused_index = table.insert_stem(stem_from_byte(3));
+        assert_eq!(reused_index, STEM_SUBTREE_WIDTH);
+    }
+
+    #[test]
+    fn test_rewind_to_unknown_block_errors() {
+        let mut table = CheckpointedStemTable::new();
+        table.insert_stem(stem_from_byte(1));
+        table.checkpoint(100);
+
+        assert_eq!(
+            table.rewind_to(999),
+            Err(RewindError::NotFound { requested: 999 })
+        );
+    }
+
+    #[test]
+    fn test_rewind_past_oldest_retained_checkpoint_errors() {
+        let mut table = CheckpointedStemTable::with_max_checkpoints(2);
+
+        table.insert_stem(stem_from_byte(1));
+        table.checkpoint(100);
+        table.insert_stem(stem_from_byte(2));
+        table.checkpoint(101);
+        table.insert_stem(stem_from_byte(3));
+        table.checkpoint(102); // evicts checkpoint 100
+
+        assert_eq!(
+            table.rewind_to(100),
+            Err(RewindError::TooOld {
+                requested: 100,
+                oldest_retained: 101,
+            })
+        );
+    }
+
+    #[test]
+    fn test_inserting_existing_stem_does_not_log_or_reallocate() {
+        let mut table = CheckpointedStemTable::new();
+        let stem = stem_from_byte(1);
+
+        let first = table.insert_stem(stem);
+        table.checkpoint(100);
+        let second = table.insert_stem(stem);
+        table.checkpoint(101);
+
+        assert_eq!(first, second);
+
+        // Rewinding past the re-insertion should not have freed the stem,
+        // since re-inserting an existing stem logs nothing.
+        table.rewind_to(100).unwrap();
+        assert_eq!(table.lookup(&stem), Some(first));
+    }
+}
@@ -0,0 +1,354 @@
+//! EIP-7864 Unified Binary Trie commitment root and inclusion proofs
+//!
+//! [`crate::ubt`] computes `tree_key`s but never materializes the trie or a
+//! commitment to it, so a PIR client has no way to verify that a value it
+//! received actually belongs to the committed state. This module builds a
+//! sparse binary Merkle tree over `(TreeKey, value)` pairs: each of a
+//! `TreeKey`'s 256 bits (MSB-first) selects a left/right descent step, each
+//! internal node is `blake3(left_child_hash || right_child_hash)`, and an
+//! absent subtree - including every position under a stem whose subindex was
+//! never written - hashes to a fixed empty-node constant. Since every
+//! subindex belonging to one stem differs only in the key's low byte, the
+//! bottom 8 levels of this descent are exactly "a stem node committing to
+//! its up-to-256 subindex values"; the implementation does not special-case
+//! that, because descending the full 256 bits uniformly already produces it.
+//!
+//! Like a sealable-trie proof, an [`InclusionProof`] is self-contained: it
+//! carries only sibling hashes, and the bit-direction at each level is
+//! re-derived from the `tree_key` being verified, so [`verify`] needs no
+//! access to the tree itself.
+//!
+//! A sealed stem ([`crate::nodemap::NodeMap::seal_stem`]) has had its
+//! individual subindex values pruned from the queryable PIR set, so its
+//! subtree's `(TreeKey, value)` pairs are no longer available to hash from
+//! scratch. [`UbtCommitment::build_with_sealed_stems`] takes each sealed
+//! stem's retained hash and substitutes it directly at depth [`STEM_BITS`] -
+//! exactly the height at which "a stem node committing to its up-to-256
+//! subindex values" sits - so the root (and any sibling proof that passes
+//! through it) still folds in the correct value without needing the pruned
+//! entries back.
+
+use crate::ubt::{Stem, TreeKey};
+use std::collections::BTreeMap;
+
+/// Number of bits in a `TreeKey` (32 bytes), and thus the trie's depth.
+const KEY_BITS: usize = 256;
+
+/// Depth at which a subtree corresponds exactly to one stem's 256-entry
+/// subindex space (31-byte stem = 248 bits).
+const STEM_BITS: usize = 31 * 8;
+
+/// The value an absent tree_key is treated as holding, for both building
+/// default subtree hashes and proving non-membership.
+const EMPTY_LEAF_VALUE: [u8; 32] = [0u8; 32];
+
+/// A self-contained Merkle inclusion (or non-inclusion) proof for one
+/// `tree_key` against a [`UbtCommitment::root`].
+#[derive(Debug, Clone)]
+pub struct InclusionProof {
+    /// The value at `tree_key`, or [`EMPTY_LEAF_VALUE`] if it was never set.
+    pub value: [u8; 32],
+    /// Sibling hashes ordered from the leaf level up to (but not including)
+    /// the root. The direction each sibling combines on is derived from the
+    /// corresponding bit of the `tree_key` being verified, not stored here.
+    pub siblings: Vec<[u8; 32]>,
+}
+
+fn hash_pair(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(left);
+    hasher.update(right);
+    *hasher.finalize().as_bytes()
+}
+
+fn leaf_hash(value: &[u8; 32]) -> [u8; 32] {
+    *blake3::hash(value).as_bytes()
+}
+
+/// `depth` counts from the root (0) down to a leaf (`KEY_BITS`).
+fn bit_at(key: &TreeKey, depth: usize) -> bool {
+    let byte = key[depth / 8];
+    (byte >> (7 - (depth % 8))) & 1 == 1
+}
+
+/// Set bit `depth` of `key` to 1 (MSB-first, matching [`bit_at`]).
+fn set_bit(key: &mut TreeKey, depth: usize) {
+    key[depth / 8] |= 1 << (7 - (depth % 8));
+}
+
+/// `defaults[h]` is the hash of a fully-empty subtree of height `h` (`h = 0`
+/// is an empty leaf itself, `h = KEY_BITS` is a fully-empty whole tree).
+fn default_hashes() -> [[u8; 32]; KEY_BITS + 1] {
+    let mut defaults = [[0u8; 32]; KEY_BITS + 1];
+    defaults[0] = leaf_hash(&EMPTY_LEAF_VALUE);
+    for h in 1..=KEY_BITS {
+        defaults[h] = hash_pair(&defaults[h - 1], &defaults[h - 1]);
+    }
+    defaults
+}
+
+fn build_node(
+    pairs: &[(TreeKey, [u8; 32])],
+    prefix: &TreeKey,
+    depth: usize,
+    defaults: &[[u8; 32]; KEY_BITS + 1],
+    sealed: &BTreeMap<Stem, [u8; 32]>,
+) -> [u8; 32] {
+    if depth == STEM_BITS {
+        let stem: Stem = prefix[..31].try_into().unwrap();
+        if let Some(hash) = sealed.get(&stem) {
+            return *hash;
+        }
+    }
+    if pairs.is_empty() {
+        return defaults[KEY_BITS - depth];
+    }
+    if depth == KEY_BITS {
+        debug_assert_eq!(pairs.len(), 1, "tree_keys must be unique");
+        return leaf_hash(&pairs[0].1);
+    }
+
+    let split = pairs.partition_point(|(k, _)| !bit_at(k, depth));
+    let (left, right) = pairs.split_at(split);
+
+    let mut right_prefix = *prefix;
+    set_bit(&mut right_prefix, depth);
+
+    let left_hash = build_node(left, prefix, depth + 1, defaults, sealed);
+    let right_hash = build_node(right, &right_prefix, depth + 1, defaults, sealed);
+    hash_pair(&left_hash, &right_hash)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn collect_siblings(
+    pairs: &[(TreeKey, [u8; 32])],
+    target: &TreeKey,
+    prefix: &TreeKey,
+    depth: usize,
+    defaults: &[[u8; 32]; KEY_BITS + 1],
+    sealed: &BTreeMap<Stem, [u8; 32]>,
+    out: &mut Vec<[u8; 32]>,
+) {
+    if depth == KEY_BITS {
+        return;
+    }
+
+    let split = pairs.partition_point(|(k, _)| !bit_at(k, depth));
+    let (left, right) = pairs.split_at(split);
+
+    let mut right_prefix = *prefix;
+    set_bit(&mut right_prefix, depth);
+
+    let (matching, other, matching_prefix, other_prefix) = if bit_at(target, depth) {
+        (right, left, right_prefix, *prefix)
+    } else {
+        (left, right, *prefix, right_prefix)
+    };
+
+    out.push(build_node(other, &other_prefix, depth + 1, defaults, sealed));
+    collect_siblings(matching, target, &matching_prefix, depth + 1, defaults, sealed, out);
+}
+
+/// Commitment to a fixed set of `(TreeKey, value)` pairs, and the source of
+/// [`InclusionProof`]s for them.
+pub struct UbtCommitment {
+    root: [u8; 32],
+    entries: BTreeMap<TreeKey, [u8; 32]>,
+    sealed: BTreeMap<Stem, [u8; 32]>,
+    defaults: [[u8; 32]; KEY_BITS + 1],
+}
+
+impl UbtCommitment {
+    /// Build the trie and compute its root over `entries`. Later entries
+    /// for the same `tree_key` overwrite earlier ones.
+    pub fn build(entries: impl IntoIterator<Item = (TreeKey, [u8; 32])>) -> Self {
+        Self::build_with_sealed_stems(entries, std::iter::empty())
+    }
+
+    /// Build the trie as [`UbtCommitment::build`] does, but fold each sealed
+    /// stem's retained hash into its subtree instead of treating it as empty.
+    /// `entries` should no longer contain that stem's pruned subindex values;
+    /// if it does, the retained hash still wins.
+    pub fn build_with_sealed_stems(
+        entries: impl IntoIterator<Item = (TreeKey, [u8; 32])>,
+        sealed_stems: impl IntoIterator<Item = (Stem, [u8; 32])>,
+    ) -> Self {
+        let entries: BTreeMap<TreeKey, [u8; 32]> = entries.into_iter().collect();
+        let sealed: BTreeMap<Stem, [u8; 32]> = sealed_stems.into_iter().collect();
+        let defaults = default_hashes();
+        let pairs: Vec<(TreeKey, [u8; 32])> = entries.iter().map(|(k, v)| (*k, *v)).collect();
+        let root = build_node(&pairs, &[0u8; 32], 0, &defaults, &sealed);
+
+        Self {
+            root,
+            entries,
+            sealed,
+            defaults,
+        }
+    }
+
+    /// The commitment root.
+    pub fn root(&self) -> [u8; 32] {
+        self.root
+    }
+
+    /// Produce a proof for `tree_key`, whether or not it was ever set - an
+    /// absent key proves non-membership via [`EMPTY_LEAF_VALUE`].
+    pub fn prove(&self, tree_key: &TreeKey) -> InclusionProof {
+        let value = self.entries.get(tree_key).copied().unwrap_or(EMPTY_LEAF_VALUE);
+        let pairs: Vec<(TreeKey, [u8; 32])> = self.entries.iter().map(|(k, v)| (*k, *v)).collect();
+
+        let mut siblings = Vec::with_capacity(KEY_BITS);
+        collect_siblings(
+            &pairs,
+            tree_key,
+            &[0u8; 32],
+            0,
+            &self.defaults,
+            &self.sealed,
+            &mut siblings,
+        );
+        siblings.reverse(); // collected root-to-leaf; store leaf-to-root
+
+        InclusionProof { value, siblings }
+    }
+}
+
+/// Recompute the root-to-leaf path from `proof` and check it matches `root`,
+/// without access to the full tree.
+pub fn verify(root: &[u8; 32], tree_key: &TreeKey, value: &[u8; 32], proof: &InclusionProof) -> bool {
+    if proof.siblings.len() != KEY_BITS || proof.value != *value {
+        return false;
+    }
+
+    let mut current = leaf_hash(value);
+    for (i, sibling) in proof.siblings.iter().enumerate() {
+        let depth = KEY_BITS - 1 - i;
+        current = if bit_at(tree_key, depth) {
+            hash_pair(sibling, &current)
+        } else {
+            hash_pair(&current, sibling)
+        };
+    }
+
+    current == *root
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(byte: u8) -> TreeKey {
+        let mut k = [0u8; 32];
+        k[31] = byte;
+        k
+    }
+
+    #[test]
+    fn test_empty_commitment_is_deterministic() {
+        let a = UbtCommitment::build(std::iter::empty());
+        let b = UbtCommitment::build(std::iter::empty());
+        assert_eq!(a.root(), b.root());
+    }
+
+    #[test]
+    fn test_single_entry_root_changes_with_value() {
+        let a = UbtCommitment::build([(key(1), [0xaau8; 32])]);
+        let b = UbtCommitment::build([(key(1), [0xbbu8; 32])]);
+        assert_ne!(a.root(), b.root());
+    }
+
+    #[test]
+    fn test_prove_and_verify_present_key() {
+        let tree_key = key(7);
+        let value = [0x42u8; 32];
+        let commitment = UbtCommitment::build([(tree_key, value), (key(200), [0x01u8; 32])]);
+
+        let proof = commitment.prove(&tree_key);
+        assert_eq!(proof.value, value);
+        assert_eq!(proof.siblings.len(), KEY_BITS);
+        assert!(verify(&commitment.root(), &tree_key, &value, &proof));
+    }
+
+    #[test]
+    fn test_prove_absent_key_proves_non_membership() {
+        let commitment = UbtCommitment::build([(key(7), [0x42u8; 32])]);
+
+        let absent_key = key(99);
+        let proof = commitment.prove(&absent_key);
+        assert_eq!(proof.value, EMPTY_LEAF_VALUE);
+        assert!(verify(&commitment.root(), &absent_key, &EMPTY_LEAF_VALUE, &proof));
+    }
+
+    #[test]
+    fn test_verify_rejects_wrong_value() {
+        let tree_key = key(7);
+        let value = [0x42u8; 32];
+        let commitment = UbtCommitment::build([(tree_key, value)]);
+
+        let proof = commitment.prove(&tree_key);
+        assert!(!verify(&commitment.root(), &tree_key, &[0xffu8; 32], &proof));
+    }
+
+    #[test]
+    fn test_verify_rejects_wrong_root() {
+        let tree_key = key(7);
+        let value = [0x42u8; 32];
+        let commitment = UbtCommitment::build([(tree_key, value)]);
+
+        let proof = commitment.prove(&tree_key);
+        let wrong_root = [0x99u8; 32];
+        assert!(!verify(&wrong_root, &tree_key, &value, &proof));
+    }
+
+    #[test]
+    fn test_order_of_construction_does_not_matter() {
+        let a = UbtCommitment::build([(key(1), [0x11u8; 32]), (key(2), [0x22u8; 32])]);
+        let b = UbtCommitment::build([(key(2), [0x22u8; 32]), (key(1), [0x11u8; 32])]);
+        assert_eq!(a.root(), b.root());
+    }
+
+    fn stem_of(tree_key: &TreeKey) -> Stem {
+        tree_key[..31].try_into().unwrap()
+    }
+
+    #[test]
+    fn test_sealing_a_stem_with_its_own_subtree_hash_preserves_the_root() {
+        // key(7) and key(200) share the same (all-zero) stem and differ only
+        // in subindex, so they live in one stem's subtree.
+        let stem = stem_of(&key(7));
+        let entries_in_stem = vec![(key(7), [0x42u8; 32]), (key(200), [0x01u8; 32])];
+
+        let mut prefix = [0u8; 32];
+        prefix[..31].copy_from_slice(&stem);
+        let subtree_hash = build_node(
+            &entries_in_stem,
+            &prefix,
+            STEM_BITS,
+            &default_hashes(),
+            &BTreeMap::new(),
+        );
+
+        // A stem's retained hash is exactly what its own subtree already
+        // hashes to, so sealing it (and dropping its entries) must not
+        // change the root.
+        let unsealed = UbtCommitment::build(entries_in_stem);
+        let sealed =
+            UbtCommitment::build_with_sealed_stems(std::iter::empty(), [(stem, subtree_hash)]);
+
+        assert_eq!(unsealed.root(), sealed.root());
+    }
+
+    #[test]
+    fn test_sealing_with_wrong_hash_changes_the_root() {
+        let entries = [(key(7), [0x42u8; 32])];
+        let unsealed = UbtCommitment::build(entries);
+
+        let sealed = UbtCommitment::build_with_sealed_stems(
+            std::iter::empty(),
+            [(stem_of(&key(7)), [0xffu8; 32])],
+        );
+
+        assert_ne!(unsealed.root(), sealed.root());
+    }
+}
@@ -0,0 +1,76 @@
+//! Batch query routing
+//!
+//! Resolves many `(contract, slot)` targets in one request via
+//! `LaneRouter::route_batch`, so a wallet that needs several storage slots
+//! (balance, allowance, nonce, ...) pays for one HTTP round trip instead of
+//! one per slot. Actually answering each routed query still goes through
+//! whichever lane database holds it (see `pir-server-b`'s `Responder` for
+//! the PIR response side of an equivalent Dummy Subsets PIR request); this
+//! handler covers the routing step shared by both lanes.
+
+use std::sync::Arc;
+
+use axum::extract::State;
+use axum::Json;
+use serde::{Deserialize, Serialize};
+
+use inspire_core::{Lane, LaneRouter, QueryTarget};
+
+/// State backing the `/route-batch` route: just the router, since
+/// resolving targets to `(lane, index)` pairs doesn't need lane data
+/// loaded.
+pub struct BatchRouteState {
+    pub router: LaneRouter,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BatchRouteRequest {
+    pub targets: Vec<RawQueryTarget>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RawQueryTarget {
+    pub contract: [u8; 20],
+    pub slot: [u8; 32],
+}
+
+#[derive(Debug, Serialize)]
+pub struct BatchRouteResponse {
+    pub routed: Vec<RoutedQueryResponse>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RoutedQueryResponse {
+    pub contract: String,
+    pub slot: String,
+    pub lane: Lane,
+    pub index: u64,
+}
+
+/// Route a batch of `(contract, slot)` targets, grouping nothing itself -
+/// the caller groups the returned `RoutedQueryResponse`s by `lane` to issue
+/// one batched PIR pass per lane.
+pub async fn route_batch_handler(
+    State(state): State<Arc<BatchRouteState>>,
+    Json(request): Json<BatchRouteRequest>,
+) -> Json<BatchRouteResponse> {
+    let targets: Vec<QueryTarget> = request
+        .targets
+        .iter()
+        .map(|t| QueryTarget::new(t.contract, t.slot))
+        .collect();
+
+    let routed = state
+        .router
+        .route_batch(&targets)
+        .into_iter()
+        .map(|rq| RoutedQueryResponse {
+            contract: hex::encode(rq.target.contract),
+            slot: hex::encode(rq.target.slot),
+            lane: rq.lane,
+            index: rq.index,
+        })
+        .collect();
+
+    Json(BatchRouteResponse { routed })
+}
@@ -6,17 +6,29 @@
 //! ## Protocol
 //! 1. Client connects
 //! 2. Server sends Hello message (JSON): `{"version":1,"block_number":12345}`
-//! 3. Server sends binary BucketDelta after each block
-//! 4. If client lags behind, server closes with code 4000 and reason "lagged:<block>"
+//! 3. Server sends one or more binary frames per block, chunked via
+//!    `BucketDelta::to_chunks` (see `inspire_core::bucket_index::MAX_CHUNK_LEN`)
+//!    so a delta touching tens of thousands of buckets never needs a single
+//!    oversized frame; the client reassembles them with `ChunkReassembler`.
+//! 4. Server responds to Ping with Pong
+//! 5. If a client lags, the server tries an in-band catch-up first: it looks
+//!    up the smallest `range_delta` tier covering the gap (same tier
+//!    selection `/range-delta` uses) and sends it as one delta before
+//!    resuming live broadcast. Only when the gap exceeds every tier does it
+//!    close with code 4000 and reason "lagged:<block>".
 
+use std::path::PathBuf;
 use std::sync::Arc;
 
 use axum::extract::ws::{Message, WebSocket};
 use futures_util::{SinkExt, StreamExt};
 use inspire_client::BucketDelta;
+use inspire_core::bucket_index::MAX_CHUNK_LEN;
 use serde::Serialize;
 use tokio::sync::broadcast;
 
+use crate::range_delta::select_tier;
+
 /// Broadcast channel capacity (enough for ~10 minutes of blocks)
 const BROADCAST_CAPACITY: usize = 64;
 
@@ -81,13 +93,18 @@ pub struct WsHello {
 ///
 /// Protocol:
 /// 1. Server sends Hello message (JSON) with version and current block
-/// 2. Server sends binary BucketDelta messages after each block
+/// 2. Server sends the block's BucketDelta as one or more chunked binary
+///    frames (see `BucketDelta::to_chunks`)
 /// 3. Server responds to Ping with Pong
-/// 4. If client lags, server closes with code 4000 and reason "lagged:<block>"
+/// 4. If the client lags and `range_delta_path` is set, the server looks up
+///    the smallest tier covering the gap and sends it as a catch-up delta
+///    instead of closing; only a gap wider than every tier (or no
+///    `range_delta_path`) closes with code 4000 and reason "lagged:<block>"
 pub async fn handle_index_subscription(
     socket: WebSocket,
     broadcast: BucketBroadcast,
     current_block: Option<u64>,
+    range_delta_path: Option<PathBuf>,
 ) {
     let (mut sender, mut receiver) = socket.split();
     let mut rx = broadcast.subscribe();
@@ -137,23 +154,44 @@ pub async fn handle_index_subscription(
                 match delta {
                     Ok(delta) => {
                         latest_block = Some(delta.block_number);
-                        let bytes = delta.to_bytes();
-                        if let Err(e) = sender.send(Message::Binary(bytes.into())).await {
-                            tracing::debug!(error = %e, "Failed to send delta");
+                        let mut send_failed = false;
+                        for chunk in delta.to_chunks(MAX_CHUNK_LEN) {
+                            if let Err(e) = sender.send(Message::Binary(chunk.into())).await {
+                                tracing::debug!(error = %e, "Failed to send delta chunk");
+                                send_failed = true;
+                                break;
+                            }
+                        }
+                        if send_failed {
                             break;
                         }
                     }
                     Err(broadcast::error::RecvError::Lagged(n)) => {
-                        tracing::warn!(skipped = n, "Client lagged, sending reconnect hint");
-                        let reason = match latest_block {
-                            Some(block) => format!("lagged:{}", block),
-                            None => "lagged".to_string(),
-                        };
-                        let _ = sender.send(Message::Close(Some(axum::extract::ws::CloseFrame {
-                            code: 4000,
-                            reason: reason.into(),
-                        }))).await;
-                        break;
+                        tracing::warn!(skipped = n, "Client lagged, attempting range-delta catch-up");
+
+                        let caught_up = try_catch_up_from_range_delta(
+                            &mut sender,
+                            range_delta_path.as_deref(),
+                            latest_block,
+                        )
+                        .await;
+
+                        match caught_up {
+                            Some(block) => {
+                                latest_block = Some(block);
+                            }
+                            None => {
+                                let reason = match latest_block {
+                                    Some(block) => format!("lagged:{}", block),
+                                    None => "lagged".to_string(),
+                                };
+                                let _ = sender.send(Message::Close(Some(axum::extract::ws::CloseFrame {
+                                    code: 4000,
+                                    reason: reason.into(),
+                                }))).await;
+                                break;
+                            }
+                        }
                     }
                     Err(broadcast::error::RecvError::Closed) => {
                         break;
@@ -175,6 +213,41 @@ pub async fn handle_index_subscription(
     tracing::debug!("WebSocket subscription ended");
 }
 
+/// Try to fast-forward a lagged client by sending the smallest range-delta
+/// tier covering the gap since `last_sent_block`, as a single chunked
+/// delta. Returns the block the tier brought the client current to on
+/// success, or `None` if there's no `range_delta_path`, the gap exceeds
+/// every tier, or the file couldn't be read/parsed - in which case the
+/// caller falls back to closing the connection.
+async fn try_catch_up_from_range_delta(
+    sender: &mut futures_util::stream::SplitSink<WebSocket, Message>,
+    range_delta_path: Option<&std::path::Path>,
+    last_sent_block: Option<u64>,
+) -> Option<u64> {
+    let path = range_delta_path?;
+    let data = std::fs::read(path)
+        .map_err(|e| tracing::debug!(error = %e, "failed to read range-delta file for catch-up"))
+        .ok()?;
+
+    let tier = select_tier(&data, last_sent_block.unwrap_or(0))
+        .map_err(|e| tracing::debug!(error = %e, "no range-delta tier covers this client's lag"))
+        .ok()?;
+
+    let delta = BucketDelta::from_bytes(&tier.bytes)
+        .map_err(|e| tracing::warn!(error = %e, "range-delta tier failed to parse"))
+        .ok()?;
+
+    for chunk in delta.to_chunks(MAX_CHUNK_LEN) {
+        if let Err(e) = sender.send(Message::Binary(chunk.into())).await {
+            tracing::debug!(error = %e, "failed to send range-delta catch-up chunk");
+            return None;
+        }
+    }
+
+    tracing::info!(block = delta.block_number, "caught up lagged client via range-delta");
+    Some(delta.block_number)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -184,6 +257,8 @@ mod tests {
         let broadcast = BucketBroadcast::new();
         let delta = BucketDelta {
             block_number: 1,
+            block_hash: [1u8; 32],
+            parent_hash: [0u8; 32],
             updates: vec![(0, 10)],
         };
 
@@ -199,6 +274,8 @@ mod tests {
 
         let delta = BucketDelta {
             block_number: 42,
+            block_hash: [42u8; 32],
+            parent_hash: [41u8; 32],
             updates: vec![(100, 5), (200, 10)],
         };
 
@@ -56,6 +56,11 @@ pub enum ServerError {
 
     #[error("Internal error: {0}")]
     Internal(String),
+
+    #[error(
+        "requested lag of {behind_blocks} blocks exceeds the largest available range ({max_range} blocks); fall back to a full index download"
+    )]
+    LagExceedsMaxRange { behind_blocks: u64, max_range: u32 },
 }
 
 impl ServerError {
@@ -72,6 +77,7 @@ impl ServerError {
             ServerError::Io(_) => "IO_ERROR",
             ServerError::Json(_) => "JSON_ERROR",
             ServerError::Internal(_) => "INTERNAL_ERROR",
+            ServerError::LagExceedsMaxRange { .. } => "LAG_EXCEEDS_MAX_RANGE",
         }
     }
 
@@ -88,6 +94,7 @@ impl ServerError {
             ServerError::Io(_) => StatusCode::INTERNAL_SERVER_ERROR,
             ServerError::Json(_) => StatusCode::BAD_REQUEST,
             ServerError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            ServerError::LagExceedsMaxRange { .. } => StatusCode::RANGE_NOT_SATISFIABLE,
         }
     }
 }
@@ -0,0 +1,177 @@
+//! Kafka publishing sink for bucket index deltas, partitioned by bucket range
+//!
+//! Complements [`crate::broadcast::BucketBroadcast`]: the websocket broadcast
+//! is one stream for interactive clients, while [`KafkaDeltaSink`] lets
+//! downstream consumers (indexers, analytics, mirror servers) fan out from a
+//! single producer instead of each opening their own websocket. The server's
+//! block-processing loop is expected to call both `broadcast()` and
+//! [`KafkaDeltaSink::publish`] for every delta.
+//!
+//! Gated behind the `kafka` feature since most deployments don't run a
+//! broker - the rest of the crate must not depend on `rdkafka` being present.
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use inspire_client::BucketDelta;
+use inspire_core::bucket_index::NUM_BUCKETS;
+use rdkafka::error::KafkaError;
+use rdkafka::producer::{FutureProducer, FutureRecord};
+use rdkafka::ClientConfig;
+
+/// Config for [`KafkaDeltaSink::new`], taken from server config (broker
+/// list, topic, and partition count).
+#[derive(Debug, Clone)]
+pub struct KafkaSinkConfig {
+    pub brokers: String,
+    pub topic: String,
+    pub num_partitions: u32,
+}
+
+/// Publishes bucket deltas to a Kafka topic, one partition per bucket-ID
+/// range, so a consumer can subscribe to just the range it cares about.
+pub struct KafkaDeltaSink {
+    producer: FutureProducer,
+    topic: String,
+    num_partitions: u32,
+}
+
+impl KafkaDeltaSink {
+    pub fn new(config: KafkaSinkConfig) -> Result<Self, KafkaError> {
+        let producer: FutureProducer = ClientConfig::new()
+            .set("bootstrap.servers", &config.brokers)
+            .set("message.timeout.ms", "5000")
+            .create()?;
+
+        Ok(Self {
+            producer,
+            topic: config.topic,
+            num_partitions: config.num_partitions.max(1),
+        })
+    }
+
+    /// Publish `delta`, split into per-partition sub-deltas so each lands on
+    /// the partition for its bucket range. Send failures are logged, not
+    /// propagated - a down broker must never stall block ingestion.
+    pub async fn publish(&self, delta: &BucketDelta) {
+        for (partition, sub_delta) in self.partition_delta(delta) {
+            let bytes = sub_delta.to_bytes();
+            let key = partition.to_string();
+            let record = FutureRecord::to(&self.topic)
+                .key(&key)
+                .payload(&bytes)
+                .partition(partition as i32);
+
+            if let Err((err, _)) = self.producer.send(record, Duration::from_secs(0)).await {
+                tracing::warn!(
+                    error = %err,
+                    partition,
+                    block = delta.block_number,
+                    "Failed to publish bucket delta to Kafka"
+                );
+            }
+        }
+    }
+
+    /// Group `delta`'s updates by the partition their bucket ID maps to -
+    /// `partition = bucket_id / (NUM_BUCKETS / num_partitions)` - returning
+    /// one sub-delta per partition touched, each carrying the original
+    /// block/hash metadata.
+    fn partition_delta(&self, delta: &BucketDelta) -> Vec<(u32, BucketDelta)> {
+        let buckets_per_partition = (NUM_BUCKETS as u32 / self.num_partitions).max(1);
+
+        let mut by_partition: BTreeMap<u32, Vec<(usize, u16)>> = BTreeMap::new();
+        for &(bucket_id, count) in &delta.updates {
+            let partition = ((bucket_id as u32) / buckets_per_partition).min(self.num_partitions - 1);
+            by_partition.entry(partition).or_default().push((bucket_id, count));
+        }
+
+        by_partition
+            .into_iter()
+            .map(|(partition, updates)| {
+                (
+                    partition,
+                    BucketDelta {
+                        block_number: delta.block_number,
+                        block_hash: delta.block_hash,
+                        parent_hash: delta.parent_hash,
+                        updates,
+                    },
+                )
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn delta_with(updates: Vec<(usize, u16)>) -> BucketDelta {
+        BucketDelta {
+            block_number: 1,
+            block_hash: [0u8; 32],
+            parent_hash: [0u8; 32],
+            updates,
+        }
+    }
+
+    /// `partition_delta` doesn't touch the network, so exercise it through a
+    /// sink whose producer is never used - construct one directly rather
+    /// than going through `new`, since `FutureProducer` needs no live broker
+    /// to build sub-deltas.
+    fn sink(num_partitions: u32) -> KafkaDeltaSink {
+        KafkaDeltaSink {
+            producer: ClientConfig::new()
+                .set("bootstrap.servers", "localhost:0")
+                .create()
+                .expect("client config alone shouldn't touch the network"),
+            topic: "test".to_string(),
+            num_partitions,
+        }
+    }
+
+    #[test]
+    fn test_partition_delta_groups_by_bucket_range() {
+        let sink = sink(4);
+        let buckets_per_partition = NUM_BUCKETS as u32 / 4;
+        let delta = delta_with(vec![
+            (0, 1),                                 // partition 0
+            ((buckets_per_partition) as usize, 2),  // partition 1
+            ((buckets_per_partition * 3) as usize, 3), // partition 3
+        ]);
+
+        let mut by_partition = sink.partition_delta(&delta);
+        by_partition.sort_by_key(|(p, _)| *p);
+
+        assert_eq!(by_partition.len(), 3);
+        assert_eq!(by_partition[0].0, 0);
+        assert_eq!(by_partition[1].0, 1);
+        assert_eq!(by_partition[2].0, 3);
+    }
+
+    #[test]
+    fn test_partition_delta_preserves_block_metadata() {
+        let sink = sink(2);
+        let mut delta = delta_with(vec![(0, 1)]);
+        delta.block_number = 42;
+        delta.block_hash = [9u8; 32];
+        delta.parent_hash = [8u8; 32];
+
+        let by_partition = sink.partition_delta(&delta);
+        assert_eq!(by_partition.len(), 1);
+        assert_eq!(by_partition[0].1.block_number, 42);
+        assert_eq!(by_partition[0].1.block_hash, [9u8; 32]);
+        assert_eq!(by_partition[0].1.parent_hash, [8u8; 32]);
+    }
+
+    #[test]
+    fn test_partition_delta_clamps_last_bucket_to_final_partition() {
+        let sink = sink(4);
+        let delta = delta_with(vec![(NUM_BUCKETS - 1, 1)]);
+
+        let by_partition = sink.partition_delta(&delta);
+        assert_eq!(by_partition.len(), 1);
+        assert_eq!(by_partition[0].0, 3);
+    }
+}
@@ -3,16 +3,24 @@
 //! Serves PIR queries for both hot and cold lanes, routing based on
 //! the lane specified in the request.
 
+pub mod batch;
 pub mod broadcast;
 pub mod error;
+#[cfg(feature = "kafka")]
+pub mod kafka_sink;
 pub mod metrics;
+pub mod range_delta;
 pub mod routes;
 pub mod server;
 pub mod state;
 
+pub use batch::{route_batch_handler, BatchRouteState};
 pub use broadcast::BucketBroadcast;
 pub use error::ServerError;
+#[cfg(feature = "kafka")]
+pub use kafka_sink::{KafkaDeltaSink, KafkaSinkConfig};
 pub use metrics::init_prometheus_recorder;
+pub use range_delta::{serve_range_delta, RangeDeltaQuery, RangeDeltaState};
 pub use routes::{
     create_admin_router, create_public_router, create_router, create_router_with_metrics,
 };
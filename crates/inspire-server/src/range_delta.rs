@@ -0,0 +1,111 @@
+//! Serve range-delta tiers over HTTP byte ranges
+//!
+//! `RangeDeltaWriter` (in `inspire-updater`) already maintains
+//! `bucket-deltas.bin`: a `RangeDeltaHeader` followed by a directory of
+//! `RangeEntry { offset, size, blocks_covered }` tiers, precisely so a
+//! client can download just the tier it needs via an HTTP range request
+//! instead of the whole file. This module is the server half: given a
+//! client's `since_block`, pick the smallest tier whose `blocks_covered`
+//! covers how far behind it is, and respond `206 Partial Content` with a
+//! `Content-Range` header scoped to exactly that tier's bytes.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use axum::extract::{Query, State};
+use axum::http::{header, StatusCode};
+use axum::response::{IntoResponse, Response};
+use serde::Deserialize;
+
+use inspire_core::bucket_index::range_delta::{RangeDeltaHeader, RangeEntry, HEADER_SIZE, RANGE_ENTRY_SIZE};
+
+use crate::error::ServerError;
+
+/// State backing the `/range-delta` route: the path to the range-delta
+/// file `RangeDeltaWriter` maintains.
+pub struct RangeDeltaState {
+    pub file_path: PathBuf,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RangeDeltaQuery {
+    pub since_block: u64,
+}
+
+/// A range-delta tier selected for a given `since_block`, sliced out of the
+/// full `bucket-deltas.bin` contents. Shared between [`serve_range_delta`]
+/// (the HTTP route) and `broadcast::handle_index_subscription`'s in-band lag
+/// catch-up path, so both use the same tier-selection logic.
+pub struct SelectedTier {
+    pub bytes: Vec<u8>,
+    pub start: usize,
+    pub end: usize,
+    pub total_len: usize,
+}
+
+/// Select the smallest range-delta tier covering `current_block -
+/// since_block` out of `data`, the full contents of `bucket-deltas.bin`.
+pub fn select_tier(data: &[u8], since_block: u64) -> Result<SelectedTier, ServerError> {
+    let header = RangeDeltaHeader::from_bytes(data)
+        .ok_or_else(|| ServerError::Internal("malformed range-delta header".to_string()))?;
+
+    let behind_blocks = header.current_block.saturating_sub(since_block);
+
+    let directory_start = HEADER_SIZE;
+    let entries: Vec<RangeEntry> = (0..header.num_ranges as usize)
+        .map(|i| {
+            let start = directory_start + i * RANGE_ENTRY_SIZE;
+            let end = start + RANGE_ENTRY_SIZE;
+            RangeEntry::from_bytes(&data[start..end])
+                .ok_or_else(|| ServerError::Internal("malformed range-delta directory entry".to_string()))
+        })
+        .collect::<Result<_, _>>()?;
+
+    let tiers: Vec<u32> = entries.iter().map(|e| e.blocks_covered).collect();
+    let tier_index = inspire_core::bucket_index::range_delta::select_range(behind_blocks, &tiers)
+        .ok_or_else(|| ServerError::LagExceedsMaxRange {
+            behind_blocks,
+            max_range: tiers.iter().copied().max().unwrap_or(0),
+        })?;
+
+    let entry = entries[tier_index];
+    let start = entry.offset as usize;
+    let end = start + entry.size as usize;
+    let bytes = data
+        .get(start..end)
+        .ok_or_else(|| ServerError::Internal("range-delta entry out of bounds".to_string()))?
+        .to_vec();
+
+    Ok(SelectedTier {
+        bytes,
+        start,
+        end,
+        total_len: data.len(),
+    })
+}
+
+/// Serve the smallest range-delta tier covering `current_block -
+/// since_block`, as a `206 Partial Content` response scoped to that
+/// tier's byte range within `bucket-deltas.bin`.
+pub async fn serve_range_delta(
+    State(state): State<Arc<RangeDeltaState>>,
+    Query(query): Query<RangeDeltaQuery>,
+) -> Result<Response, ServerError> {
+    let data = std::fs::read(&state.file_path)
+        .map_err(|e| ServerError::Internal(format!("failed to read range-delta file: {e}")))?;
+
+    let tier = select_tier(&data, query.since_block)?;
+    let content_range = format!(
+        "bytes {}-{}/{}",
+        tier.start,
+        tier.end.saturating_sub(1),
+        tier.total_len
+    );
+
+    Ok((
+        StatusCode::PARTIAL_CONTENT,
+        [(header::CONTENT_RANGE, content_range)],
+        tier.bytes,
+    )
+        .into_response())
+}
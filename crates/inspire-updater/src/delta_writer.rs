@@ -89,8 +89,12 @@ impl RangeDeltaWriter {
         for deltas in &self.deltas {
             let deltas_vec: Vec<BucketDelta> = deltas.iter().cloned().collect();
             let merged = if deltas_vec.is_empty() {
+                // No deltas landed for this tier, so there's no chain data
+                // to report either - the placeholder carries zeroed hashes.
                 BucketDelta {
                     block_number: self.current_block,
+                    block_hash: [0u8; 32],
+                    parent_hash: [0u8; 32],
                     updates: vec![],
                 }
             } else {
@@ -156,6 +160,8 @@ mod tests {
         for block in 1..=5 {
             let delta = BucketDelta {
                 block_number: block,
+                block_hash: [block as u8; 32],
+                parent_hash: [(block.saturating_sub(1)) as u8; 32],
                 updates: vec![(block as usize, block as u16)],
             };
             writer.add_delta(delta);
@@ -181,6 +187,8 @@ mod tests {
         for block in 1..=10 {
             let delta = BucketDelta {
                 block_number: block,
+                block_hash: [block as u8; 32],
+                parent_hash: [(block.saturating_sub(1)) as u8; 32],
                 updates: vec![(0, block as u16)],
             };
             writer.add_delta(delta);
@@ -1,14 +1,24 @@
 //! Bucket Index Builder
 //!
-//! Builds a sparse bucket index from state.bin for efficient client-side lookups.
+//! Maintains a persistent, memory-mapped bucket count store for efficient
+//! client-side lookups. Unlike a one-shot builder, the store (see
+//! [`lane_builder::mmap_store::BucketStore`]) can be updated in place: running
+//! this binary again against a delta of new `state.bin` records applies just
+//! those entries via `allocate`, instead of recomputing all 256K
+//! bucket counts from scratch.
 //!
 //! Input: state.bin (84-byte records: [address:20][slot:32][value:32])
-//! Output: bucket-index.bin (256K buckets × 2 bytes = 512 KB)
+//! Output: bucket-store.bin (persistent mmap store, see `mmap_store`)
+//!         bucket-index.bin (compact snapshot for client distribution)
 //!
 //! Usage:
+//!   # First run: builds the store from a full state dump
 //!   cargo run --bin bucket-index --features bucket-index -- \
-//!     --input state.bin \
-//!     --output bucket-index.bin
+//!     --input state.bin --store bucket-store.bin --output bucket-index.bin
+//!
+//!   # Later runs: apply just the new records since the last run
+//!   cargo run --bin bucket-index --features bucket-index -- \
+//!     --input state-delta.bin --store bucket-store.bin --output bucket-index.bin
 
 #![cfg(feature = "bucket-index")]
 
@@ -18,6 +28,7 @@ use std::path::PathBuf;
 
 use clap::Parser;
 use indicatif::{ProgressBar, ProgressStyle};
+use lane_builder::mmap_store::BucketStore;
 use tiny_keccak::{Hasher, Keccak};
 
 /// Number of buckets (2^18 = 256K)
@@ -27,21 +38,52 @@ const BUCKET_BITS: usize = 18;
 /// Size of each input record (address:20 + slot:32 + value:32)
 const RECORD_SIZE: usize = 84;
 
+/// Default open-addressing load factor for within-bucket slot arrays (see
+/// `inspire_client::bucket_index`, which this builder's output format
+/// matches).
+const DEFAULT_SLOT_LOAD_FACTOR: f64 = 0.75;
+/// Default bound on consecutive slots probed by `lookup_exact`.
+const DEFAULT_MAX_SEARCH: usize = 8;
+/// Sentinel `local_offset` marking an empty open-addressing slot.
+const EMPTY_SLOT: u16 = u16::MAX;
+
+/// Magic bytes for the self-describing compressed index envelope (must match
+/// `inspire_client::bucket_index`'s reader).
+const ENVELOPE_MAGIC: &[u8; 4] = b"BIDX";
+/// Current compressed index envelope version.
+const ENVELOPE_VERSION: u32 = 2;
+
 #[derive(Parser, Debug)]
 #[command(name = "bucket-index")]
-#[command(about = "Build bucket index from state.bin for sparse PIR lookups")]
+#[command(about = "Apply state.bin records to a persistent bucket count store")]
 struct Args {
-    /// Input state.bin file (84-byte records)
+    /// Input state.bin file (84-byte records). On a fresh store this should
+    /// be the full state; on subsequent runs, just the records new since the
+    /// last run.
     #[arg(long)]
     input: PathBuf,
 
-    /// Output bucket index file
+    /// Persistent mmap bucket store (created if it doesn't exist)
+    #[arg(long, default_value = "bucket-store.bin")]
+    store: PathBuf,
+
+    /// Output compact bucket index snapshot for client distribution
     #[arg(long, default_value = "bucket-index.bin")]
     output: PathBuf,
 
-    /// Also output compressed version (zstd)
+    /// Also output compressed version (zstd) of the compact snapshot
     #[arg(long)]
     compress: bool,
+
+    /// Load factor for within-bucket open-addressing slot arrays (only
+    /// used to build slot arrays on a fresh build)
+    #[arg(long, default_value_t = DEFAULT_SLOT_LOAD_FACTOR)]
+    slot_load_factor: f64,
+
+    /// Max consecutive slots to probe when placing an entry; the build fails
+    /// if an entry can't be placed within this many probes
+    #[arg(long, default_value_t = DEFAULT_MAX_SEARCH)]
+    max_search: usize,
 }
 
 fn main() -> anyhow::Result<()> {
@@ -51,10 +93,23 @@ fn main() -> anyhow::Result<()> {
 
     tracing::info!(
         input = %args.input.display(),
-        output = %args.output.display(),
-        "Building bucket index"
+        store = %args.store.display(),
+        "Applying records to bucket store"
     );
 
+    // Within-bucket slots can only be rebuilt from a full pass over every
+    // entry a bucket contains, so they're only derivable on a fresh build —
+    // capture that before `store.exists()` stops reflecting it.
+    let is_fresh_store = !args.store.exists();
+
+    let mut store = if !is_fresh_store {
+        tracing::info!("Resuming existing bucket store");
+        BucketStore::open(&args.store)?
+    } else {
+        tracing::info!(buckets = NUM_BUCKETS, "Creating new bucket store");
+        BucketStore::create(&args.store, NUM_BUCKETS)?
+    };
+
     // Get file size to calculate entry count
     let file_size = std::fs::metadata(&args.input)?.len() as usize;
     let entry_count = file_size / RECORD_SIZE;
@@ -69,9 +124,6 @@ fn main() -> anyhow::Result<()> {
 
     tracing::info!(entry_count, "Processing entries");
 
-    // Initialize bucket counts
-    let mut bucket_counts: Vec<u32> = vec![0; NUM_BUCKETS];
-
     // Read and process entries
     let file = File::open(&args.input)?;
     let mut reader = BufReader::with_capacity(64 * 1024 * 1024, file);
@@ -98,7 +150,7 @@ fn main() -> anyhow::Result<()> {
 
         // Compute keccak256(address || slot)
         let bucket_id = compute_bucket_id(address, slot);
-        bucket_counts[bucket_id] += 1;
+        store.allocate(bucket_id)?;
 
         processed += 1;
         if processed % 1_000_000 == 0 {
@@ -107,74 +159,98 @@ fn main() -> anyhow::Result<()> {
     }
 
     pb.finish_with_message("Done processing entries");
+    store.flush()?;
 
-    // Validate counts
-    let total: u64 = bucket_counts.iter().map(|&c| c as u64).sum();
     tracing::info!(
-        total_entries = total,
-        buckets = NUM_BUCKETS,
-        avg_per_bucket = total as f64 / NUM_BUCKETS as f64,
-        "Bucket distribution computed"
+        total_entries = store.entry_count(),
+        buckets = store.num_buckets(),
+        avg_per_bucket = store.entry_count() as f64 / store.num_buckets() as f64,
+        "Bucket distribution updated"
     );
 
-    // Check for overflow (count > u16::MAX)
-    let max_count = *bucket_counts.iter().max().unwrap_or(&0);
-    if max_count > u16::MAX as u32 {
-        tracing::error!(
-            max_count,
-            "Bucket count overflow! Max count {} exceeds u16::MAX. Need larger count type.",
-            max_count
+    write_compact_snapshot(&store, &args, is_fresh_store)?;
+
+    Ok(())
+}
+
+/// Write a compact 2-byte-per-bucket snapshot for client distribution,
+/// clamping any bucket that has outgrown `u16` (the store itself tracks the
+/// true count without truncation).
+fn write_compact_snapshot(store: &BucketStore, args: &Args, is_fresh_store: bool) -> anyhow::Result<()> {
+    let mut counts: Vec<u16> = Vec::with_capacity(store.num_buckets());
+    let mut clamped = 0usize;
+    for bucket_id in 0..store.num_buckets() {
+        let count = store.count(bucket_id);
+        if count > u16::MAX as u64 {
+            clamped += 1;
+            counts.push(u16::MAX);
+        } else {
+            counts.push(count as u16);
+        }
+    }
+
+    if clamped > 0 {
+        tracing::warn!(
+            clamped,
+            "Some buckets exceed u16::MAX; compact snapshot clamps them (full counts remain in the store)"
         );
-        return Err(anyhow::anyhow!("Bucket count overflow"));
     }
 
-    // Write bucket index (2 bytes per bucket)
     let output_file = File::create(&args.output)?;
     let mut writer = BufWriter::new(output_file);
-
-    for &count in &bucket_counts {
-        writer.write_all(&(count as u16).to_le_bytes())?;
+    for &count in &counts {
+        writer.write_all(&count.to_le_bytes())?;
     }
     writer.flush()?;
 
-    let output_size = NUM_BUCKETS * 2;
+    let output_size = counts.len() * 2;
     tracing::info!(
         output = %args.output.display(),
         size_bytes = output_size,
         size_kb = output_size / 1024,
-        "Wrote bucket index"
+        "Wrote compact bucket index snapshot"
     );
 
-    // Optionally compress with zstd
     if args.compress {
-        let compressed_path = args.output.with_extension("bin.zst");
-        let raw_data: Vec<u8> = bucket_counts
-            .iter()
-            .flat_map(|&c| (c as u16).to_le_bytes())
-            .collect();
-
-        let compressed = zstd::encode_all(&raw_data[..], 19)?; // Level 19 = high compression
+        let slots = if is_fresh_store {
+            tracing::info!(
+                load_factor = args.slot_load_factor,
+                max_search = args.max_search,
+                "Building within-bucket slot arrays"
+            );
+            Some(build_slots_from_input(
+                &args.input,
+                &counts,
+                args.slot_load_factor,
+                args.max_search,
+            )?)
+        } else {
+            tracing::warn!(
+                "Skipping within-bucket slot rebuild on an incremental update; \
+                 lookup_exact will be unavailable until a full rebuild refreshes it"
+            );
+            None
+        };
 
+        let compressed_path = args.output.with_extension("bin.zst");
+        let envelope = build_envelope(&counts, slots.as_deref(), args.slot_load_factor, args.max_search);
+        let compressed = zstd::encode_all(&envelope[..], 19)?;
         std::fs::write(&compressed_path, &compressed)?;
 
         tracing::info!(
             output = %compressed_path.display(),
-            uncompressed = output_size,
+            envelope_bytes = envelope.len(),
             compressed = compressed.len(),
-            ratio = format!("{:.1}%", compressed.len() as f64 / output_size as f64 * 100.0),
-            "Wrote compressed bucket index"
+            ratio = format!("{:.1}%", compressed.len() as f64 / envelope.len() as f64 * 100.0),
+            has_slots = slots.is_some(),
+            "Wrote compressed bucket index snapshot"
         );
     }
 
-    // Print statistics
-    let non_empty = bucket_counts.iter().filter(|&&c| c > 0).count();
-    let min_count = *bucket_counts.iter().filter(|&&c| c > 0).min().unwrap_or(&0);
-
+    let non_empty = counts.iter().filter(|&&c| c > 0).count();
     tracing::info!(
         non_empty_buckets = non_empty,
-        empty_buckets = NUM_BUCKETS - non_empty,
-        min_count,
-        max_count,
+        empty_buckets = counts.len() - non_empty,
         "Bucket statistics"
     );
 
@@ -198,6 +274,135 @@ fn compute_bucket_id(address: &[u8], slot: &[u8]) -> usize {
     bucket_id & (NUM_BUCKETS - 1) // Mask to ensure in range
 }
 
+/// Compute a second, independent 16-bit hash of (address, slot) for
+/// within-bucket open-addressing slots (see `inspire_client::bucket_index`,
+/// whose `compute_slot_tag` this mirrors).
+fn compute_slot_tag(address: &[u8], slot: &[u8]) -> u16 {
+    let mut hasher = Keccak::v256();
+    hasher.update(slot);
+    hasher.update(address);
+
+    let mut hash = [0u8; 32];
+    hasher.finalize(&mut hash);
+
+    u16::from_le_bytes([hash[30], hash[31]])
+}
+
+/// Number of open-addressing slots for a bucket holding `count` entries at
+/// `load_factor`.
+fn slots_for_count(count: u64, load_factor: f64) -> u64 {
+    if count == 0 {
+        return 0;
+    }
+    ((count as f64) / load_factor).ceil() as u64
+}
+
+/// Cumulative within-bucket slot-array offsets, mirroring `store`'s counts.
+fn compute_slot_starts(counts: &[u16], load_factor: f64) -> Vec<u64> {
+    let mut starts = Vec::with_capacity(counts.len() + 1);
+    starts.push(0);
+
+    let mut sum = 0u64;
+    for &count in counts {
+        sum += slots_for_count(count as u64, load_factor);
+        starts.push(sum);
+    }
+
+    starts
+}
+
+/// Re-read `input` (a second, cheap sequential pass now that `counts` is
+/// known) and place every entry into its bucket's open-addressing slot
+/// array in encounter order, matching the physical bucket-ID-ordered layout
+/// the PIR database assumes.
+fn build_slots_from_input(
+    input: &PathBuf,
+    counts: &[u16],
+    load_factor: f64,
+    max_search: usize,
+) -> anyhow::Result<Vec<(u16, u16)>> {
+    let slot_starts = compute_slot_starts(counts, load_factor);
+    let total_slots = *slot_starts.last().unwrap_or(&0) as usize;
+    let mut slots = vec![(0u16, EMPTY_SLOT); total_slots];
+    let mut next_local_offset = vec![0u16; counts.len()];
+
+    let file = File::open(input)?;
+    let mut reader = BufReader::with_capacity(64 * 1024 * 1024, file);
+    let mut record = [0u8; RECORD_SIZE];
+
+    loop {
+        match reader.read_exact(&mut record) {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => break,
+            Err(e) => return Err(e.into()),
+        }
+
+        let address = &record[0..20];
+        let slot = &record[20..52];
+
+        let bucket_id = compute_bucket_id(address, slot);
+        let bucket_slot_start = slot_starts[bucket_id] as usize;
+        let bucket_slot_count = (slot_starts[bucket_id + 1] - slot_starts[bucket_id]) as usize;
+
+        let local_offset = next_local_offset[bucket_id];
+        next_local_offset[bucket_id] += 1;
+
+        if bucket_slot_count == 0 {
+            continue;
+        }
+
+        let tag = compute_slot_tag(address, slot);
+        let start = (tag as usize) % bucket_slot_count;
+
+        let placed = (0..max_search.min(bucket_slot_count)).find(|&probe| {
+            let slot_idx = bucket_slot_start + (start + probe) % bucket_slot_count;
+            slots[slot_idx].1 == EMPTY_SLOT
+        });
+
+        match placed {
+            Some(probe) => {
+                let slot_idx = bucket_slot_start + (start + probe) % bucket_slot_count;
+                slots[slot_idx] = (tag, local_offset);
+            }
+            None => {
+                anyhow::bail!(
+                    "could not place an entry for bucket {bucket_id} within {max_search} probes; \
+                     retry with a lower --slot-load-factor or a larger --max-search"
+                );
+            }
+        }
+    }
+
+    Ok(slots)
+}
+
+/// Build the self-describing envelope consumed by
+/// `inspire_client::bucket_index::BucketIndex::from_compressed`.
+fn build_envelope(counts: &[u16], slots: Option<&[(u16, u16)]>, load_factor: f64, max_search: usize) -> Vec<u8> {
+    let mut buf = Vec::with_capacity(
+        18 + counts.len() * 2 + slots.map_or(0, |s| s.len() * 4),
+    );
+
+    buf.extend_from_slice(ENVELOPE_MAGIC);
+    buf.extend_from_slice(&ENVELOPE_VERSION.to_le_bytes());
+    buf.extend_from_slice(&load_factor.to_bits().to_le_bytes());
+    buf.push(max_search.min(u8::MAX as usize) as u8);
+    buf.push(slots.is_some() as u8);
+
+    for &count in counts {
+        buf.extend_from_slice(&count.to_le_bytes());
+    }
+
+    if let Some(slots) = slots {
+        for &(tag, local_offset) in slots {
+            buf.extend_from_slice(&tag.to_le_bytes());
+            buf.extend_from_slice(&local_offset.to_le_bytes());
+        }
+    }
+
+    buf
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
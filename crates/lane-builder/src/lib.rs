@@ -0,0 +1,5 @@
+//! lane-builder: tooling for building and maintaining PIR lane databases
+
+pub mod mmap_store;
+
+pub use mmap_store::BucketStore;
@@ -0,0 +1,315 @@
+//! Persistent, incrementally updatable mmap-backed bucket count store
+//!
+//! Replaces the one-shot `bucket-index` builder's in-memory `Vec<u16>` with a
+//! memory-mapped file that can be updated in place as new `state.bin` records
+//! arrive, without recomputing all 256K bucket counts from scratch.
+//!
+//! ## File layout
+//!
+//! ```text
+//! [Header: HEADER_SIZE bytes]
+//!   magic: "BKTS" (4 bytes)
+//!   version: u32
+//!   num_buckets: u64
+//!   cell_size: u32     (bytes per bucket cell, starts at 4 and only grows)
+//!   entry_count: u64   (total entries across all buckets)
+//!   reserved: padding to HEADER_SIZE
+//!
+//! [Cells: num_buckets * cell_size bytes]
+//!   Each cell is a little-endian unsigned integer bucket count.
+//! ```
+//!
+//! Cells start at 4 bytes (`u32`) - wide enough that overflow is not expected
+//! in practice, but `allocate` still doubles `cell_size` and migrates every
+//! cell in place if a count would ever exceed the current cell width, so the
+//! format never needs an ad-hoc panic for buckets that grow unexpectedly
+//! large.
+
+use std::fs::{File, OpenOptions};
+use std::io;
+use std::path::Path;
+
+use memmap2::{MmapMut, MmapOptions};
+
+/// Magic bytes identifying a bucket store file
+pub const MAGIC: &[u8; 4] = b"BKTS";
+
+/// Current file format version
+pub const VERSION: u32 = 1;
+
+/// Fixed header size in bytes
+pub const HEADER_SIZE: usize = 32;
+
+/// Initial (and minimum) cell width in bytes
+const INITIAL_CELL_SIZE: usize = 4;
+
+/// A persistent, memory-mapped store of per-bucket entry counts.
+pub struct BucketStore {
+    file: File,
+    mmap: MmapMut,
+    num_buckets: usize,
+    cell_size: usize,
+}
+
+impl BucketStore {
+    /// Create a new bucket store with `num_buckets` cells, all initialized to zero.
+    pub fn create<P: AsRef<Path>>(path: P, num_buckets: usize) -> io::Result<Self> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path)?;
+
+        let total_size = HEADER_SIZE + num_buckets * INITIAL_CELL_SIZE;
+        file.set_len(total_size as u64)?;
+
+        let mut mmap = unsafe { MmapMut::map_mut(&file)? };
+        write_header(
+            &mut mmap,
+            num_buckets as u64,
+            INITIAL_CELL_SIZE as u32,
+            0,
+        );
+
+        Ok(Self {
+            file,
+            mmap,
+            num_buckets,
+            cell_size: INITIAL_CELL_SIZE,
+        })
+    }
+
+    /// Open an existing bucket store file, applying deltas against it.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let file = OpenOptions::new().read(true).write(true).open(path)?;
+        let mmap = unsafe { MmapMut::map_mut(&file)? };
+
+        if mmap.len() < HEADER_SIZE || &mmap[0..4] != MAGIC {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "not a bucket store file (bad magic)",
+            ));
+        }
+        let version = u32::from_le_bytes(mmap[4..8].try_into().unwrap());
+        if version != VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("unsupported bucket store version {version}"),
+            ));
+        }
+        let num_buckets = u64::from_le_bytes(mmap[8..16].try_into().unwrap()) as usize;
+        let cell_size = u32::from_le_bytes(mmap[16..20].try_into().unwrap()) as usize;
+
+        Ok(Self {
+            file,
+            mmap,
+            num_buckets,
+            cell_size,
+        })
+    }
+
+    /// Number of buckets in this store.
+    pub fn num_buckets(&self) -> usize {
+        self.num_buckets
+    }
+
+    /// Total entries across all buckets.
+    pub fn entry_count(&self) -> u64 {
+        u64::from_le_bytes(self.mmap[20..28].try_into().unwrap())
+    }
+
+    fn set_entry_count(&mut self, value: u64) {
+        self.mmap[20..28].copy_from_slice(&value.to_le_bytes());
+    }
+
+    fn cell_offset(&self, bucket_id: usize) -> usize {
+        HEADER_SIZE + bucket_id * self.cell_size
+    }
+
+    /// Read the count for `bucket_id`.
+    pub fn count(&self, bucket_id: usize) -> u64 {
+        assert!(bucket_id < self.num_buckets, "bucket_id out of range");
+        let offset = self.cell_offset(bucket_id);
+        read_cell(&self.mmap[offset..offset + self.cell_size])
+    }
+
+    /// Increment the count for `bucket_id` by one, growing the cell width
+    /// (and migrating every cell) in place if the increment would overflow
+    /// the current width.
+    pub fn allocate(&mut self, bucket_id: usize) -> io::Result<()> {
+        assert!(bucket_id < self.num_buckets, "bucket_id out of range");
+
+        if self.count(bucket_id) == max_for_width(self.cell_size) {
+            self.grow()?;
+        }
+
+        let offset = self.cell_offset(bucket_id);
+        let new_value = read_cell(&self.mmap[offset..offset + self.cell_size]) + 1;
+        write_cell(&mut self.mmap[offset..offset + self.cell_size], new_value);
+
+        let entries = self.entry_count() + 1;
+        self.set_entry_count(entries);
+        Ok(())
+    }
+
+    /// Decrement the count for `bucket_id` by one.
+    pub fn free(&mut self, bucket_id: usize) {
+        assert!(bucket_id < self.num_buckets, "bucket_id out of range");
+        let current = self.count(bucket_id);
+        assert!(current > 0, "cannot free an empty bucket");
+
+        let offset = self.cell_offset(bucket_id);
+        write_cell(&mut self.mmap[offset..offset + self.cell_size], current - 1);
+
+        let entries = self.entry_count() - 1;
+        self.set_entry_count(entries);
+    }
+
+    /// Double the cell width and migrate every existing count into the wider
+    /// layout, resizing the backing file in place.
+    fn grow(&mut self) -> io::Result<()> {
+        let old_cell_size = self.cell_size;
+        let new_cell_size = old_cell_size * 2;
+
+        let old_counts: Vec<u64> = (0..self.num_buckets).map(|id| self.count(id)).collect();
+
+        let new_total = HEADER_SIZE + self.num_buckets * new_cell_size;
+        self.file.set_len(new_total as u64)?;
+        self.mmap = unsafe { MmapMut::map_mut(&self.file)? };
+
+        self.cell_size = new_cell_size;
+        self.mmap[16..20].copy_from_slice(&(new_cell_size as u32).to_le_bytes());
+
+        for (bucket_id, value) in old_counts.into_iter().enumerate() {
+            let offset = self.cell_offset(bucket_id);
+            write_cell(&mut self.mmap[offset..offset + new_cell_size], value);
+        }
+
+        Ok(())
+    }
+
+    /// Flush pending writes to disk.
+    pub fn flush(&self) -> io::Result<()> {
+        self.mmap.flush()
+    }
+}
+
+fn write_header(mmap: &mut MmapMut, num_buckets: u64, cell_size: u32, entry_count: u64) {
+    mmap[0..4].copy_from_slice(MAGIC);
+    mmap[4..8].copy_from_slice(&VERSION.to_le_bytes());
+    mmap[8..16].copy_from_slice(&num_buckets.to_le_bytes());
+    mmap[16..20].copy_from_slice(&cell_size.to_le_bytes());
+    mmap[20..28].copy_from_slice(&entry_count.to_le_bytes());
+}
+
+fn max_for_width(cell_size: usize) -> u64 {
+    if cell_size >= 8 {
+        u64::MAX
+    } else {
+        (1u64 << (cell_size * 8)) - 1
+    }
+}
+
+fn read_cell(bytes: &[u8]) -> u64 {
+    let mut buf = [0u8; 8];
+    buf[..bytes.len()].copy_from_slice(bytes);
+    u64::from_le_bytes(buf)
+}
+
+fn write_cell(bytes: &mut [u8], value: u64) {
+    let buf = value.to_le_bytes();
+    bytes.copy_from_slice(&buf[..bytes.len()]);
+}
+
+/// Read-only memory map of a bucket store, for fast bulk scans.
+pub fn open_readonly<P: AsRef<Path>>(path: P) -> io::Result<memmap2::Mmap> {
+    let file = File::open(path)?;
+    unsafe { MmapOptions::new().map(&file) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_create_and_count_zero() {
+        let dir = tempdir().unwrap();
+        let store = BucketStore::create(dir.path().join("buckets.bin"), 16).unwrap();
+        assert_eq!(store.num_buckets(), 16);
+        assert_eq!(store.count(0), 0);
+        assert_eq!(store.entry_count(), 0);
+    }
+
+    #[test]
+    fn test_allocate_and_free() {
+        let dir = tempdir().unwrap();
+        let mut store = BucketStore::create(dir.path().join("buckets.bin"), 16).unwrap();
+
+        store.allocate(3).unwrap();
+        store.allocate(3).unwrap();
+        store.allocate(5).unwrap();
+
+        assert_eq!(store.count(3), 2);
+        assert_eq!(store.count(5), 1);
+        assert_eq!(store.entry_count(), 3);
+
+        store.free(3);
+        assert_eq!(store.count(3), 1);
+        assert_eq!(store.entry_count(), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot free an empty bucket")]
+    fn test_free_empty_bucket_panics() {
+        let dir = tempdir().unwrap();
+        let mut store = BucketStore::create(dir.path().join("buckets.bin"), 4).unwrap();
+        store.free(0);
+    }
+
+    #[test]
+    fn test_open_roundtrip() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("buckets.bin");
+
+        {
+            let mut store = BucketStore::create(&path, 8).unwrap();
+            store.allocate(1).unwrap();
+            store.allocate(1).unwrap();
+            store.flush().unwrap();
+        }
+
+        let store = BucketStore::open(&path).unwrap();
+        assert_eq!(store.num_buckets(), 8);
+        assert_eq!(store.count(1), 2);
+        assert_eq!(store.entry_count(), 2);
+    }
+
+    #[test]
+    fn test_grow_migrates_existing_counts() {
+        let dir = tempdir().unwrap();
+        let mut store = BucketStore::create(dir.path().join("buckets.bin"), 4).unwrap();
+
+        // Shrink to a 1-byte cell already holding 255, so the next allocate
+        // overflows it.
+        store.cell_size = 1;
+        store.mmap[16..20].copy_from_slice(&1u32.to_le_bytes());
+        write_cell(&mut store.mmap[HEADER_SIZE..HEADER_SIZE + 1], 255);
+
+        store.allocate(0).unwrap();
+
+        assert_eq!(store.cell_size, 2);
+        assert_eq!(store.count(0), 256);
+    }
+
+    #[test]
+    fn test_allocate_out_of_range_panics() {
+        let dir = tempdir().unwrap();
+        let mut store = BucketStore::create(dir.path().join("buckets.bin"), 4).unwrap();
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            store.allocate(10).unwrap();
+        }));
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,188 @@
+//! Bounded LRU cache layer over [`HintStore`]
+//!
+//! `HintStore` keeps every hint resident and `rebuild_index` fully expands
+//! every subset up front, so memory scales linearly with the number of
+//! accumulated snapshots, and `Subset::expand`'s PRF walk reruns on every
+//! `find_hint_for_target`/`contains` call. [`BoundedHintStore`] caps the
+//! resident hint set, the expanded-index map, and memoized `Subset::expand`
+//! results at a configurable capacity, evicting least-recently-used entries.
+//! An evicted hint is reloaded lazily - by re-reading the on-disk bincode
+//! store - the next time it's needed, so a long-running client's memory
+//! footprint stays bounded instead of growing with every snapshot it's
+//! downloaded.
+
+use std::num::NonZeroUsize;
+use std::path::PathBuf;
+
+use lru::LruCache;
+
+use crate::hint_store::{HintStore, StoredHint};
+
+/// Capacity-bounded, expand-memoizing view over a [`HintStore`] persisted
+/// on disk at a fixed path.
+pub struct BoundedHintStore {
+    store_path: PathBuf,
+    block_number: u64,
+    hint_count: usize,
+    hints: LruCache<usize, StoredHint>,
+    /// Memoizes `Subset::expand()` per hint id, so repeated lookups don't
+    /// rerun the PRF.
+    expand_cache: LruCache<usize, Vec<u64>>,
+    /// Bounded counterpart of `HintStore::index`: target index -> hint ids
+    /// known to contain it, populated lazily as lookups resolve.
+    index: LruCache<u64, Vec<usize>>,
+}
+
+impl BoundedHintStore {
+    /// Open the bincode store at `path`, keeping at most `capacity` hints
+    /// (and as many expanded-index/memoization entries) resident at once.
+    pub fn open(path: impl Into<PathBuf>, capacity: NonZeroUsize) -> anyhow::Result<Self> {
+        let path = path.into();
+        let full = HintStore::load(&path)?;
+        let block_number = full.block_number;
+        let hint_count = full.hints.len();
+
+        let mut hints = LruCache::new(capacity);
+        for (id, stored) in full.hints.into_iter().enumerate().take(capacity.get()) {
+            hints.put(id, stored);
+        }
+
+        Ok(Self {
+            store_path: path,
+            block_number,
+            hint_count,
+            hints,
+            expand_cache: LruCache::new(capacity),
+            index: LruCache::new(capacity),
+        })
+    }
+
+    pub fn block_number(&self) -> u64 {
+        self.block_number
+    }
+
+    pub fn hint_count(&self) -> usize {
+        self.hint_count
+    }
+
+    /// Get hint `id`, reloading it from disk on a cache miss.
+    fn hint(&mut self, id: usize) -> Option<&StoredHint> {
+        if self.hints.get(&id).is_none() {
+            let reloaded = self.reload_hint(id)?;
+            self.hints.put(id, reloaded);
+        }
+        self.hints.get(&id)
+    }
+
+    /// Re-read the on-disk store to recover a hint evicted from the
+    /// resident cache. `HintStore`'s bincode format has no random access,
+    /// so this re-reads the whole file - acceptable since it only happens
+    /// on a miss, and keeps the *resident* footprint bounded regardless.
+    fn reload_hint(&self, id: usize) -> Option<StoredHint> {
+        let full = HintStore::load(&self.store_path).ok()?;
+        full.hints.into_iter().nth(id)
+    }
+
+    /// Memoized `Subset::expand()` for hint `id`.
+    fn expand(&mut self, id: usize) -> Option<Vec<u64>> {
+        if let Some(cached) = self.expand_cache.get(&id) {
+            return Some(cached.clone());
+        }
+        let expanded = self.hint(id)?.subset.expand();
+        self.expand_cache.put(id, expanded.clone());
+        Some(expanded)
+    }
+
+    /// Whether hint `id`'s subset contains `target`, memoizing the
+    /// expansion behind the same LRU `find_hint_for_target` uses.
+    pub fn contains(&mut self, id: usize, target: u64) -> bool {
+        self.expand(id)
+            .map(|indices| indices.contains(&target))
+            .unwrap_or(false)
+    }
+
+    /// Find a hint id whose subset contains `target`, consulting (and
+    /// populating) the bounded index before falling back to a linear scan
+    /// that memoizes each hint's expansion as it goes.
+    pub fn find_hint_for_target(&mut self, target: u64) -> Option<usize> {
+        if let Some(hint_ids) = self.index.get(&target) {
+            if let Some(&id) = hint_ids.first() {
+                return Some(id);
+            }
+        }
+
+        for id in 0..self.hint_count {
+            if self.contains(id, target) {
+                self.index.put(target, vec![id]);
+                return Some(id);
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pir_core::subset::Subset;
+
+    fn write_test_store(hints: Vec<[u8; 32]>, block_number: u64) -> tempfile::NamedTempFile {
+        let mut store = HintStore::new();
+        let subsets_and_hints = hints
+            .into_iter()
+            .enumerate()
+            .map(|(i, hint)| {
+                let mut seed = [0u8; 32];
+                seed[..8].copy_from_slice(&(i as u64).to_le_bytes());
+                (Subset::new(seed, 4, 1_000), hint)
+            })
+            .collect();
+        store.add_hints(subsets_and_hints, block_number);
+
+        let file = tempfile::NamedTempFile::new().unwrap();
+        store.save(file.path()).unwrap();
+        file
+    }
+
+    #[test]
+    fn test_open_reads_block_number_and_hint_count() {
+        let file = write_test_store(vec![[1u8; 32], [2u8; 32], [3u8; 32]], 42);
+        let store = BoundedHintStore::open(file.path(), NonZeroUsize::new(2).unwrap()).unwrap();
+
+        assert_eq!(store.block_number(), 42);
+        assert_eq!(store.hint_count(), 3);
+    }
+
+    #[test]
+    fn test_reloads_evicted_hint_from_disk() {
+        let file = write_test_store(vec![[1u8; 32], [2u8; 32], [3u8; 32]], 0);
+        let mut store = BoundedHintStore::open(file.path(), NonZeroUsize::new(1).unwrap()).unwrap();
+
+        // Capacity 1: warming with hint 0 evicts nothing else yet, but
+        // accessing hint 2 must evict hint 0 and still succeed by
+        // reloading from disk.
+        assert!(store.hint(2).is_some());
+        assert!(store.hint(0).is_some());
+    }
+
+    #[test]
+    fn test_find_hint_for_target_matches_linear_scan() {
+        let file = write_test_store(vec![[1u8; 32], [2u8; 32]], 0);
+        let mut store = BoundedHintStore::open(file.path(), NonZeroUsize::new(1).unwrap()).unwrap();
+
+        let full = HintStore::load(file.path()).unwrap();
+        let target = full.hints[1].subset.expand()[0];
+
+        assert_eq!(store.find_hint_for_target(target), Some(1));
+    }
+
+    #[test]
+    fn test_expand_is_memoized_across_calls() {
+        let file = write_test_store(vec![[1u8; 32]], 0);
+        let mut store = BoundedHintStore::open(file.path(), NonZeroUsize::new(4).unwrap()).unwrap();
+
+        let first = store.expand(0).unwrap();
+        let second = store.expand(0).unwrap();
+        assert_eq!(first, second);
+    }
+}
@@ -17,6 +17,10 @@ pub struct StoredHint {
 pub struct HintStore {
     /// Block number of the snapshot
     pub block_number: u64,
+    /// Merkle root the current `hints` were last verified against, via
+    /// either `SyncClient::download_hints` (bulk) or `SyncClient::sync_deltas`
+    /// (incremental). `[0u8; 32]` if the store was never verified.
+    pub merkle_root: [u8; 32],
     /// All stored hints
     pub hints: Vec<StoredHint>,
     /// Index: target_index -> hint_ids that contain it
@@ -57,21 +61,25 @@ impl HintStore {
 
     /// Find a hint that contains the target index
     pub fn find_hint_for_target(&self, target: u64) -> Option<&StoredHint> {
+        self.find_hint_index_for_target(target)
+            .and_then(|id| self.hints.get(id))
+    }
+
+    /// Find the position (within `self.hints`) of a hint that contains the
+    /// target index. Exposed alongside `find_hint_for_target` so callers
+    /// that need to address a specific hint in a [`crate::merkle::MerkleTree`]
+    /// built over `self.hints` (e.g. for an inclusion proof) don't have to
+    /// re-scan to recover its index.
+    pub fn find_hint_index_for_target(&self, target: u64) -> Option<usize> {
         // First check index
         if let Some(hint_ids) = self.index.get(&target) {
             if let Some(&id) = hint_ids.first() {
-                return self.hints.get(id);
+                return Some(id);
             }
         }
-        
+
         // Fallback to linear scan
-        for hint in &self.hints {
-            if hint.subset.contains(target) {
-                return Some(hint);
-            }
-        }
-        
-        None
+        self.hints.iter().position(|hint| hint.subset.contains(target))
     }
 
     /// Rebuild the index (called after loading or adding hints)
@@ -0,0 +1,23 @@
+//! PIR Client - Hint storage and query construction for Dummy Subsets PIR
+//!
+//! This crate provides:
+//! - Local hint storage with target-index lookup
+//! - A capacity-bounded LRU cache layer over that storage, for long-running
+//!   clients with more accumulated hints than fit comfortably in memory
+//! - DHT/IPFS hint synchronization with Merkle-verified integrity
+//! - Live hint-delta streaming over WebSocket, resuming after disconnects
+//! - Query construction and response recovery
+
+pub mod cache;
+pub mod hint_store;
+pub mod live;
+pub mod merkle;
+pub mod query;
+pub mod rlp;
+pub mod sync;
+
+pub use cache::BoundedHintStore;
+pub use hint_store::HintStore;
+pub use live::{LiveHintStream, LiveHintStreamError};
+pub use query::PirClient;
+pub use sync::{HintDelta, HintManifest, SyncClient};
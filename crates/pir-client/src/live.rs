@@ -0,0 +1,217 @@
+//! Live hint-delta streaming over WebSocket
+//!
+//! `SyncClient::sync_deltas` only pulls deltas the caller already knows the
+//! CIDs for. `LiveHintStream` instead subscribes to a server-pushed delta
+//! feed over a websocket, verifying and applying each [`HintDelta`] to a
+//! [`HintStore`] the same way `sync_deltas` does as it arrives, and tracking
+//! the last applied block so a dropped connection resumes from where it left
+//! off (via a `?since=` backfill parameter) instead of replaying the whole
+//! feed.
+
+use futures_util::{SinkExt, StreamExt};
+use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite::Message;
+
+use crate::hint_store::HintStore;
+use crate::sync::{HintDelta, SyncClient};
+
+/// Streams [`HintDelta`]s from a server and applies them to a [`HintStore`].
+pub struct LiveHintStream {
+    ws_url: String,
+    last_applied_block: Option<u64>,
+}
+
+impl LiveHintStream {
+    /// `ws_url` is the server's delta subscription endpoint.
+    /// `last_applied_block` should be `store.block_number` if `store` was
+    /// already populated (e.g. via `SyncClient::download_hints`), so the
+    /// first connection backfills from there instead of from genesis.
+    pub fn new(ws_url: impl Into<String>, last_applied_block: Option<u64>) -> Self {
+        Self {
+            ws_url: ws_url.into(),
+            last_applied_block,
+        }
+    }
+
+    /// The last block number applied to a store via `run`, or the starting
+    /// point passed to `new` if `run` hasn't applied anything yet.
+    pub fn last_applied_block(&self) -> Option<u64> {
+        self.last_applied_block
+    }
+
+    /// Connect and apply deltas to `store` as they arrive, sending each
+    /// applied block number on `applied`. Returns once the connection ends,
+    /// with a [`LiveHintStreamError`] describing why, so the caller can
+    /// decide whether and how to reconnect.
+    pub async fn run(
+        &mut self,
+        store: &mut HintStore,
+        applied: mpsc::Sender<u64>,
+    ) -> Result<(), LiveHintStreamError> {
+        let url = match self.last_applied_block {
+            Some(block) => format!("{}?since={}", self.ws_url, block),
+            None => self.ws_url.clone(),
+        };
+
+        let (ws_stream, _) = tokio_tungstenite::connect_async(&url).await?;
+        let (mut write, mut read) = ws_stream.split();
+
+        while let Some(msg) = read.next().await {
+            match msg? {
+                Message::Binary(bytes) => {
+                    let delta = HintDelta::decode_rlp(&bytes)
+                        .map_err(|e| LiveHintStreamError::InvalidDelta(e.to_string()))?;
+                    apply_hint_delta(store, &delta)
+                        .map_err(LiveHintStreamError::InvalidDelta)?;
+                    self.last_applied_block = Some(delta.block_number);
+                    let _ = applied.send(delta.block_number).await;
+                }
+                Message::Ping(payload) => {
+                    write.send(Message::Pong(payload)).await?;
+                }
+                Message::Close(frame) => {
+                    let (code, reason) = frame
+                        .map(|f| (u16::from(f.code), f.reason.to_string()))
+                        .unwrap_or_default();
+                    return Err(LiveHintStreamError::ServerClosed { code, reason });
+                }
+                _ => {}
+            }
+        }
+
+        Err(LiveHintStreamError::ConnectionClosed)
+    }
+}
+
+/// Verify every changed hint against `delta`'s claimed `new_root`, the same
+/// way `SyncClient::sync_deltas` does, before applying any of them to
+/// `store`. A malicious or compromised relay pushing arbitrary hint values
+/// over the websocket feed is caught here instead of silently corrupting the
+/// store. Rejects the whole delta (applying nothing) if a proof count or
+/// any individual proof doesn't check out.
+fn apply_hint_delta(store: &mut HintStore, delta: &HintDelta) -> Result<(), String> {
+    if delta.changes.len() != delta.proofs.len() {
+        return Err(format!(
+            "delta for block {} has {} changes but {} proofs",
+            delta.block_number,
+            delta.changes.len(),
+            delta.proofs.len()
+        ));
+    }
+
+    for ((hint_id, new_value), proof) in delta.changes.iter().zip(&delta.proofs) {
+        if !SyncClient::verify_hint(new_value, proof, &delta.new_root) {
+            return Err(format!(
+                "hint {hint_id} in delta for block {} failed Merkle verification against the delta's claimed root",
+                delta.block_number
+            ));
+        }
+    }
+
+    for &(hint_id, new_value) in &delta.changes {
+        if let Some(stored) = store.hints.get_mut(hint_id) {
+            stored.hint = new_value;
+        }
+    }
+    store.block_number = delta.block_number;
+    store.merkle_root = delta.new_root;
+
+    Ok(())
+}
+
+/// Errors from [`LiveHintStream::run`]. Every variant ends the subscription;
+/// the caller decides whether to reconnect.
+#[derive(Debug, thiserror::Error)]
+pub enum LiveHintStreamError {
+    #[error("websocket error: {0}")]
+    WebSocket(#[from] tokio_tungstenite::tungstenite::Error),
+
+    #[error("invalid delta: {0}")]
+    InvalidDelta(String),
+
+    #[error("server closed the connection (code {code}): {reason}")]
+    ServerClosed { code: u16, reason: String },
+
+    #[error("websocket connection closed")]
+    ConnectionClosed,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::merkle::MerkleTree;
+    use pir_core::subset::Subset;
+
+    /// Builds a two-leaf Merkle tree with `hint` as leaf 0 (and an arbitrary
+    /// padding leaf) so a delta whose `new_root` and lone proof come from
+    /// this tree verifies against the changed value it carries, with a
+    /// proof long enough to corrupt in rejection tests.
+    fn single_hint_delta(block_number: u64, hint_id: usize, hint: [u8; 32]) -> HintDelta {
+        let tree = MerkleTree::build(&[hint, [0u8; 32]]);
+        HintDelta {
+            block_number,
+            changes: vec![(hint_id, hint)],
+            new_root: tree.root(),
+            proofs: vec![tree.inclusion_proof(0)],
+        }
+    }
+
+    fn store_with_hints(hints: Vec<[u8; 32]>) -> HintStore {
+        let mut store = HintStore::new();
+        let subsets_and_hints = hints
+            .into_iter()
+            .enumerate()
+            .map(|(i, hint)| {
+                let mut seed = [0u8; 32];
+                seed[..8].copy_from_slice(&(i as u64).to_le_bytes());
+                (Subset::new(seed, 4, 1_000), hint)
+            })
+            .collect();
+        store.add_hints(subsets_and_hints, 0);
+        store
+    }
+
+    #[test]
+    fn test_apply_hint_delta_overwrites_targeted_hints() {
+        let mut store = store_with_hints(vec![[1u8; 32], [2u8; 32], [3u8; 32]]);
+        let delta = single_hint_delta(7, 1, [0xaau8; 32]);
+
+        apply_hint_delta(&mut store, &delta).unwrap();
+
+        assert_eq!(store.hints[0].hint, [1u8; 32]);
+        assert_eq!(store.hints[1].hint, [0xaau8; 32]);
+        assert_eq!(store.hints[2].hint, [3u8; 32]);
+        assert_eq!(store.block_number, 7);
+        assert_eq!(store.merkle_root, delta.new_root);
+    }
+
+    #[test]
+    fn test_apply_hint_delta_ignores_out_of_range_hint_id() {
+        let mut store = store_with_hints(vec![[1u8; 32]]);
+        let delta = single_hint_delta(1, 5, [0xaau8; 32]);
+
+        apply_hint_delta(&mut store, &delta).unwrap();
+
+        assert_eq!(store.hints[0].hint, [1u8; 32]);
+        assert_eq!(store.block_number, 1);
+    }
+
+    #[test]
+    fn test_apply_hint_delta_rejects_hint_that_fails_its_proof() {
+        let mut store = store_with_hints(vec![[1u8; 32], [2u8; 32]]);
+        let mut delta = single_hint_delta(7, 1, [0xaau8; 32]);
+        delta.proofs[0][0].sibling = [0xffu8; 32];
+
+        let err = apply_hint_delta(&mut store, &delta).unwrap_err();
+
+        assert!(err.contains("failed Merkle verification"));
+        assert_eq!(store.hints[1].hint, [2u8; 32]);
+        assert_eq!(store.block_number, 0);
+    }
+
+    #[test]
+    fn test_new_stream_reports_starting_block() {
+        let stream = LiveHintStream::new("ws://localhost/deltas", Some(42));
+        assert_eq!(stream.last_applied_block(), Some(42));
+    }
+}
@@ -0,0 +1,228 @@
+//! Merkle tree construction and verification for hint sets
+//!
+//! The manifest published alongside a hint set commits to the hints via a
+//! binary Merkle tree: each leaf is `keccak256(hint_bytes)` in the same
+//! index order as `manifest.hint_cids`, and each internal node is
+//! `keccak256(left || right)`. Odd levels duplicate their last node to pair
+//! with itself, matching the usual Bitcoin-style Merkle tree construction.
+//!
+//! [`MerkleTree`] retains every level so a caller holding the full hint set
+//! (a seeder publishing it, or a client that has already downloaded and
+//! bulk-verified it) can generate an `inclusion_proof` for any single leaf
+//! without rebuilding the tree. `compute_merkle_root`/`verify_hint` remain
+//! for callers that only need the one-shot root or a proof handed to them.
+
+use pir_core::Hint;
+use serde::{Deserialize, Serialize};
+use tiny_keccak::{Hasher, Keccak};
+
+/// A single step of a Merkle inclusion proof.
+///
+/// `sibling` is the hash at this level that must be combined with the
+/// current node, and `sibling_is_left` records which side it goes on when
+/// recomputing the parent hash.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct ProofStep {
+    pub sibling: [u8; 32],
+    pub sibling_is_left: bool,
+}
+
+fn keccak256(data: &[&[u8]]) -> [u8; 32] {
+    let mut hasher = Keccak::v256();
+    for chunk in data {
+        hasher.update(chunk);
+    }
+    let mut out = [0u8; 32];
+    hasher.finalize(&mut out);
+    out
+}
+
+/// A binary Merkle tree built once over an ordered hint set, retaining every
+/// level so `inclusion_proof` is O(log n) instead of rebuilding the whole
+/// tree per call. Use `compute_merkle_root` instead if only the root is
+/// needed and proofs never will be.
+pub struct MerkleTree {
+    /// `levels[0]` is the leaf hashes (one per hint, `keccak256(hint)`);
+    /// each later level is its parent row, ending in a single root.
+    levels: Vec<Vec<[u8; 32]>>,
+}
+
+impl MerkleTree {
+    /// Build the tree over `hints`, in the same index order as the
+    /// manifest's `hint_cids`.
+    pub fn build(hints: &[Hint]) -> Self {
+        let leaves: Vec<[u8; 32]> = if hints.is_empty() {
+            vec![[0u8; 32]]
+        } else {
+            hints.iter().map(|h| keccak256(&[h])).collect()
+        };
+
+        let mut levels = vec![leaves];
+        while levels.last().unwrap().len() > 1 {
+            let prev = levels.last().unwrap();
+            let mut next = Vec::with_capacity(prev.len().div_ceil(2));
+            for pair in prev.chunks(2) {
+                let left = pair[0];
+                let right = *pair.get(1).unwrap_or(&pair[0]);
+                next.push(keccak256(&[&left, &right]));
+            }
+            levels.push(next);
+        }
+
+        Self { levels }
+    }
+
+    /// The Merkle root, i.e. the single node of the last level.
+    pub fn root(&self) -> [u8; 32] {
+        self.levels.last().unwrap()[0]
+    }
+
+    /// Build the sibling-hash inclusion proof for the leaf at `index`,
+    /// verifiable with `verify_hint` against `self.root()`.
+    ///
+    /// Panics if `index` is out of range for the leaf level - same contract
+    /// as indexing `hints` directly, since a proof for a nonexistent leaf
+    /// isn't a meaningful result.
+    pub fn inclusion_proof(&self, mut index: usize) -> Vec<ProofStep> {
+        assert!(index < self.levels[0].len(), "leaf index out of range");
+
+        let mut proof = Vec::with_capacity(self.levels.len().saturating_sub(1));
+        for level in &self.levels[..self.levels.len() - 1] {
+            let sibling_index = index ^ 1;
+            let sibling = level.get(sibling_index).copied().unwrap_or(level[index]);
+            proof.push(ProofStep {
+                sibling,
+                sibling_is_left: index % 2 == 1,
+            });
+            index /= 2;
+        }
+        proof
+    }
+}
+
+/// Compute the Merkle root over a set of hints, in `hint_cids` order.
+pub fn compute_merkle_root(hints: &[Hint]) -> [u8; 32] {
+    MerkleTree::build(hints).root()
+}
+
+/// Verify a single hint against a Merkle root using a sibling-hash proof.
+///
+/// Walks `proof` from leaf to root, recomputing the parent hash at each
+/// step, and checks the final hash equals `root`. This lets `sync_deltas`
+/// validate individual changed hints without re-downloading the whole set.
+pub fn verify_hint(hint: &Hint, proof: &[ProofStep], root: &[u8; 32]) -> bool {
+    let mut current = keccak256(&[hint]);
+
+    for step in proof {
+        current = if step.sibling_is_left {
+            keccak256(&[&step.sibling, &current])
+        } else {
+            keccak256(&[&current, &step.sibling])
+        };
+    }
+
+    &current == root
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merkle_root_single_hint() {
+        let hints = vec![[0x42u8; 32]];
+        let root = compute_merkle_root(&hints);
+        let expected = keccak256(&[&hints[0]]);
+        assert_eq!(root, expected);
+    }
+
+    #[test]
+    fn test_merkle_root_deterministic() {
+        let hints = vec![[1u8; 32], [2u8; 32], [3u8; 32]];
+        let root1 = compute_merkle_root(&hints);
+        let root2 = compute_merkle_root(&hints);
+        assert_eq!(root1, root2);
+    }
+
+    #[test]
+    fn test_merkle_root_odd_count_duplicates_last() {
+        let hints = vec![[1u8; 32], [2u8; 32], [3u8; 32]];
+        let root = compute_merkle_root(&hints);
+
+        let leaves: Vec<[u8; 32]> = hints.iter().map(|h| keccak256(&[h])).collect();
+        let node01 = keccak256(&[&leaves[0], &leaves[1]]);
+        let node22 = keccak256(&[&leaves[2], &leaves[2]]);
+        let expected = keccak256(&[&node01, &node22]);
+
+        assert_eq!(root, expected);
+    }
+
+    #[test]
+    fn test_verify_hint_two_leaves() {
+        let hints = vec![[0xaau8; 32], [0xbbu8; 32]];
+        let root = compute_merkle_root(&hints);
+
+        let leaf1 = keccak256(&[&hints[1]]);
+        let proof = vec![ProofStep {
+            sibling: leaf1,
+            sibling_is_left: false,
+        }];
+        assert!(verify_hint(&hints[0], &proof, &root));
+
+        let leaf0 = keccak256(&[&hints[0]]);
+        let proof = vec![ProofStep {
+            sibling: leaf0,
+            sibling_is_left: true,
+        }];
+        assert!(verify_hint(&hints[1], &proof, &root));
+    }
+
+    #[test]
+    fn test_verify_hint_rejects_wrong_proof() {
+        let hints = vec![[0xaau8; 32], [0xbbu8; 32]];
+        let root = compute_merkle_root(&hints);
+
+        let proof = vec![ProofStep {
+            sibling: [0xffu8; 32],
+            sibling_is_left: false,
+        }];
+        assert!(!verify_hint(&hints[0], &proof, &root));
+    }
+
+    #[test]
+    fn test_tree_root_matches_compute_merkle_root() {
+        let hints = vec![[1u8; 32], [2u8; 32], [3u8; 32], [4u8; 32], [5u8; 32]];
+        let tree = MerkleTree::build(&hints);
+        assert_eq!(tree.root(), compute_merkle_root(&hints));
+    }
+
+    #[test]
+    fn test_tree_inclusion_proof_verifies_every_leaf() {
+        let hints = vec![[1u8; 32], [2u8; 32], [3u8; 32], [4u8; 32], [5u8; 32]];
+        let tree = MerkleTree::build(&hints);
+        let root = tree.root();
+
+        for (i, hint) in hints.iter().enumerate() {
+            let proof = tree.inclusion_proof(i);
+            assert!(verify_hint(hint, &proof, &root), "leaf {i} failed to verify");
+        }
+    }
+
+    #[test]
+    fn test_tree_inclusion_proof_rejects_wrong_leaf() {
+        let hints = vec![[1u8; 32], [2u8; 32], [3u8; 32], [4u8; 32]];
+        let tree = MerkleTree::build(&hints);
+        let root = tree.root();
+
+        let proof = tree.inclusion_proof(0);
+        assert!(!verify_hint(&hints[1], &proof, &root));
+    }
+
+    #[test]
+    fn test_tree_single_leaf_root_matches_free_function() {
+        let hints = vec![[0x77u8; 32]];
+        let tree = MerkleTree::build(&hints);
+        assert_eq!(tree.root(), compute_merkle_root(&hints));
+        assert!(tree.inclusion_proof(0).is_empty());
+    }
+}
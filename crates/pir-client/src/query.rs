@@ -1,6 +1,7 @@
 //! Query construction and execution
 
-use crate::hint_store::HintStore;
+use crate::hint_store::{HintStore, StoredHint};
+use crate::merkle::{self, MerkleTree};
 use pir_core::{hint::recover_entry, subset::CompressedQuery, Hint, ENTRY_SIZE};
 use serde::{Deserialize, Serialize};
 
@@ -12,6 +13,15 @@ pub struct PirClient {
     pub server_url: String,
     /// HTTP client
     client: reqwest::Client,
+    /// Present when constructed via `new_verified`: the trusted manifest
+    /// root and the tree built over `hints` used to re-check a hint's
+    /// inclusion before trusting it in a query result.
+    integrity: Option<HintIntegrity>,
+}
+
+struct HintIntegrity {
+    tree: MerkleTree,
+    root: [u8; 32],
 }
 
 /// Query result
@@ -35,26 +45,102 @@ struct QueryRequest {
     query: CompressedQuery,
 }
 
+/// Batched query request: many independent sub-queries in one round trip
+#[derive(Debug, Serialize)]
+struct BatchQueryRequest {
+    queries: Vec<CompressedQuery>,
+}
+
+/// Batched query response: results aligned 1:1 with the request's `queries`
+#[derive(Debug, Deserialize)]
+struct BatchServerResponse {
+    results: Vec<ServerResponse>,
+    #[allow(dead_code)]
+    total_time_ms: f64,
+}
+
 impl PirClient {
-    /// Create a new PIR client
+    /// Create a new PIR client. Trusts `hints` as-is; use `new_verified` if
+    /// `hints` was loaded from disk (or anywhere else not already checked
+    /// against a manifest, such as `HintStore::load`) and should be
+    /// Merkle-verified before use.
     pub fn new(hints: HintStore, server_url: String) -> Self {
         Self {
             hints,
             server_url,
             client: reqwest::Client::new(),
+            integrity: None,
         }
     }
 
+    /// Create a PIR client after checking that every hint in `hints`
+    /// re-hashes to `expected_root` via the same binary Merkle tree the
+    /// manifest commits to (see `merkle::MerkleTree`). Catches corruption or
+    /// tampering of the local hint store that happened after the download
+    /// itself was already verified (e.g. by `SyncClient`) - `HintStore::load`
+    /// performs no integrity check on its own.
+    ///
+    /// Once verified, every `query`/`query_batch` call re-checks the
+    /// specific hint it's about to trust via an O(log n) inclusion proof
+    /// against the same tree, rather than trusting `hints` blindly for the
+    /// client's whole lifetime.
+    pub fn new_verified(
+        hints: HintStore,
+        server_url: String,
+        expected_root: [u8; 32],
+    ) -> anyhow::Result<Self> {
+        let leaves: Vec<Hint> = hints.hints.iter().map(|h| h.hint).collect();
+        let tree = MerkleTree::build(&leaves);
+        if tree.root() != expected_root {
+            anyhow::bail!(
+                "hint store failed Merkle verification: expected root {}, computed {}",
+                hex::encode(expected_root),
+                hex::encode(tree.root())
+            );
+        }
+
+        Ok(Self {
+            hints,
+            server_url,
+            client: reqwest::Client::new(),
+            integrity: Some(HintIntegrity {
+                tree,
+                root: expected_root,
+            }),
+        })
+    }
+
+    /// If this client was constructed with `new_verified`, check that the
+    /// hint at `hint_index` still matches its expected leaf hash before it's
+    /// trusted to recover a query result.
+    fn verify_hint_inclusion(&self, hint_index: usize, stored_hint: &StoredHint) -> anyhow::Result<()> {
+        let Some(integrity) = &self.integrity else {
+            return Ok(());
+        };
+
+        let proof = integrity.tree.inclusion_proof(hint_index);
+        if !merkle::verify_hint(&stored_hint.hint, &proof, &integrity.root) {
+            anyhow::bail!(
+                "hint {} failed Merkle verification against root {}",
+                hint_index,
+                hex::encode(integrity.root)
+            );
+        }
+        Ok(())
+    }
+
     /// Query for a specific database index
     pub async fn query(&self, target_index: u64) -> anyhow::Result<QueryResult> {
         let start = std::time::Instant::now();
-        
+
         // Find a hint containing the target
-        let stored_hint = self
+        let hint_index = self
             .hints
-            .find_hint_for_target(target_index)
+            .find_hint_index_for_target(target_index)
             .ok_or_else(|| anyhow::anyhow!("No hint found for target {}", target_index))?;
-        
+        let stored_hint = &self.hints.hints[hint_index];
+        self.verify_hint_inclusion(hint_index, stored_hint)?;
+
         // Create compressed query
         let query = CompressedQuery::new(&stored_hint.subset);
         
@@ -85,15 +171,145 @@ impl PirClient {
         })
     }
 
-    /// Query multiple indices (batched)
-    pub async fn query_batch(&self, indices: &[u64]) -> anyhow::Result<Vec<QueryResult>> {
-        let mut results = Vec::with_capacity(indices.len());
-        
-        // TODO: Parallelize queries
-        for &idx in indices {
-            results.push(self.query(idx).await?);
+    /// Query multiple indices in a single round trip.
+    ///
+    /// Unlike [`Self::query`], a missing hint for one target does not abort
+    /// the whole batch: the returned `Vec` is index-aligned with `indices`,
+    /// and each entry is its own `Result` so callers can see exactly which
+    /// targets succeeded.
+    pub async fn query_batch(&self, indices: &[u64]) -> anyhow::Result<Vec<anyhow::Result<QueryResult>>> {
+        // Gather a stored hint per target up front, before any network call.
+        let prepared: Vec<Option<(CompressedQuery, usize, &StoredHint)>> = indices
+            .iter()
+            .map(|&idx| {
+                self.hints.find_hint_index_for_target(idx).map(|hint_index| {
+                    let stored_hint = &self.hints.hints[hint_index];
+                    (CompressedQuery::new(&stored_hint.subset), hint_index, stored_hint)
+                })
+            })
+            .collect();
+
+        let queries: Vec<CompressedQuery> = prepared
+            .iter()
+            .filter_map(|p| p.as_ref().map(|(query, _, _)| query.clone()))
+            .collect();
+
+        if queries.is_empty() {
+            return Ok(indices
+                .iter()
+                .map(|&idx| Err(anyhow::anyhow!("No hint found for target {}", idx)))
+                .collect());
         }
-        
-        Ok(results)
+
+        let start = std::time::Instant::now();
+
+        let response: BatchServerResponse = self
+            .client
+            .post(format!("{}/query_batch", self.server_url))
+            .json(&BatchQueryRequest { queries })
+            .send()
+            .await?
+            .json()
+            .await?;
+
+        let query_time_ms = start.elapsed().as_secs_f64() * 1000.0;
+
+        let mut server_results = response.results.into_iter();
+        let mut out = Vec::with_capacity(indices.len());
+
+        for (idx, prepared) in indices.iter().zip(prepared) {
+            out.push(match prepared {
+                None => Err(anyhow::anyhow!("No hint found for target {}", idx)),
+                Some((_, hint_index, stored_hint)) => (|| {
+                    // Always consume a server response here, even if
+                    // verification below fails, so `server_results` stays
+                    // aligned with the remaining prepared queries.
+                    let server_response = server_results
+                        .next()
+                        .ok_or_else(|| anyhow::anyhow!("server returned fewer results than requested"))?;
+
+                    self.verify_hint_inclusion(hint_index, stored_hint)?;
+
+                    let server_result: Hint = hex::decode(&server_response.result)?
+                        .try_into()
+                        .map_err(|_| anyhow::anyhow!("Invalid response length"))?;
+
+                    let entry = recover_entry(&server_result, &stored_hint.hint);
+
+                    Ok(QueryResult {
+                        entry,
+                        query_time_ms,
+                        server_time_ms: server_response.query_time_ms,
+                    })
+                })(),
+            });
+        }
+
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pir_core::subset::Subset;
+
+    fn store_with_hints(hints: Vec<Hint>) -> HintStore {
+        let mut store = HintStore::new();
+        let subsets_and_hints = hints
+            .into_iter()
+            .enumerate()
+            .map(|(i, hint)| {
+                let mut seed = [0u8; 32];
+                seed[..8].copy_from_slice(&(i as u64).to_le_bytes());
+                (Subset::new(seed, 4, 1_000), hint)
+            })
+            .collect();
+        store.add_hints(subsets_and_hints, 0);
+        store
+    }
+
+    #[test]
+    fn test_new_verified_accepts_matching_root() {
+        let hints = vec![[1u8; 32], [2u8; 32], [3u8; 32]];
+        let root = merkle::compute_merkle_root(&hints);
+        let store = store_with_hints(hints);
+
+        assert!(PirClient::new_verified(store, "http://localhost".into(), root).is_ok());
+    }
+
+    #[test]
+    fn test_new_verified_rejects_mismatched_root() {
+        let hints = vec![[1u8; 32], [2u8; 32], [3u8; 32]];
+        let store = store_with_hints(hints);
+
+        let result = PirClient::new_verified(store, "http://localhost".into(), [0xffu8; 32]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_verify_hint_inclusion_detects_tampered_hint() {
+        let hints = vec![[1u8; 32], [2u8; 32], [3u8; 32]];
+        let root = merkle::compute_merkle_root(&hints);
+        let store = store_with_hints(hints);
+
+        let mut client = PirClient::new_verified(store, "http://localhost".into(), root).unwrap();
+        client.hints.hints[1].hint = [0xaau8; 32];
+
+        let tampered = client.hints.hints[1].clone();
+        assert!(client.verify_hint_inclusion(1, &tampered).is_err());
+        // Untouched hints still verify against the same root.
+        let untouched = client.hints.hints[0].clone();
+        assert!(client.verify_hint_inclusion(0, &untouched).is_ok());
+    }
+
+    #[test]
+    fn test_verify_hint_inclusion_noop_when_unverified() {
+        let hints = vec![[1u8; 32], [2u8; 32]];
+        let store = store_with_hints(hints);
+        let client = PirClient::new(store, "http://localhost".into());
+
+        let stored = client.hints.hints[0].clone();
+        assert!(client.verify_hint_inclusion(0, &stored).is_ok());
     }
 }
@@ -0,0 +1,221 @@
+//! Minimal canonical RLP (Recursive Length Prefix) encode/decode
+//!
+//! Implements just enough of the Ethereum RLP spec to encode/decode
+//! `HintManifest` and `HintDelta` in a form a Solidity contract (or any
+//! other RLP-aware client) can reproduce byte-for-byte. Integers are
+//! encoded as the minimal big-endian representation (no leading zero
+//! bytes, and zero itself encodes as an empty string).
+
+use std::fmt;
+
+/// A decoded RLP item: either a byte string or a list of items.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RlpItem {
+    String(Vec<u8>),
+    List(Vec<RlpItem>),
+}
+
+/// Errors produced while decoding RLP-encoded bytes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RlpError {
+    /// Input ended before the declared length could be read
+    UnexpectedEof,
+    /// A length prefix encoded a non-canonical (not minimal) value
+    NonCanonicalLength,
+    /// Decoded item did not match the expected shape (string vs list, field count)
+    InvalidShape(&'static str),
+}
+
+impl fmt::Display for RlpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RlpError::UnexpectedEof => write!(f, "RLP: unexpected end of input"),
+            RlpError::NonCanonicalLength => write!(f, "RLP: non-canonical length prefix"),
+            RlpError::InvalidShape(what) => write!(f, "RLP: invalid shape ({what})"),
+        }
+    }
+}
+
+impl std::error::Error for RlpError {}
+
+/// Encode a single byte string per RLP rules.
+pub fn encode_bytes(data: &[u8]) -> Vec<u8> {
+    if data.len() == 1 && data[0] < 0x80 {
+        return vec![data[0]];
+    }
+    let mut out = encode_length(data.len(), 0x80);
+    out.extend_from_slice(data);
+    out
+}
+
+/// Encode a `u64` as the minimal big-endian byte string RLP expects
+/// (zero encodes as an empty string, no leading zero bytes otherwise).
+pub fn encode_u64(value: u64) -> Vec<u8> {
+    encode_bytes(&minimal_be_bytes(value))
+}
+
+/// Encode a list of already-RLP-encoded items.
+pub fn encode_list(items: &[Vec<u8>]) -> Vec<u8> {
+    let payload_len: usize = items.iter().map(|i| i.len()).sum();
+    let mut out = encode_length(payload_len, 0xc0);
+    for item in items {
+        out.extend_from_slice(item);
+    }
+    out
+}
+
+fn minimal_be_bytes(value: u64) -> Vec<u8> {
+    let bytes = value.to_be_bytes();
+    let first_nonzero = bytes.iter().position(|&b| b != 0);
+    match first_nonzero {
+        Some(i) => bytes[i..].to_vec(),
+        None => Vec::new(),
+    }
+}
+
+fn encode_length(len: usize, offset: u8) -> Vec<u8> {
+    if len < 56 {
+        vec![offset + len as u8]
+    } else {
+        let len_bytes = minimal_be_bytes(len as u64);
+        let mut out = vec![offset + 55 + len_bytes.len() as u8];
+        out.extend_from_slice(&len_bytes);
+        out
+    }
+}
+
+/// Decode one RLP item from the start of `data`, returning the item and
+/// the number of bytes consumed.
+pub fn decode_item(data: &[u8]) -> Result<(RlpItem, usize), RlpError> {
+    let first = *data.first().ok_or(RlpError::UnexpectedEof)?;
+
+    if first < 0x80 {
+        Ok((RlpItem::String(vec![first]), 1))
+    } else if first < 0xb8 {
+        let len = (first - 0x80) as usize;
+        let (body, consumed) = take_body(data, 1, len)?;
+        if len == 1 && body[0] < 0x80 {
+            return Err(RlpError::NonCanonicalLength);
+        }
+        Ok((RlpItem::String(body.to_vec()), consumed))
+    } else if first < 0xc0 {
+        let len_of_len = (first - 0xb7) as usize;
+        let (len, header_len) = read_long_length(data, 1, len_of_len)?;
+        let (body, consumed) = take_body(data, header_len, len)?;
+        Ok((RlpItem::String(body.to_vec()), consumed))
+    } else if first < 0xf8 {
+        let len = (first - 0xc0) as usize;
+        let (body, consumed) = take_body(data, 1, len)?;
+        Ok((RlpItem::List(decode_list_items(body)?), consumed))
+    } else {
+        let len_of_len = (first - 0xf7) as usize;
+        let (len, header_len) = read_long_length(data, 1, len_of_len)?;
+        let (body, consumed) = take_body(data, header_len, len)?;
+        Ok((RlpItem::List(decode_list_items(body)?), consumed))
+    }
+}
+
+fn take_body(data: &[u8], start: usize, len: usize) -> Result<(&[u8], usize), RlpError> {
+    let end = start.checked_add(len).ok_or(RlpError::UnexpectedEof)?;
+    if end > data.len() {
+        return Err(RlpError::UnexpectedEof);
+    }
+    Ok((&data[start..end], end))
+}
+
+fn read_long_length(data: &[u8], start: usize, len_of_len: usize) -> Result<(usize, usize), RlpError> {
+    let (len_bytes, consumed) = take_body(data, start, len_of_len)?;
+    if len_bytes.is_empty() || len_bytes[0] == 0 {
+        return Err(RlpError::NonCanonicalLength);
+    }
+    let mut buf = [0u8; 8];
+    buf[8 - len_bytes.len()..].copy_from_slice(len_bytes);
+    let len = u64::from_be_bytes(buf) as usize;
+    if len < 56 {
+        return Err(RlpError::NonCanonicalLength);
+    }
+    Ok((len, consumed))
+}
+
+fn decode_list_items(mut data: &[u8]) -> Result<Vec<RlpItem>, RlpError> {
+    let mut items = Vec::new();
+    while !data.is_empty() {
+        let (item, consumed) = decode_item(data)?;
+        items.push(item);
+        data = &data[consumed..];
+    }
+    Ok(items)
+}
+
+/// Decode a minimal big-endian byte string back into a `u64`.
+pub fn decode_u64(bytes: &[u8]) -> Result<u64, RlpError> {
+    if bytes.len() > 8 || (bytes.len() > 1 && bytes[0] == 0) {
+        return Err(RlpError::NonCanonicalLength);
+    }
+    let mut buf = [0u8; 8];
+    buf[8 - bytes.len()..].copy_from_slice(bytes);
+    Ok(u64::from_be_bytes(buf))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_u64_zero_is_empty_string() {
+        assert_eq!(encode_u64(0), vec![0x80]);
+    }
+
+    #[test]
+    fn test_encode_u64_small_value() {
+        assert_eq!(encode_u64(1), vec![0x01]);
+        assert_eq!(encode_u64(127), vec![0x7f]);
+        assert_eq!(encode_u64(128), vec![0x81, 0x80]);
+    }
+
+    #[test]
+    fn test_roundtrip_bytes() {
+        let data = vec![0xaau8; 32];
+        let encoded = encode_bytes(&data);
+        let (item, consumed) = decode_item(&encoded).unwrap();
+        assert_eq!(consumed, encoded.len());
+        assert_eq!(item, RlpItem::String(data));
+    }
+
+    #[test]
+    fn test_roundtrip_long_string() {
+        let data = vec![0x42u8; 100];
+        let encoded = encode_bytes(&data);
+        let (item, consumed) = decode_item(&encoded).unwrap();
+        assert_eq!(consumed, encoded.len());
+        assert_eq!(item, RlpItem::String(data));
+    }
+
+    #[test]
+    fn test_roundtrip_list() {
+        let items = vec![encode_u64(1), encode_bytes(b"hello"), encode_u64(1000)];
+        let encoded = encode_list(&items);
+        let (item, consumed) = decode_item(&encoded).unwrap();
+        assert_eq!(consumed, encoded.len());
+        assert_eq!(
+            item,
+            RlpItem::List(vec![
+                RlpItem::String(vec![1]),
+                RlpItem::String(b"hello".to_vec()),
+                RlpItem::String(vec![0x03, 0xe8]),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_decode_u64_roundtrip() {
+        for v in [0u64, 1, 127, 128, 255, 256, u64::MAX] {
+            let encoded = encode_u64(v);
+            let (item, _) = decode_item(&encoded).unwrap();
+            let RlpItem::String(bytes) = item else {
+                panic!("expected string item");
+            };
+            assert_eq!(decode_u64(&bytes).unwrap(), v);
+        }
+    }
+}
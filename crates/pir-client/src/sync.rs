@@ -1,8 +1,18 @@
 //! Hint synchronization from DHT/IPFS
 
 use crate::hint_store::HintStore;
+use crate::merkle;
+use crate::rlp::{self, RlpItem};
+use futures::stream::{self, StreamExt};
 use pir_core::{subset::Subset, Hint};
 use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tiny_keccak::{Hasher, Keccak};
+
+/// Default number of concurrent in-flight hint downloads.
+const DEFAULT_MAX_IN_FLIGHT: usize = 64;
+/// Default number of retries for a single hint fetch before giving up.
+const DEFAULT_MAX_RETRIES: u32 = 5;
 
 /// Manifest from DHT
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -14,11 +24,305 @@ pub struct HintManifest {
     pub domain_size: u64,
 }
 
+impl HintManifest {
+    /// Canonical RLP encoding: `[block_number, merkle_root, hint_cids, subset_size, domain_size]`
+    ///
+    /// `block_number` is encoded as a minimal big-endian integer, `merkle_root`
+    /// as a 32-byte string, `hint_cids` as a list of byte strings, and
+    /// `subset_size`/`domain_size` as integers - matching what a Solidity
+    /// contract would compute over the same fields.
+    pub fn rlp_bytes(&self) -> Vec<u8> {
+        let cids = rlp::encode_list(
+            &self
+                .hint_cids
+                .iter()
+                .map(|cid| rlp::encode_bytes(cid.as_bytes()))
+                .collect::<Vec<_>>(),
+        );
+
+        rlp::encode_list(&[
+            rlp::encode_u64(self.block_number),
+            rlp::encode_bytes(&self.merkle_root),
+            cids,
+            rlp::encode_u64(self.subset_size as u64),
+            rlp::encode_u64(self.domain_size),
+        ])
+    }
+
+    /// Decode a manifest from its canonical RLP encoding.
+    pub fn decode_rlp(data: &[u8]) -> anyhow::Result<Self> {
+        let (item, _) = rlp::decode_item(data)?;
+        let RlpItem::List(fields) = item else {
+            anyhow::bail!("HintManifest RLP root must be a list");
+        };
+        let [block_number, merkle_root, hint_cids, subset_size, domain_size] =
+            <[RlpItem; 5]>::try_from(fields)
+                .map_err(|_| anyhow::anyhow!("HintManifest RLP list must have 5 fields"))?;
+
+        let RlpItem::String(block_number) = block_number else {
+            anyhow::bail!("block_number must be a string");
+        };
+        let RlpItem::String(merkle_root) = merkle_root else {
+            anyhow::bail!("merkle_root must be a string");
+        };
+        let RlpItem::List(hint_cids) = hint_cids else {
+            anyhow::bail!("hint_cids must be a list");
+        };
+        let RlpItem::String(subset_size) = subset_size else {
+            anyhow::bail!("subset_size must be a string");
+        };
+        let RlpItem::String(domain_size) = domain_size else {
+            anyhow::bail!("domain_size must be a string");
+        };
+
+        let merkle_root: [u8; 32] = merkle_root
+            .try_into()
+            .map_err(|_| anyhow::anyhow!("merkle_root must be 32 bytes"))?;
+
+        let hint_cids = hint_cids
+            .into_iter()
+            .map(|item| match item {
+                RlpItem::String(bytes) => String::from_utf8(bytes)
+                    .map_err(|_| anyhow::anyhow!("hint_cid must be valid UTF-8")),
+                RlpItem::List(_) => anyhow::bail!("hint_cid must be a string"),
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        Ok(Self {
+            block_number: rlp::decode_u64(&block_number)?,
+            merkle_root,
+            hint_cids,
+            subset_size: rlp::decode_u64(&subset_size)? as usize,
+            domain_size: rlp::decode_u64(&domain_size)?,
+        })
+    }
+
+    /// `keccak256(rlp_bytes())` - the commitment to anchor on-chain.
+    pub fn manifest_commitment(&self) -> [u8; 32] {
+        let mut hasher = Keccak::v256();
+        hasher.update(&self.rlp_bytes());
+        let mut out = [0u8; 32];
+        hasher.finalize(&mut out);
+        out
+    }
+}
+
 /// Delta update
+///
+/// `new_root` is the Merkle root the hint set has *after* `changes` are
+/// applied, and `proofs[i]` is the sibling-hash inclusion proof for
+/// `changes[i]` against that root (same order, same length). `new_root`
+/// itself is trusted the same way `HintManifest::merkle_root` is - anchored
+/// out-of-band (e.g. on-chain) before this delta is applied - `proofs` only
+/// lets `sync_deltas` check that the claimed changes are internally
+/// consistent with the root it's asked to adopt, not that the root is
+/// legitimate.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HintDelta {
     pub block_number: u64,
     pub changes: Vec<(usize, Hint)>,
+    pub new_root: [u8; 32],
+    pub proofs: Vec<Vec<merkle::ProofStep>>,
+}
+
+impl HintDelta {
+    /// Canonical RLP encoding: `[block_number, [[hint_idx, hint], ...], new_root, [[[sibling, is_left], ...], ...]]`
+    pub fn rlp_bytes(&self) -> Vec<u8> {
+        let changes = rlp::encode_list(
+            &self
+                .changes
+                .iter()
+                .map(|(idx, hint)| {
+                    rlp::encode_list(&[rlp::encode_u64(*idx as u64), rlp::encode_bytes(hint)])
+                })
+                .collect::<Vec<_>>(),
+        );
+
+        let proofs = rlp::encode_list(
+            &self
+                .proofs
+                .iter()
+                .map(|proof| rlp::encode_list(&proof.iter().map(encode_proof_step).collect::<Vec<_>>()))
+                .collect::<Vec<_>>(),
+        );
+
+        rlp::encode_list(&[
+            rlp::encode_u64(self.block_number),
+            changes,
+            rlp::encode_bytes(&self.new_root),
+            proofs,
+        ])
+    }
+
+    /// Decode a delta from its canonical RLP encoding.
+    pub fn decode_rlp(data: &[u8]) -> anyhow::Result<Self> {
+        let (item, _) = rlp::decode_item(data)?;
+        let RlpItem::List(fields) = item else {
+            anyhow::bail!("HintDelta RLP root must be a list");
+        };
+        let [block_number, changes, new_root, proofs] = <[RlpItem; 4]>::try_from(fields)
+            .map_err(|_| anyhow::anyhow!("HintDelta RLP list must have 4 fields"))?;
+
+        let RlpItem::String(block_number) = block_number else {
+            anyhow::bail!("block_number must be a string");
+        };
+        let RlpItem::List(changes) = changes else {
+            anyhow::bail!("changes must be a list");
+        };
+        let RlpItem::String(new_root) = new_root else {
+            anyhow::bail!("new_root must be a string");
+        };
+        let RlpItem::List(proofs) = proofs else {
+            anyhow::bail!("proofs must be a list");
+        };
+
+        let changes = changes
+            .into_iter()
+            .map(|item| {
+                let RlpItem::List(pair) = item else {
+                    anyhow::bail!("change entry must be a list");
+                };
+                let [idx, hint] = <[RlpItem; 2]>::try_from(pair)
+                    .map_err(|_| anyhow::anyhow!("change entry must have 2 fields"))?;
+                let RlpItem::String(idx) = idx else {
+                    anyhow::bail!("change index must be a string");
+                };
+                let RlpItem::String(hint) = hint else {
+                    anyhow::bail!("change hint must be a string");
+                };
+                let hint: Hint = hint
+                    .try_into()
+                    .map_err(|_| anyhow::anyhow!("change hint must be 32 bytes"))?;
+                Ok((rlp::decode_u64(&idx)? as usize, hint))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        let new_root: [u8; 32] = new_root
+            .try_into()
+            .map_err(|_| anyhow::anyhow!("new_root must be 32 bytes"))?;
+
+        let proofs = proofs
+            .into_iter()
+            .map(|item| {
+                let RlpItem::List(steps) = item else {
+                    anyhow::bail!("proof entry must be a list");
+                };
+                steps.into_iter().map(decode_proof_step).collect()
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        Ok(Self {
+            block_number: rlp::decode_u64(&block_number)?,
+            changes,
+            new_root,
+            proofs,
+        })
+    }
+}
+
+/// Encode a single Merkle [`merkle::ProofStep`] as `[sibling, is_left]`.
+fn encode_proof_step(step: &merkle::ProofStep) -> Vec<u8> {
+    rlp::encode_list(&[
+        rlp::encode_bytes(&step.sibling),
+        rlp::encode_u64(step.sibling_is_left as u64),
+    ])
+}
+
+/// Decode a single Merkle [`merkle::ProofStep`] from its RLP encoding.
+fn decode_proof_step(item: RlpItem) -> anyhow::Result<merkle::ProofStep> {
+    let RlpItem::List(pair) = item else {
+        anyhow::bail!("proof step must be a list");
+    };
+    let [sibling, is_left] = <[RlpItem; 2]>::try_from(pair)
+        .map_err(|_| anyhow::anyhow!("proof step must have 2 fields"))?;
+    let RlpItem::String(sibling) = sibling else {
+        anyhow::bail!("proof step sibling must be a string");
+    };
+    let RlpItem::String(is_left) = is_left else {
+        anyhow::bail!("proof step is_left must be a string");
+    };
+    let sibling: [u8; 32] = sibling
+        .try_into()
+        .map_err(|_| anyhow::anyhow!("proof step sibling must be 32 bytes"))?;
+
+    Ok(merkle::ProofStep {
+        sibling,
+        sibling_is_left: rlp::decode_u64(&is_left)? != 0,
+    })
+}
+
+/// Tracks which hint indices (by position in `HintManifest::hint_cids`) a
+/// partial [`SyncClient::download_hints_resumable`] run has already fetched,
+/// so restarting a multi-hour sync only requests what's missing.
+#[derive(Debug, Clone)]
+pub struct DownloadProgress {
+    present: Vec<bool>,
+}
+
+impl DownloadProgress {
+    /// A fresh progress tracker with nothing downloaded yet.
+    pub fn new(total: usize) -> Self {
+        Self {
+            present: vec![false; total],
+        }
+    }
+
+    /// Reconstruct progress from an existing `store`, assuming its hints
+    /// were appended in index order starting from 0 (as `download_hints`
+    /// always does), so the first `store.hints.len()` indices are present.
+    pub fn from_store(store: &HintStore, total: usize) -> Self {
+        let mut progress = Self::new(total);
+        for i in 0..store.hints.len().min(total) {
+            progress.present[i] = true;
+        }
+        progress
+    }
+
+    pub fn is_present(&self, i: usize) -> bool {
+        self.present[i]
+    }
+
+    pub fn mark_present(&mut self, i: usize) {
+        self.present[i] = true;
+    }
+
+    pub fn missing_count(&self) -> usize {
+        self.present.iter().filter(|&&p| !p).count()
+    }
+}
+
+/// Fetch a single hint, retrying with exponential backoff (100ms * 2^attempt,
+/// capped) on failure so one flaky gateway response doesn't abort a
+/// multi-hour sync.
+async fn fetch_hint_with_retry(
+    client: &reqwest::Client,
+    gateway_url: &str,
+    cid: &str,
+    max_retries: u32,
+) -> anyhow::Result<Hint> {
+    let url = format!("{gateway_url}/ipfs/{cid}");
+    let mut attempt = 0u32;
+    loop {
+        match fetch_hint_once(client, &url).await {
+            Ok(hint) => return Ok(hint),
+            Err(err) if attempt < max_retries => {
+                attempt += 1;
+                let backoff = Duration::from_millis(100u64 * (1u64 << attempt.min(10)));
+                tracing::warn!(cid, attempt, error = %err, "hint fetch failed, retrying");
+                tokio::time::sleep(backoff).await;
+            }
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+async fn fetch_hint_once(client: &reqwest::Client, url: &str) -> anyhow::Result<Hint> {
+    let response = client.get(url).send().await?;
+    let bytes = response.bytes().await?;
+    bytes
+        .as_ref()
+        .try_into()
+        .map_err(|_| anyhow::anyhow!("Invalid hint size"))
 }
 
 /// Sync client for downloading hints from DHT
@@ -27,6 +331,10 @@ pub struct SyncClient {
     pub gateway_url: String,
     /// HTTP client
     client: reqwest::Client,
+    /// Maximum number of hint fetches in flight at once
+    max_in_flight: usize,
+    /// Retries for a single hint fetch before giving up
+    max_retries: u32,
 }
 
 impl SyncClient {
@@ -34,43 +342,143 @@ impl SyncClient {
         Self {
             gateway_url,
             client: reqwest::Client::new(),
+            max_in_flight: DEFAULT_MAX_IN_FLIGHT,
+            max_retries: DEFAULT_MAX_RETRIES,
         }
     }
 
-    /// Download full hint set from manifest
+    /// Bound how many hint fetches may be in flight at once. Downloading the
+    /// ~6.7M-hint mainnet set over a single sequential connection is
+    /// unacceptably slow, so `download_hints` issues up to this many
+    /// concurrent requests.
+    pub fn with_max_in_flight(mut self, max_in_flight: usize) -> Self {
+        self.max_in_flight = max_in_flight;
+        self
+    }
+
+    /// Set how many times a single hint fetch is retried (with exponential
+    /// backoff) before the whole download is aborted.
+    pub fn with_max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Download the full hint set from a manifest.
+    ///
+    /// Verifies the downloaded hints against `manifest.merkle_root` before
+    /// returning, so a malicious gateway cannot silently serve corrupted or
+    /// poisoned hints. Equivalent to `download_hints_resumable` starting from
+    /// an empty store.
     pub async fn download_hints(&self, manifest: &HintManifest) -> anyhow::Result<HintStore> {
-        let mut store = HintStore::new();
-        let mut hints = Vec::with_capacity(manifest.hint_cids.len());
-        
-        for (i, cid) in manifest.hint_cids.iter().enumerate() {
-            // Download hint from IPFS
-            let url = format!("{}/ipfs/{}", self.gateway_url, cid);
-            let response = self.client.get(&url).send().await?;
-            let hint_bytes = response.bytes().await?;
-            
-            let hint: Hint = hint_bytes
-                .as_ref()
-                .try_into()
-                .map_err(|_| anyhow::anyhow!("Invalid hint size"))?;
-            
-            // Reconstruct subset from index
-            let mut seed = [0u8; 32];
-            seed[..8].copy_from_slice(&(i as u64).to_le_bytes());
-            let subset = Subset::new(seed, manifest.subset_size, manifest.domain_size);
-            
-            hints.push((subset, hint));
-            
-            if i % 10_000 == 0 {
-                tracing::info!("Downloaded {}/{} hints", i, manifest.hint_cids.len());
+        self.download_hints_resumable(manifest, HintStore::new())
+            .await
+    }
+
+    /// Download a hint set, resuming from a partially-populated `store`.
+    ///
+    /// Hints already present in `store` (as tracked by [`DownloadProgress`])
+    /// are not re-fetched; only the missing indices are requested, with up
+    /// to `max_in_flight` requests in flight at once via `buffer_unordered`.
+    /// Each future is tagged with its position in `manifest.hint_cids` so
+    /// results can be reassembled in index order regardless of completion
+    /// order. A transient per-hint failure is retried with backoff rather
+    /// than aborting the whole multi-hour sync.
+    pub async fn download_hints_resumable(
+        &self,
+        manifest: &HintManifest,
+        store: HintStore,
+    ) -> anyhow::Result<HintStore> {
+        let total = manifest.hint_cids.len();
+        let mut progress = DownloadProgress::from_store(&store, total);
+
+        let mut raw_hints: Vec<Option<Hint>> = vec![None; total];
+        for (i, stored) in store.hints.iter().enumerate() {
+            raw_hints[i] = Some(stored.hint);
+        }
+
+        let to_fetch: Vec<usize> = (0..total).filter(|&i| !progress.is_present(i)).collect();
+        let missing = to_fetch.len();
+        if missing > 0 {
+            tracing::info!(missing, total, "Downloading missing hints");
+        }
+
+        let mut completed = 0usize;
+        let mut results = stream::iter(to_fetch.into_iter().map(|i| {
+            let cid = manifest.hint_cids[i].clone();
+            let client = self.client.clone();
+            let gateway_url = self.gateway_url.clone();
+            let max_retries = self.max_retries;
+            async move {
+                let hint = fetch_hint_with_retry(&client, &gateway_url, &cid, max_retries).await;
+                (i, hint)
+            }
+        }))
+        .buffer_unordered(self.max_in_flight.max(1));
+
+        while let Some((i, hint)) = results.next().await {
+            let hint = hint?;
+            raw_hints[i] = Some(hint);
+            progress.mark_present(i);
+            completed += 1;
+            if completed % 10_000 == 0 {
+                tracing::info!("Downloaded {}/{} missing hints", completed, missing);
             }
         }
-        
+
+        let raw_hints: Vec<Hint> = raw_hints
+            .into_iter()
+            .enumerate()
+            .map(|(i, hint)| hint.ok_or_else(|| anyhow::anyhow!("hint {i} missing after download")))
+            .collect::<anyhow::Result<_>>()?;
+
+        let computed_root = merkle::compute_merkle_root(&raw_hints);
+        if computed_root != manifest.merkle_root {
+            return Err(anyhow::anyhow!(
+                "Merkle root mismatch: manifest says {}, downloaded hints hash to {}",
+                hex::encode(manifest.merkle_root),
+                hex::encode(computed_root)
+            ));
+        }
+
+        let hints = raw_hints
+            .into_iter()
+            .enumerate()
+            .map(|(i, hint)| {
+                let mut seed = [0u8; 32];
+                seed[..8].copy_from_slice(&(i as u64).to_le_bytes());
+                let subset = Subset::new(seed, manifest.subset_size, manifest.domain_size);
+                (subset, hint)
+            })
+            .collect();
+
+        let mut store = store;
         store.add_hints(hints, manifest.block_number);
-        
+        store.merkle_root = computed_root;
+
         Ok(store)
     }
 
-    /// Download and apply deltas since a block
+    /// Verify a single hint against a trusted Merkle root using a sibling-hash proof.
+    ///
+    /// Lets `sync_deltas` validate an individual changed hint without
+    /// re-downloading and re-hashing the whole set. `root` must itself
+    /// already be trusted by the caller (e.g. anchored on-chain) - this only
+    /// checks that `hint` is internally consistent with it.
+    pub fn verify_hint(
+        hint: &Hint,
+        proof: &[merkle::ProofStep],
+        root: &[u8; 32],
+    ) -> bool {
+        merkle::verify_hint(hint, proof, root)
+    }
+
+    /// Download and apply deltas since a block.
+    ///
+    /// Every changed hint in a delta is verified with [`Self::verify_hint`]
+    /// against that delta's own `new_root` before anything is applied - a
+    /// delta with even one hint that doesn't match its claimed root is
+    /// rejected whole, the same fail-closed behavior `download_hints` already
+    /// gives the bulk download.
     pub async fn sync_deltas(
         &self,
         store: &mut HintStore,
@@ -80,17 +488,181 @@ impl SyncClient {
             let url = format!("{}/ipfs/{}", self.gateway_url, cid);
             let response = self.client.get(&url).send().await?;
             let delta: HintDelta = response.json().await?;
-            
-            // Apply delta to store
+
+            if delta.changes.len() != delta.proofs.len() {
+                anyhow::bail!(
+                    "delta for block {} has {} changes but {} proofs",
+                    delta.block_number,
+                    delta.changes.len(),
+                    delta.proofs.len()
+                );
+            }
+
+            for ((hint_id, new_value), proof) in delta.changes.iter().zip(&delta.proofs) {
+                if !Self::verify_hint(new_value, proof, &delta.new_root) {
+                    anyhow::bail!(
+                        "hint {hint_id} in delta for block {} failed Merkle verification against the delta's claimed root",
+                        delta.block_number
+                    );
+                }
+            }
+
             for (hint_id, new_value) in delta.changes {
                 if let Some(stored) = store.hints.get_mut(hint_id) {
                     stored.hint = new_value;
                 }
             }
-            
+
             store.block_number = delta.block_number;
+            store.merkle_root = delta.new_root;
         }
-        
+
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_manifest_rlp_roundtrip() {
+        let manifest = HintManifest {
+            block_number: 19_000_000,
+            merkle_root: [0x42u8; 32],
+            hint_cids: vec!["QmA".to_string(), "QmB".to_string()],
+            subset_size: 52_250,
+            domain_size: 2_730_000_000,
+        };
+
+        let encoded = manifest.rlp_bytes();
+        let decoded = HintManifest::decode_rlp(&encoded).unwrap();
+
+        assert_eq!(decoded.block_number, manifest.block_number);
+        assert_eq!(decoded.merkle_root, manifest.merkle_root);
+        assert_eq!(decoded.hint_cids, manifest.hint_cids);
+        assert_eq!(decoded.subset_size, manifest.subset_size);
+        assert_eq!(decoded.domain_size, manifest.domain_size);
+    }
+
+    #[test]
+    fn test_manifest_commitment_deterministic() {
+        let manifest = HintManifest {
+            block_number: 1,
+            merkle_root: [0x01u8; 32],
+            hint_cids: vec![],
+            subset_size: 100,
+            domain_size: 1_000_000,
+        };
+
+        assert_eq!(manifest.manifest_commitment(), manifest.manifest_commitment());
+    }
+
+    #[test]
+    fn test_delta_rlp_roundtrip() {
+        let delta = HintDelta {
+            block_number: 42,
+            changes: vec![(0, [0xaau8; 32]), (100, [0xbbu8; 32])],
+            new_root: [0x55u8; 32],
+            proofs: vec![
+                vec![merkle::ProofStep {
+                    sibling: [0x11u8; 32],
+                    sibling_is_left: false,
+                }],
+                vec![
+                    merkle::ProofStep {
+                        sibling: [0x22u8; 32],
+                        sibling_is_left: true,
+                    },
+                    merkle::ProofStep {
+                        sibling: [0x33u8; 32],
+                        sibling_is_left: false,
+                    },
+                ],
+            ],
+        };
+
+        let encoded = delta.rlp_bytes();
+        let decoded = HintDelta::decode_rlp(&encoded).unwrap();
+
+        assert_eq!(decoded.block_number, delta.block_number);
+        assert_eq!(decoded.changes, delta.changes);
+        assert_eq!(decoded.new_root, delta.new_root);
+        assert_eq!(decoded.proofs.len(), delta.proofs.len());
+        for (a, b) in decoded.proofs.iter().zip(&delta.proofs) {
+            assert_eq!(a.len(), b.len());
+            for (step_a, step_b) in a.iter().zip(b) {
+                assert_eq!(step_a.sibling, step_b.sibling);
+                assert_eq!(step_a.sibling_is_left, step_b.sibling_is_left);
+            }
+        }
+    }
+
+    #[test]
+    fn test_verify_hint_rejects_hint_that_fails_its_proof() {
+        let hints = vec![[0xaau8; 32], [0xbbu8; 32]];
+        let tree = merkle::MerkleTree::build(&hints);
+
+        let bad_delta = HintDelta {
+            block_number: 1,
+            changes: vec![(0, hints[0])],
+            new_root: tree.root(),
+            proofs: vec![vec![merkle::ProofStep {
+                sibling: [0xffu8; 32],
+                sibling_is_left: false,
+            }]],
+        };
+
+        assert!(!SyncClient::verify_hint(
+            &bad_delta.changes[0].1,
+            &bad_delta.proofs[0],
+            &bad_delta.new_root
+        ));
+
+        let good_delta = HintDelta {
+            block_number: 1,
+            changes: vec![(0, hints[0])],
+            new_root: tree.root(),
+            proofs: vec![tree.inclusion_proof(0)],
+        };
+
+        assert!(SyncClient::verify_hint(
+            &good_delta.changes[0].1,
+            &good_delta.proofs[0],
+            &good_delta.new_root
+        ));
+    }
+
+    #[test]
+    fn test_download_progress_from_empty_store() {
+        let store = HintStore::new();
+        let progress = DownloadProgress::from_store(&store, 10);
+
+        assert_eq!(progress.missing_count(), 10);
+        assert!(!progress.is_present(0));
+    }
+
+    #[test]
+    fn test_download_progress_from_partial_store() {
+        let mut store = HintStore::new();
+        let subset = Subset::new([0u8; 32], 10, 1_000);
+        store.add_hints(vec![(subset.clone(), [0u8; 32]), (subset, [1u8; 32])], 0);
+
+        let progress = DownloadProgress::from_store(&store, 5);
+
+        assert_eq!(progress.missing_count(), 3);
+        assert!(progress.is_present(0));
+        assert!(progress.is_present(1));
+        assert!(!progress.is_present(2));
+    }
+
+    #[test]
+    fn test_download_progress_mark_present() {
+        let mut progress = DownloadProgress::new(3);
+        assert_eq!(progress.missing_count(), 3);
+
+        progress.mark_present(1);
+        assert!(progress.is_present(1));
+        assert_eq!(progress.missing_count(), 2);
+    }
+}
@@ -24,36 +24,61 @@ impl Prf {
         Self { cipher, domain_size }
     }
 
-    /// Generate a single pseudorandom index in [0, domain_size)
-    pub fn generate_index(&self, counter: u64) -> u64 {
-        let mut block = [0u8; 16];
-        block[..8].copy_from_slice(&counter.to_le_bytes());
-        
-        let mut encrypted = block.into();
-        self.cipher.encrypt_block(&mut encrypted);
-        
-        let value = u64::from_le_bytes(encrypted[..8].try_into().unwrap());
-        value % self.domain_size
+    /// Generate a single pseudorandom index in `[0, domain_size)` via rejection sampling.
+    ///
+    /// Plain `value % domain_size` biases the distribution toward low indices
+    /// whenever `domain_size` does not evenly divide `2^64`. Instead, we
+    /// compute `limit = 2^64 - (2^64 % domain_size)` (the largest multiple of
+    /// `domain_size` that fits in a `u64`) and reject any PRF output `>=
+    /// limit`, advancing `counter` and retrying. `counter` is shared with the
+    /// caller so that rejections here and duplicate-rejections in
+    /// [`Prf::generate_subset`] draw from one monotonic stream of PRF
+    /// outputs, keeping generation fully deterministic.
+    pub fn generate_index(&self, counter: &mut u64) -> u64 {
+        let limit = rejection_limit(self.domain_size);
+
+        loop {
+            let mut block = [0u8; 16];
+            block[..8].copy_from_slice(&counter.to_le_bytes());
+            *counter += 1;
+
+            let mut encrypted = block.into();
+            self.cipher.encrypt_block(&mut encrypted);
+
+            let value = u64::from_le_bytes(encrypted[..8].try_into().unwrap());
+            if (value as u128) < limit {
+                return value % self.domain_size;
+            }
+        }
     }
 
     /// Generate a subset of `size` indices
     pub fn generate_subset(&self, size: usize) -> Vec<u64> {
-        // Use rejection sampling to avoid duplicates
+        // Use rejection sampling to avoid duplicates; `counter` is also
+        // threaded through `generate_index`'s own rejection sampling, so the
+        // whole subset is drawn from one monotonic counter.
         let mut indices = std::collections::HashSet::with_capacity(size);
         let mut counter = 0u64;
-        
+
         while indices.len() < size {
-            let idx = self.generate_index(counter);
+            let idx = self.generate_index(&mut counter);
             indices.insert(idx);
-            counter += 1;
         }
-        
+
         let mut result: Vec<_> = indices.into_iter().collect();
         result.sort_unstable();
         result
     }
 }
 
+/// The largest multiple of `domain_size` that fits in a `u64`, i.e.
+/// `2^64 - (2^64 % domain_size)`. PRF outputs `>= limit` must be rejected to
+/// avoid biasing `value % domain_size` toward low indices.
+fn rejection_limit(domain_size: u64) -> u128 {
+    const TWO_POW_64: u128 = 1u128 << 64;
+    TWO_POW_64 - (TWO_POW_64 % domain_size as u128)
+}
+
 /// Expand a seed into a subset (convenience function)
 pub fn expand_seed(seed: &Seed, subset_size: usize, domain_size: u64) -> Vec<u64> {
     let prf = Prf::new(seed, domain_size);
@@ -95,7 +120,34 @@ mod tests {
         let seed = [42u8; 32];
         let prf = Prf::new(&seed, 1_000_000);
         let subset = prf.generate_subset(1000);
-        
+
         assert_eq!(subset.len(), 1000);
     }
+
+    #[test]
+    fn test_generate_index_uniform_for_non_dividing_domain() {
+        // domain_size = 7 does not evenly divide 2^64, so naive `value %
+        // domain_size` would overrepresent indices 0..(2^64 % 7). Rejection
+        // sampling should keep the histogram close to flat.
+        let domain_size = 7u64;
+        let seed = [7u8; 32];
+        let prf = Prf::new(&seed, domain_size);
+
+        let samples = 70_000;
+        let mut counts = [0u64; 7];
+        let mut counter = 0u64;
+        for _ in 0..samples {
+            let idx = prf.generate_index(&mut counter);
+            counts[idx as usize] += 1;
+        }
+
+        let expected = samples as f64 / domain_size as f64;
+        for &count in &counts {
+            let deviation = (count as f64 - expected).abs() / expected;
+            assert!(
+                deviation < 0.1,
+                "bucket count {count} deviates {deviation:.2} from expected {expected}"
+            );
+        }
+    }
 }
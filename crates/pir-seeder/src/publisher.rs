@@ -2,6 +2,44 @@
 
 use pir_core::{subset::Subset, Hint};
 use serde::{Deserialize, Serialize};
+use tiny_keccak::{Hasher, Keccak};
+
+fn keccak256(data: &[&[u8]]) -> [u8; 32] {
+    let mut hasher = Keccak::v256();
+    for chunk in data {
+        hasher.update(chunk);
+    }
+    let mut out = [0u8; 32];
+    hasher.finalize(&mut out);
+    out
+}
+
+/// Compute the Merkle root over a set of hints, in the same order they're
+/// published to `hint_cids`: each leaf is `keccak256(hint)`, each internal
+/// node `keccak256(left || right)` with odd levels duplicating their last
+/// node - matching what `pir_client::merkle::compute_merkle_root` checks
+/// downloaded hints against (the pir-client/pir-seeder crates each own their
+/// own copy of `HintManifest`'s shape rather than sharing one, so this
+/// mirrors that existing duplication).
+fn compute_merkle_root(hints: &[Hint]) -> [u8; 32] {
+    if hints.is_empty() {
+        return [0u8; 32];
+    }
+
+    let mut level: Vec<[u8; 32]> = hints.iter().map(|h| keccak256(&[h])).collect();
+
+    while level.len() > 1 {
+        let mut next = Vec::with_capacity(level.len().div_ceil(2));
+        for pair in level.chunks(2) {
+            let left = pair[0];
+            let right = *pair.get(1).unwrap_or(&pair[0]);
+            next.push(keccak256(&[&left, &right]));
+        }
+        level = next;
+    }
+
+    level[0]
+}
 
 /// Manifest describing all hints for a snapshot
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -23,24 +61,19 @@ pub async fn publish_to_ipfs(
     block_number: u64,
     ipfs_url: &str,
 ) -> anyhow::Result<HintManifest> {
-    use sha2::{Sha256, Digest};
-    
     let mut hint_cids = Vec::with_capacity(hints.len());
-    let mut hasher = Sha256::new();
-    
+
     // TODO: Use actual IPFS client
     // For now, just compute CIDs locally
-    for (i, (_subset, hint)) in hints.iter().enumerate() {
+    for i in 0..hints.len() {
         // Simulate IPFS add
         let cid = format!("Qm{:064x}", i);
         hint_cids.push(cid);
-        
-        // Update Merkle root
-        hasher.update(hint);
     }
-    
-    let merkle_root: [u8; 32] = hasher.finalize().into();
-    
+
+    let leaves: Vec<Hint> = hints.iter().map(|(_subset, hint)| *hint).collect();
+    let merkle_root = compute_merkle_root(&leaves);
+
     let manifest = HintManifest {
         block_number,
         merkle_root,
@@ -0,0 +1,103 @@
+//! Admin credential check for the `/admin` routes
+//!
+//! Deliberately separate from [`crate::auth`]: the query routes' API keys
+//! are meant to be handed out to many clients, while `/admin/reload`,
+//! `/admin/stats`, and `/admin/config` expose operations a compromised
+//! query client must never reach, so they're gated by one operator-only
+//! token checked via `require_admin_token`.
+
+use std::sync::Arc;
+
+use axum::extract::{Request, State};
+use axum::http::{header, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+
+use crate::server::LaneState;
+
+/// The admin bearer token, if configured. With no token set (the default),
+/// `/admin` is unreachable rather than silently open - same fail-closed
+/// default as [`crate::auth::AuthState`].
+pub struct AdminAuth {
+    token: Option<String>,
+}
+
+impl AdminAuth {
+    pub fn new(token: Option<String>) -> Self {
+        Self { token }
+    }
+
+    fn check(&self, provided: &str) -> bool {
+        match &self.token {
+            Some(expected) => constant_time_eq(expected.as_bytes(), provided.as_bytes()),
+            None => false,
+        }
+    }
+}
+
+impl Default for AdminAuth {
+    fn default() -> Self {
+        Self::new(None)
+    }
+}
+
+/// Compare two byte strings in time independent of where they first
+/// differ, so a timing side channel can't be used to guess the token one
+/// byte at a time.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b.iter()).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// Axum middleware guarding the `/admin` routes: `401` unless
+/// `Authorization: Bearer <token>` matches the configured admin token.
+pub async fn require_admin_token(
+    State(lane): State<Arc<LaneState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let provided = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "));
+
+    match provided {
+        Some(token) if lane.admin_auth.check(token) => next.run(request).await,
+        _ => (StatusCode::UNAUTHORIZED, "invalid or missing admin token").into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_admin_auth_rejects_everything() {
+        let auth = AdminAuth::default();
+        assert!(!auth.check("anything"));
+    }
+
+    #[test]
+    fn test_admin_auth_accepts_matching_token() {
+        let auth = AdminAuth::new(Some("secret".to_string()));
+        assert!(auth.check("secret"));
+    }
+
+    #[test]
+    fn test_admin_auth_rejects_wrong_token() {
+        let auth = AdminAuth::new(Some("secret".to_string()));
+        assert!(!auth.check("wrong"));
+        assert!(!auth.check("secre"));
+        assert!(!auth.check("secrets"));
+    }
+
+    #[test]
+    fn test_constant_time_eq() {
+        assert!(constant_time_eq(b"abc", b"abc"));
+        assert!(!constant_time_eq(b"abc", b"abd"));
+        assert!(!constant_time_eq(b"abc", b"ab"));
+    }
+}
@@ -0,0 +1,251 @@
+//! API-key authentication and per-key rate limiting for the query routes
+//!
+//! Checked via [`require_api_key`], an axum middleware `route_layer`'d onto
+//! just the `/query` and `/query_batch` routes in [`crate::server`] -
+//! `/health` and `/metrics` stay open so operators can probe the process
+//! without a key. An unrecognized key (including no key at all, and the
+//! default empty key table) gets `401`; a recognized key that has drained
+//! its token bucket gets `429` with `Retry-After`.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use axum::extract::{Request, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+
+use crate::server::LaneState;
+
+/// A token bucket: `capacity` tokens, refilling at `refill_per_second`,
+/// lazily caught up on each [`TokenBucket::try_take`] instead of running a
+/// background ticker.
+struct TokenBucket {
+    capacity: f64,
+    refill_per_second: f64,
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    fn new(capacity: f64, refill_per_second: f64) -> Self {
+        Self {
+            capacity,
+            refill_per_second,
+            tokens: capacity,
+            last_refill: Instant::now(),
+        }
+    }
+
+    /// Refill for elapsed time, then take one token if available. On
+    /// exhaustion, returns how long until a token would next be available.
+    fn try_take(&mut self) -> Result<(), Duration> {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last_refill).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.refill_per_second).min(self.capacity);
+        self.last_refill = now;
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            Ok(())
+        } else if self.refill_per_second <= 0.0 {
+            // A zero refill rate (e.g. the locked-down default) never
+            // recovers on its own - report a conservative fixed wait
+            // instead of dividing by zero.
+            Err(Duration::from_secs(3600))
+        } else {
+            let wait_secs = (1.0 - self.tokens) / self.refill_per_second;
+            Err(Duration::from_secs_f64(wait_secs))
+        }
+    }
+}
+
+/// The configured API key table and each key's token-bucket limiter.
+/// Shared across both lanes, since one client should be rate-limited the
+/// same way regardless of which lane it queries.
+pub struct AuthState {
+    keys: HashSet<String>,
+    capacity: f64,
+    refill_per_second: f64,
+    buckets: Mutex<HashMap<String, TokenBucket>>,
+}
+
+impl AuthState {
+    /// Build the key table. Every accepted key shares the same `capacity` /
+    /// `refill_per_second` limit; an empty `keys` set (the default if
+    /// `ServerBuilder::with_api_keys` is never called) rejects every
+    /// request, so a server is locked down unless explicitly configured
+    /// with keys rather than silently left open.
+    pub fn new(
+        keys: impl IntoIterator<Item = String>,
+        capacity: f64,
+        refill_per_second: f64,
+    ) -> Self {
+        Self {
+            keys: keys.into_iter().collect(),
+            capacity,
+            refill_per_second,
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn check(&self, key: &str) -> Result<(), AuthRejection> {
+        let known = self
+            .keys
+            .iter()
+            .fold(false, |found, candidate| found | constant_time_eq(candidate.as_bytes(), key.as_bytes()));
+        if !known {
+            return Err(AuthRejection::UnknownKey);
+        }
+
+        let mut buckets = self.buckets.lock().unwrap();
+        let bucket = buckets
+            .entry(key.to_string())
+            .or_insert_with(|| TokenBucket::new(self.capacity, self.refill_per_second));
+        bucket.try_take().map_err(AuthRejection::RateLimited)
+    }
+}
+
+impl Default for AuthState {
+    /// No keys configured - every query request is rejected with `401`.
+    fn default() -> Self {
+        Self::new(std::iter::empty(), 0.0, 0.0)
+    }
+}
+
+/// Compare two byte strings in time independent of where they first
+/// differ, so a timing side channel can't be used to guess a valid key one
+/// byte at a time. Same reasoning as [`crate::admin::AdminAuth::check`].
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b.iter()).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+enum AuthRejection {
+    UnknownKey,
+    RateLimited(Duration),
+}
+
+impl IntoResponse for AuthRejection {
+    fn into_response(self) -> Response {
+        match self {
+            AuthRejection::UnknownKey => {
+                (StatusCode::UNAUTHORIZED, "unknown or missing API key").into_response()
+            }
+            AuthRejection::RateLimited(wait) => {
+                let retry_after = wait.as_secs().max(1).to_string();
+                (
+                    StatusCode::TOO_MANY_REQUESTS,
+                    [(header::RETRY_AFTER, retry_after)],
+                    "rate limit exceeded",
+                )
+                    .into_response()
+            }
+        }
+    }
+}
+
+/// Pull the API key out of `Authorization: Bearer <key>` or `X-API-Key`,
+/// preferring the former when both are present.
+fn extract_key(headers: &HeaderMap) -> Option<&str> {
+    if let Some(key) = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+    {
+        return Some(key);
+    }
+    headers.get("x-api-key").and_then(|v| v.to_str().ok())
+}
+
+/// Middleware guarding `/query` and `/query_batch`: reject requests with an
+/// unrecognized (or missing) API key with `401`, or one that has exhausted
+/// its token bucket with `429`.
+pub async fn require_api_key(
+    State(lane): State<Arc<LaneState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let key = match extract_key(request.headers()) {
+        Some(key) => key.to_string(),
+        None => return AuthRejection::UnknownKey.into_response(),
+    };
+
+    match lane.auth.check(&key) {
+        Ok(()) => next.run(request).await,
+        Err(rejection) => rejection.into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_auth_state_rejects_everything() {
+        let auth = AuthState::default();
+        assert!(auth.check("any-key").is_err());
+    }
+
+    #[test]
+    fn test_unknown_key_rejected() {
+        let auth = AuthState::new(["known".to_string()], 10.0, 1.0);
+        assert!(auth.check("unknown").is_err());
+    }
+
+    #[test]
+    fn test_known_key_accepted_until_bucket_drains() {
+        let auth = AuthState::new(["known".to_string()], 2.0, 0.0);
+        assert!(auth.check("known").is_ok());
+        assert!(auth.check("known").is_ok());
+        // Capacity 2, no refill - the third request in the same instant
+        // must be rate limited.
+        assert!(matches!(
+            auth.check("known"),
+            Err(AuthRejection::RateLimited(_))
+        ));
+    }
+
+    #[test]
+    fn test_any_configured_key_is_accepted() {
+        let auth = AuthState::new(
+            ["first".to_string(), "second".to_string(), "third".to_string()],
+            10.0,
+            1.0,
+        );
+        assert!(auth.check("first").is_ok());
+        assert!(auth.check("second").is_ok());
+        assert!(auth.check("third").is_ok());
+    }
+
+    #[test]
+    fn test_constant_time_eq_matches_equality() {
+        assert!(constant_time_eq(b"matching", b"matching"));
+        assert!(!constant_time_eq(b"matching", b"different"));
+        assert!(!constant_time_eq(b"short", b"longer-string"));
+    }
+
+    #[test]
+    fn test_extract_key_prefers_bearer_over_x_api_key() {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::AUTHORIZATION, "Bearer abc".parse().unwrap());
+        headers.insert("x-api-key", "def".parse().unwrap());
+        assert_eq!(extract_key(&headers), Some("abc"));
+    }
+
+    #[test]
+    fn test_extract_key_falls_back_to_x_api_key_header() {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-api-key", "def".parse().unwrap());
+        assert_eq!(extract_key(&headers), Some("def"));
+    }
+
+    #[test]
+    fn test_extract_key_none_when_absent() {
+        let headers = HeaderMap::new();
+        assert_eq!(extract_key(&headers), None);
+    }
+}
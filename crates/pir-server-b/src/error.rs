@@ -0,0 +1,75 @@
+//! Server error types
+
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use serde::Serialize;
+use thiserror::Error;
+
+/// Structured error response for API clients
+#[derive(Debug, Serialize)]
+pub struct ErrorResponse {
+    pub error: String,
+    pub code: &'static str,
+}
+
+#[derive(Error, Debug)]
+pub enum ServerError {
+    #[error("invalid query: {0}")]
+    InvalidQuery(String),
+
+    #[error("database corrupt: expected size to be a multiple of {expected} bytes, got {actual}")]
+    DatabaseCorrupt { expected: usize, actual: usize },
+
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error(
+        "incremental deltas aren't supported for a flat PIR database (no bucket/stem structure to diff against) - fetch /raw instead"
+    )]
+    DeltasUnsupported,
+
+    #[error("invalid server configuration: {0}")]
+    InvalidConfig(String),
+
+    #[error("malformed request body: {0}")]
+    MalformedBody(String),
+}
+
+impl ServerError {
+    fn code(&self) -> &'static str {
+        match self {
+            ServerError::InvalidQuery(_) => "INVALID_QUERY",
+            ServerError::DatabaseCorrupt { .. } => "DATABASE_CORRUPT",
+            ServerError::Io(_) => "IO_ERROR",
+            ServerError::DeltasUnsupported => "DELTAS_UNSUPPORTED",
+            ServerError::InvalidConfig(_) => "INVALID_CONFIG",
+            ServerError::MalformedBody(_) => "MALFORMED_BODY",
+        }
+    }
+
+    fn status(&self) -> StatusCode {
+        match self {
+            ServerError::InvalidQuery(_) => StatusCode::BAD_REQUEST,
+            ServerError::DatabaseCorrupt { .. } => StatusCode::INTERNAL_SERVER_ERROR,
+            ServerError::Io(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            ServerError::DeltasUnsupported => StatusCode::NOT_IMPLEMENTED,
+            ServerError::InvalidConfig(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            ServerError::MalformedBody(_) => StatusCode::BAD_REQUEST,
+        }
+    }
+}
+
+impl IntoResponse for ServerError {
+    fn into_response(self) -> Response {
+        let status = self.status();
+        let body = ErrorResponse {
+            error: self.to_string(),
+            code: self.code(),
+        };
+
+        (status, Json(body)).into_response()
+    }
+}
+
+pub type Result<T> = std::result::Result<T, ServerError>;
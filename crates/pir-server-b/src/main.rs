@@ -5,11 +5,24 @@ use memmap2::Mmap;
 use std::sync::Arc;
 use tracing_subscriber::EnvFilter;
 
+mod admin;
+mod auth;
+mod error;
+mod metrics;
 mod responder;
 mod server;
 
 use responder::Responder;
-use server::{create_router, AppState};
+use server::ServerBuilder;
+
+fn load_responder(db_path: &str) -> Result<Responder> {
+    tracing::info!("Loading database from {}", db_path);
+    let file = std::fs::File::open(db_path)?;
+    let mmap = Arc::new(unsafe { Mmap::map(&file)? });
+    let responder = Responder::new(mmap)?;
+    tracing::info!("Loaded {} entries from {}", responder.entry_count(), db_path);
+    Ok(responder)
+}
 
 #[tokio::main]
 async fn main() -> Result<()> {
@@ -17,37 +30,85 @@ async fn main() -> Result<()> {
         .with_env_filter(EnvFilter::from_default_env())
         .init();
 
+    let metrics_handle = metrics::init_prometheus_recorder();
+
     let args: Vec<String> = std::env::args().collect();
-    
-    if args.len() < 2 {
-        eprintln!("Usage: pir-server-b <database.bin> [--port PORT]");
+
+    let hot_path = args
+        .iter()
+        .position(|a| a == "--hot")
+        .and_then(|i| args.get(i + 1));
+    let cold_path = args
+        .iter()
+        .position(|a| a == "--cold")
+        .and_then(|i| args.get(i + 1));
+
+    if hot_path.is_none() && cold_path.is_none() {
+        eprintln!(
+            "Usage: pir-server-b --hot <db.bin> | --cold <db.bin> [--hot <db.bin>] [--port PORT] [--no-raw] [--no-deltas] [--no-query] [--no-admin] [--api-key KEY]... [--rate-capacity N] [--rate-refill-per-second N] [--admin-token TOKEN]"
+        );
         std::process::exit(1);
     }
-    
-    let db_path = &args[1];
+
     let port: u16 = args
         .iter()
         .position(|a| a == "--port")
         .and_then(|i| args.get(i + 1))
         .and_then(|p| p.parse().ok())
         .unwrap_or(3000);
-    
-    tracing::info!("Loading database from {}", db_path);
-    
-    let file = std::fs::File::open(db_path)?;
-    let mmap = Arc::new(unsafe { Mmap::map(&file)? });
-    
-    let responder = Responder::new(mmap);
-    tracing::info!("Loaded {} entries", responder.entry_count());
-    
-    let state = Arc::new(AppState { responder });
-    let app = create_router(state);
-    
+
+    // Every occurrence of --api-key adds one accepted key; none means the
+    // query routes reject everything (see AuthState::default).
+    let api_keys: Vec<String> = args
+        .iter()
+        .zip(args.iter().skip(1))
+        .filter(|(flag, _)| *flag == "--api-key")
+        .map(|(_, key)| key.clone())
+        .collect();
+    let rate_capacity: f64 = args
+        .iter()
+        .position(|a| a == "--rate-capacity")
+        .and_then(|i| args.get(i + 1))
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(60.0);
+    let rate_refill_per_second: f64 = args
+        .iter()
+        .position(|a| a == "--rate-refill-per-second")
+        .and_then(|i| args.get(i + 1))
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(10.0);
+    let admin_token = args
+        .iter()
+        .position(|a| a == "--admin-token")
+        .and_then(|i| args.get(i + 1))
+        .cloned();
+
+    let mut builder = ServerBuilder::new()
+        .with_metrics_handle(metrics_handle)
+        .with_api_keys(api_keys, rate_capacity, rate_refill_per_second)
+        .enable_raw(!args.iter().any(|a| a == "--no-raw"))
+        .enable_deltas(!args.iter().any(|a| a == "--no-deltas"))
+        .enable_query(!args.iter().any(|a| a == "--no-query"))
+        .enable_admin(!args.iter().any(|a| a == "--no-admin"));
+
+    if let Some(token) = admin_token {
+        builder = builder.with_admin_token(token);
+    }
+
+    if let Some(path) = hot_path {
+        builder = builder.with_hot_lane(load_responder(path)?, path.clone());
+    }
+    if let Some(path) = cold_path {
+        builder = builder.with_cold_lane(load_responder(path)?, path.clone());
+    }
+
+    let (_state, app) = builder.build()?;
+
     let addr = format!("0.0.0.0:{}", port);
     tracing::info!("Starting server on {}", addr);
-    
+
     let listener = tokio::net::TcpListener::bind(&addr).await?;
     axum::serve(listener, app).await?;
-    
+
     Ok(())
 }
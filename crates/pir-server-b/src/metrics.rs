@@ -0,0 +1,19 @@
+//! Prometheus metrics registry for the PIR query server
+//!
+//! Installs the global `metrics` recorder this binary's [`crate::responder::Responder`]
+//! records against (XOR compute time, expanded subset size), and the
+//! `lane_router_hits_total`/`lane_router_*_count` gauges `inspire_core`'s
+//! `LaneRouter::route` records in processes that link it. [`AppState`](crate::server::AppState)
+//! holds the returned handle so the `/metrics` route can render it.
+
+use metrics_exporter_prometheus::{PrometheusBuilder, PrometheusHandle};
+
+/// Install the global Prometheus recorder and return a handle that renders
+/// the current registry in Prometheus text format. Must be called exactly
+/// once, before any `metrics::counter!`/`gauge!`/`histogram!` call - `main`
+/// does this before constructing [`AppState`](crate::server::AppState).
+pub fn init_prometheus_recorder() -> PrometheusHandle {
+    PrometheusBuilder::new()
+        .install_recorder()
+        .expect("failed to install Prometheus recorder")
+}
@@ -2,8 +2,11 @@
 
 use pir_core::{hint, prf::expand_seed, subset::CompressedQuery, Hint, ENTRY_SIZE};
 use memmap2::Mmap;
+use metrics::histogram;
 use std::sync::Arc;
 
+use crate::error::ServerError;
+
 /// Database handle for responding to queries
 pub struct Responder {
     mmap: Arc<Mmap>,
@@ -11,14 +14,39 @@ pub struct Responder {
 }
 
 impl Responder {
-    /// Create a responder from a memory-mapped database file
-    pub fn new(mmap: Arc<Mmap>) -> Self {
+    /// Create a responder from a memory-mapped database file.
+    ///
+    /// Fails with [`ServerError::DatabaseCorrupt`] if the file's size isn't
+    /// an exact multiple of `ENTRY_SIZE`, rather than silently truncating
+    /// the last partial entry.
+    pub fn new(mmap: Arc<Mmap>) -> Result<Self, ServerError> {
+        if mmap.len() % ENTRY_SIZE != 0 {
+            return Err(ServerError::DatabaseCorrupt {
+                expected: ENTRY_SIZE,
+                actual: mmap.len(),
+            });
+        }
         let entry_count = (mmap.len() / ENTRY_SIZE) as u64;
-        Self { mmap, entry_count }
+        Ok(Self { mmap, entry_count })
     }
 
-    /// Process a compressed query and return the XOR result
-    pub fn respond(&self, query: &CompressedQuery) -> Hint {
+    /// Process a compressed query and return the XOR result, recording a
+    /// histogram of the expanded subset size and the XOR compute time.
+    ///
+    /// Returns [`ServerError::InvalidQuery`] instead of silently zero-filling
+    /// when `query.domain_size` doesn't match the database's actual entry
+    /// count, or when an expanded index falls outside it - either of which
+    /// would otherwise corrupt the XOR result without any signal.
+    pub fn respond(&self, query: &CompressedQuery) -> Result<Hint, ServerError> {
+        if query.domain_size != self.entry_count {
+            return Err(ServerError::InvalidQuery(format!(
+                "query domain_size {} does not match database entry_count {}",
+                query.domain_size, self.entry_count
+            )));
+        }
+
+        let start = std::time::Instant::now();
+
         // Expand seed to get subset indices
         let indices = expand_seed(
             &query.seed,
@@ -26,24 +54,49 @@ impl Responder {
             query.domain_size,
         );
 
+        if let Some(&bad_idx) = indices.iter().find(|&&idx| idx >= self.entry_count) {
+            return Err(ServerError::InvalidQuery(format!(
+                "expanded index {bad_idx} is out of range for {} entries",
+                self.entry_count
+            )));
+        }
+        histogram!("pir_responder_subset_size").record(indices.len() as f64);
+
         // XOR all entries at those indices
-        hint::compute_hint(&indices, |idx| self.get_entry(idx))
+        let result = hint::compute_hint(&indices, |idx| self.get_entry(idx));
+
+        histogram!("pir_responder_xor_seconds").record(start.elapsed().as_secs_f64());
+        Ok(result)
+    }
+
+    /// Process many queries against the same mmap'd database, amortizing
+    /// per-request overhead (e.g. when a wallet needs several storage
+    /// slots in one round trip) instead of one HTTP round trip per query.
+    /// Fails on the first invalid query, same as calling `respond` in a
+    /// loop and propagating its error.
+    pub fn respond_batch(&self, queries: &[CompressedQuery]) -> Result<Vec<Hint>, ServerError> {
+        queries.iter().map(|query| self.respond(query)).collect()
     }
 
-    /// Get a single entry from the database
+    /// Get a single entry from the database. Only called with indices
+    /// `respond` has already validated are `< entry_count`, so the slice is
+    /// always in bounds.
     fn get_entry(&self, idx: u64) -> [u8; ENTRY_SIZE] {
         let offset = (idx as usize) * ENTRY_SIZE;
-        if offset + ENTRY_SIZE <= self.mmap.len() {
-            self.mmap[offset..offset + ENTRY_SIZE].try_into().unwrap()
-        } else {
-            [0u8; ENTRY_SIZE]
-        }
+        self.mmap[offset..offset + ENTRY_SIZE].try_into().unwrap()
     }
 
     /// Entry count
     pub fn entry_count(&self) -> u64 {
         self.entry_count
     }
+
+    /// The full mmap'd database, byte for byte. Backs a "raw" bulk-download
+    /// endpoint for edge nodes that want to sync the whole database without
+    /// issuing PIR queries.
+    pub fn raw_bytes(&self) -> &[u8] {
+        &self.mmap
+    }
 }
 
 #[cfg(test)]
@@ -70,10 +123,78 @@ mod tests {
             [2u8; ENTRY_SIZE],
             [3u8; ENTRY_SIZE],
         ];
-        
+
         let (_file, mmap) = create_test_db(&entries);
-        let responder = Responder::new(mmap);
-        
+        let responder = Responder::new(mmap).unwrap();
+
         assert_eq!(responder.entry_count(), 3);
     }
+
+    #[test]
+    fn test_new_rejects_truncated_database() {
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(&[0u8; ENTRY_SIZE]).unwrap();
+        file.write_all(&[0u8; 5]).unwrap(); // partial trailing entry
+        file.flush().unwrap();
+
+        let mmap = Arc::new(unsafe { Mmap::map(file.as_file()).unwrap() });
+
+        match Responder::new(mmap) {
+            Err(ServerError::DatabaseCorrupt { expected, actual }) => {
+                assert_eq!(expected, ENTRY_SIZE);
+                assert_eq!(actual, ENTRY_SIZE + 5);
+            }
+            other => panic!("expected DatabaseCorrupt, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_respond_rejects_domain_size_mismatch() {
+        let entries = [[1u8; ENTRY_SIZE], [2u8; ENTRY_SIZE], [3u8; ENTRY_SIZE]];
+        let (_file, mmap) = create_test_db(&entries);
+        let responder = Responder::new(mmap).unwrap();
+
+        let query = CompressedQuery {
+            seed: [1u8; 32],
+            subset_size: 1,
+            domain_size: 1_000, // doesn't match the 3-entry database
+        };
+
+        assert!(matches!(
+            responder.respond(&query),
+            Err(ServerError::InvalidQuery(_))
+        ));
+    }
+
+    #[test]
+    fn test_respond_batch_matches_individual_respond_calls() {
+        let entries = [
+            [1u8; ENTRY_SIZE],
+            [2u8; ENTRY_SIZE],
+            [3u8; ENTRY_SIZE],
+        ];
+        let (_file, mmap) = create_test_db(&entries);
+        let responder = Responder::new(mmap).unwrap();
+
+        let queries = vec![
+            CompressedQuery {
+                seed: [1u8; 32],
+                subset_size: 2,
+                domain_size: 3,
+            },
+            CompressedQuery {
+                seed: [2u8; 32],
+                subset_size: 1,
+                domain_size: 3,
+            },
+        ];
+
+        let batched = responder.respond_batch(&queries).unwrap();
+        let individual: Vec<_> = queries
+            .iter()
+            .map(|q| responder.respond(q).unwrap())
+            .collect();
+
+        assert_eq!(batched, individual);
+    }
 }
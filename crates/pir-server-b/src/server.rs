@@ -1,19 +1,324 @@
 //! HTTP server for PIR queries
+//!
+//! The service is composed from independently toggleable modules - raw
+//! index serving, `/deltas`, and the PIR query responder - and can mount a
+//! hot lane and a cold lane (each its own mmap + [`Responder`]) in one
+//! process, routed under `/hot` and `/cold`. `ServerBuilder` lets an
+//! operator pick which modules and lanes to run from the same binary
+//! without recompiling (e.g. a deltas-only edge node, or a query-only
+//! node).
 
+use crate::admin::AdminAuth;
+use crate::auth::AuthState;
+use crate::error::ServerError;
 use crate::responder::Responder;
+use arc_swap::ArcSwap;
 use axum::{
+    body::Bytes,
     extract::State,
-    http::StatusCode,
+    http::{header, HeaderMap},
+    middleware,
+    response::{IntoResponse, Response},
     routing::{get, post},
     Json, Router,
 };
+use metrics::{counter, gauge, histogram};
+use metrics_exporter_prometheus::PrometheusHandle;
 use pir_core::subset::CompressedQuery;
 use serde::{Deserialize, Serialize};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
 
-/// Server state
+/// Which optional HTTP modules a server instance exposes. All default to
+/// enabled; `ServerBuilder` can turn any subset off.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct EnabledModules {
+    pub raw: bool,
+    pub deltas: bool,
+    pub query: bool,
+    pub admin: bool,
+}
+
+impl Default for EnabledModules {
+    fn default() -> Self {
+        Self {
+            raw: true,
+            deltas: true,
+            query: true,
+            admin: true,
+        }
+    }
+}
+
+/// Bookkeeping around the lane's current snapshot, updated each
+/// `/admin/reload` and echoed by `/admin/stats` and `/admin/config`. Behind
+/// its own `Mutex` rather than `responder`'s `ArcSwap` - reload is rare
+/// enough that locking here costs nothing, and keeping it separate means a
+/// stats read never contends with the query hot path.
+pub struct LaneMeta {
+    pub source_path: String,
+    pub loaded_at: SystemTime,
+}
+
+/// Per-lane state: the lane's database responder behind an `ArcSwap` so
+/// `/admin/reload` can swap in a new snapshot without readers ever
+/// blocking, a semaphore (shared by both lanes) bounding how many batched
+/// queries run concurrently, and the lane's metadata. Mounted under `/hot` or `/cold`
+/// as its own `Router<Arc<LaneState>>`.
+pub struct LaneState {
+    pub responder: ArcSwap<Responder>,
+    pub query_semaphore: Arc<tokio::sync::Semaphore>,
+    /// The limit `query_semaphore` was built with, kept alongside it since
+    /// a `Semaphore` only reports permits currently available, not its
+    /// original capacity - `/admin/config` needs the latter.
+    pub max_concurrent_queries: usize,
+    pub auth: Arc<AuthState>,
+    pub admin_auth: Arc<AdminAuth>,
+    pub meta: Mutex<LaneMeta>,
+    pub modules: EnabledModules,
+    /// "hot" or "cold" - the `lane` label `/admin/reload` re-tags
+    /// `pir_server_entry_count` with after a swap.
+    pub lane_label: &'static str,
+}
+
+/// Default concurrency limit for `/query_batch`, when `ServerBuilder` isn't
+/// given an explicit one: one in-flight query per available core, since
+/// `Responder::respond` is CPU-bound.
+fn default_query_concurrency() -> usize {
+    std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(4)
+}
+
+/// Server state. `hot` and/or `cold` may each be absent - `ServerBuilder`
+/// requires at least one to be present.
 pub struct AppState {
-    pub responder: Responder,
+    pub hot: Option<Arc<LaneState>>,
+    pub cold: Option<Arc<LaneState>>,
+    pub modules: EnabledModules,
+    /// Handle to the installed Prometheus recorder, rendered by `/metrics`.
+    pub metrics_handle: PrometheusHandle,
+}
+
+/// Builds an [`AppState`] and its [`Router`] from independently toggleable
+/// lanes and modules.
+///
+/// ```ignore
+/// let (_state, app) = ServerBuilder::new()
+///     .with_hot_lane(hot_responder, "hot.bin")
+///     .with_cold_lane(cold_responder, "cold.bin")
+///     .with_metrics_handle(metrics_handle)
+///     .enable_deltas(false)
+///     .build()?;
+/// ```
+#[derive(Default)]
+pub struct ServerBuilder {
+    hot: Option<(Responder, String)>,
+    cold: Option<(Responder, String)>,
+    modules: EnabledModules,
+    metrics_handle: Option<PrometheusHandle>,
+    max_concurrent_queries: Option<usize>,
+    auth: Option<AuthState>,
+    admin_token: Option<String>,
+}
+
+impl ServerBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// `source_path` is recorded so `/admin/stats` and `/admin/config` can
+    /// report where the lane's current snapshot was loaded from; a reload
+    /// replaces it with the new snapshot's path.
+    pub fn with_hot_lane(mut self, responder: Responder, source_path: impl Into<String>) -> Self {
+        self.hot = Some((responder, source_path.into()));
+        self
+    }
+
+    pub fn with_cold_lane(mut self, responder: Responder, source_path: impl Into<String>) -> Self {
+        self.cold = Some((responder, source_path.into()));
+        self
+    }
+
+    pub fn with_metrics_handle(mut self, metrics_handle: PrometheusHandle) -> Self {
+        self.metrics_handle = Some(metrics_handle);
+        self
+    }
+
+    /// Cap how many `/query_batch` sub-queries run at once, counted across
+    /// all batch requests (shared across both lanes). Defaults to the number of available
+    /// cores if never called.
+    pub fn with_max_concurrent_queries(mut self, limit: usize) -> Self {
+        self.max_concurrent_queries = Some(limit);
+        self
+    }
+
+    /// Configure the API keys accepted by `/query` and `/query_batch`, each
+    /// sharing a token-bucket limit of `capacity` requests that refills at
+    /// `refill_per_second`. Without this call the key table is empty and
+    /// every query request is rejected - see [`AuthState::default`].
+    pub fn with_api_keys(
+        mut self,
+        keys: impl IntoIterator<Item = String>,
+        capacity: f64,
+        refill_per_second: f64,
+    ) -> Self {
+        self.auth = Some(AuthState::new(keys, capacity, refill_per_second));
+        self
+    }
+
+    pub fn enable_raw(mut self, enabled: bool) -> Self {
+        self.modules.raw = enabled;
+        self
+    }
+
+    pub fn enable_deltas(mut self, enabled: bool) -> Self {
+        self.modules.deltas = enabled;
+        self
+    }
+
+    pub fn enable_query(mut self, enabled: bool) -> Self {
+        self.modules.query = enabled;
+        self
+    }
+
+    pub fn enable_admin(mut self, enabled: bool) -> Self {
+        self.modules.admin = enabled;
+        self
+    }
+
+    /// Set the bearer token required by `/admin/*`. Without this call the
+    /// admin routes reject every request - see [`AdminAuth::default`].
+    pub fn with_admin_token(mut self, token: impl Into<String>) -> Self {
+        self.admin_token = Some(token.into());
+        self
+    }
+
+    /// Assemble the configured lanes and modules into an `AppState` and its
+    /// top-level `Router`. Fails if no lane was configured or no metrics
+    /// handle was supplied, rather than serving a state with nothing to
+    /// query.
+    pub fn build(self) -> Result<(Arc<AppState>, Router), ServerError> {
+        if self.hot.is_none() && self.cold.is_none() {
+            return Err(ServerError::InvalidConfig(
+                "at least one of hot or cold lane must be configured".to_string(),
+            ));
+        }
+        let metrics_handle = self.metrics_handle.ok_or_else(|| {
+            ServerError::InvalidConfig("metrics handle must be configured".to_string())
+        })?;
+
+        let max_concurrent_queries = self
+            .max_concurrent_queries
+            .unwrap_or_else(default_query_concurrency);
+        let query_semaphore = Arc::new(tokio::sync::Semaphore::new(max_concurrent_queries));
+        let auth = Arc::new(self.auth.unwrap_or_default());
+        let admin_auth = Arc::new(AdminAuth::new(self.admin_token));
+        let modules = self.modules;
+
+        let make_lane = |responder: Responder, source_path: String, lane_label: &'static str| {
+            Arc::new(LaneState {
+                responder: ArcSwap::new(Arc::new(responder)),
+                query_semaphore: query_semaphore.clone(),
+                max_concurrent_queries,
+                auth: auth.clone(),
+                admin_auth: admin_auth.clone(),
+                meta: Mutex::new(LaneMeta {
+                    source_path,
+                    loaded_at: SystemTime::now(),
+                }),
+                modules,
+                lane_label,
+            })
+        };
+
+        let state = Arc::new(AppState {
+            hot: self
+                .hot
+                .map(|(responder, path)| make_lane(responder, path, "hot")),
+            cold: self
+                .cold
+                .map(|(responder, path)| make_lane(responder, path, "cold")),
+            modules: self.modules,
+            metrics_handle,
+        });
+
+        // entry_count is fixed for the life of the mmap'd database, so
+        // report it once here rather than recomputing it per scrape; a
+        // reload updates the gauge itself, in `admin_reload_handler`.
+        if let Some(hot) = &state.hot {
+            gauge!("pir_server_entry_count", "lane" => "hot")
+                .set(hot.responder.load().entry_count() as f64);
+        }
+        if let Some(cold) = &state.cold {
+            gauge!("pir_server_entry_count", "lane" => "cold")
+                .set(cold.responder.load().entry_count() as f64);
+        }
+
+        Ok((state.clone(), create_router(state)))
+    }
+}
+
+/// `/health` and `/metrics`, always mounted regardless of which lanes or
+/// modules are enabled, plus each configured lane's router nested under
+/// `/hot` or `/cold`.
+pub fn create_router(state: Arc<AppState>) -> Router {
+    let mut router = Router::new()
+        .route("/health", get(health_handler))
+        .route("/metrics", get(metrics_handler))
+        .with_state(state.clone());
+
+    if let Some(hot) = &state.hot {
+        router = router.nest(
+            "/hot",
+            build_lane_router(hot.clone()).with_state(hot.clone()),
+        );
+    }
+    if let Some(cold) = &state.cold {
+        router = router.nest(
+            "/cold",
+            build_lane_router(cold.clone()).with_state(cold.clone()),
+        );
+    }
+    router
+}
+
+/// Build the per-lane router for whichever of `lane`'s modules are enabled,
+/// with the auth middlewares bound to `lane`'s state. Returned generic over
+/// `Arc<LaneState>` so the same router shape can be `.with_state()`-bound
+/// to either the hot or cold lane's state and nested under `/hot` or
+/// `/cold`.
+fn build_lane_router(lane: Arc<LaneState>) -> Router<Arc<LaneState>> {
+    let modules = lane.modules;
+    let mut router = Router::new();
+    if modules.raw {
+        router = router.route("/raw", get(raw_handler));
+    }
+    if modules.deltas {
+        router = router.route("/deltas", get(deltas_handler));
+    }
+    if modules.query {
+        let query_router = Router::new()
+            .route("/query", post(query_handler))
+            .route("/query_batch", post(query_batch_handler))
+            .route_layer(middleware::from_fn_with_state(
+                lane.clone(),
+                crate::auth::require_api_key,
+            ));
+        router = router.merge(query_router);
+    }
+    if modules.admin {
+        let admin_router = Router::new()
+            .route("/admin/reload", post(admin_reload_handler))
+            .route("/admin/stats", get(admin_stats_handler))
+            .route("/admin/config", get(admin_config_handler))
+            .route_layer(middleware::from_fn_with_state(
+                lane,
+                crate::admin::require_admin_token,
+            ));
+        router = router.merge(admin_router);
+    }
+    router
 }
 
 /// Query request
@@ -29,42 +334,396 @@ pub struct QueryResponse {
     pub query_time_ms: f64,
 }
 
+/// Batched query request: many independent sub-queries in one round trip
+#[derive(Debug, Deserialize)]
+pub struct BatchQueryRequest {
+    pub queries: Vec<CompressedQuery>,
+}
+
+/// Batched query response: results aligned 1:1 with the request's `queries`
+#[derive(Debug, Serialize)]
+pub struct BatchQueryResponse {
+    pub results: Vec<QueryResponse>,
+    pub total_time_ms: f64,
+}
+
 /// Health check response
 #[derive(Debug, Serialize)]
 pub struct HealthResponse {
     pub status: String,
+    pub hot_entry_count: Option<u64>,
+    pub cold_entry_count: Option<u64>,
+}
+
+/// `/admin/reload` request: path to the new snapshot to swap in for this
+/// lane.
+#[derive(Debug, Deserialize)]
+pub struct ReloadRequest {
+    pub path: String,
+}
+
+/// `/admin/reload` response.
+#[derive(Debug, Serialize)]
+pub struct ReloadResponse {
     pub entry_count: u64,
+    pub source_path: String,
 }
 
-/// Create the router
-pub fn create_router(state: Arc<AppState>) -> Router {
-    Router::new()
-        .route("/health", get(health_handler))
-        .route("/query", post(query_handler))
-        .with_state(state)
+/// `/admin/stats` response: detailed counters the public API doesn't
+/// expose.
+#[derive(Debug, Serialize)]
+pub struct AdminStatsResponse {
+    pub entry_count: u64,
+    pub memory_bytes: usize,
+    pub source_path: String,
+    pub loaded_at_unix_secs: u64,
 }
 
-async fn health_handler(
-    State(state): State<Arc<AppState>>,
-) -> Json<HealthResponse> {
+/// `/admin/config` response: the active parameters for this lane.
+#[derive(Debug, Serialize)]
+pub struct AdminConfigResponse {
+    pub modules: EnabledModules,
+    pub max_concurrent_queries: usize,
+}
+
+async fn health_handler(State(state): State<Arc<AppState>>) -> Json<HealthResponse> {
     Json(HealthResponse {
         status: "ok".to_string(),
-        entry_count: state.responder.entry_count(),
+        hot_entry_count: state
+            .hot
+            .as_ref()
+            .map(|lane| lane.responder.load().entry_count()),
+        cold_entry_count: state
+            .cold
+            .as_ref()
+            .map(|lane| lane.responder.load().entry_count()),
     })
 }
 
+/// Render the metrics registry in Prometheus text format.
+async fn metrics_handler(State(state): State<Arc<AppState>>) -> String {
+    state.metrics_handle.render()
+}
+
+/// Serve the lane's full mmap'd database as a raw byte stream, for edge
+/// nodes that want to sync the whole database without issuing PIR queries.
+async fn raw_handler(State(lane): State<Arc<LaneState>>) -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "application/octet-stream")],
+        lane.responder.load().raw_bytes().to_vec(),
+    )
+}
+
+/// A flat PIR database has no bucket/stem structure to diff against, so
+/// there's no incremental delta to serve - this module exists so operators
+/// can probe for delta support and get an honest "not supported" rather
+/// than a silent 404, consistent with the inspire-* family's
+/// `/index/deltas` endpoint which this one deliberately does not try to
+/// imitate.
+async fn deltas_handler() -> ServerError {
+    ServerError::DeltasUnsupported
+}
+
+/// Atomically swap the lane's responder for one backed by the snapshot at
+/// `path`, via `ArcSwap::store` - in-flight queries hold their own `Arc` to
+/// the old responder (from `ArcSwap::load`) and run to completion against
+/// it unaffected.
+async fn admin_reload_handler(
+    State(lane): State<Arc<LaneState>>,
+    Json(request): Json<ReloadRequest>,
+) -> Result<Json<ReloadResponse>, ServerError> {
+    let file = std::fs::File::open(&request.path)?;
+    let mmap = Arc::new(unsafe { memmap2::Mmap::map(&file)? });
+    let responder = Responder::new(mmap)?;
+    let entry_count = responder.entry_count();
+
+    lane.responder.store(Arc::new(responder));
+    *lane.meta.lock().unwrap() = LaneMeta {
+        source_path: request.path.clone(),
+        loaded_at: SystemTime::now(),
+    };
+
+    gauge!("pir_server_entry_count", "lane" => lane.lane_label).set(entry_count as f64);
+
+    tracing::info!(path = %request.path, entry_count, "Reloaded lane snapshot via /admin/reload");
+
+    Ok(Json(ReloadResponse {
+        entry_count,
+        source_path: request.path,
+    }))
+}
+
+/// Detailed counters beyond what `/health` exposes: entry count, the
+/// mmap'd snapshot's memory footprint, and when/where it was loaded from.
+async fn admin_stats_handler(State(lane): State<Arc<LaneState>>) -> Json<AdminStatsResponse> {
+    let responder = lane.responder.load();
+    let meta = lane.meta.lock().unwrap();
+
+    Json(AdminStatsResponse {
+        entry_count: responder.entry_count(),
+        memory_bytes: responder.raw_bytes().len(),
+        source_path: meta.source_path.clone(),
+        loaded_at_unix_secs: meta
+            .loaded_at
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0),
+    })
+}
+
+/// Echo the active parameters for this lane, for an operator to confirm
+/// what's actually running without cross-referencing the launch command.
+async fn admin_config_handler(State(lane): State<Arc<LaneState>>) -> Json<AdminConfigResponse> {
+    Json(AdminConfigResponse {
+        modules: lane.modules,
+        max_concurrent_queries: lane.max_concurrent_queries,
+    })
+}
+
+/// Tracks in-flight query requests via `pir_server_queries_in_flight`,
+/// decrementing on drop so a handler that returns early through `?` still
+/// leaves the gauge accurate.
+struct InFlightGuard;
+
+impl InFlightGuard {
+    fn start() -> Self {
+        gauge!("pir_server_queries_in_flight").increment(1.0);
+        Self
+    }
+}
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        gauge!("pir_server_queries_in_flight").decrement(1.0);
+    }
+}
+
+/// Whether the request body is raw `bincode`-encoded bytes rather than
+/// JSON, per `Content-Type: application/octet-stream`.
+fn is_binary_transport(headers: &HeaderMap) -> bool {
+    headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v == "application/octet-stream")
+}
+
+/// Accepts either JSON (`QueryRequest`, for debugging/compat) or, when
+/// `Content-Type: application/octet-stream`, a `CompressedQuery` encoded
+/// directly as `bincode` bytes - skipping the JSON and hex-encoding
+/// overhead on the hot path. The binary response carries the raw 32-byte
+/// result as its body with the timing moved into `X-Query-Time-Ms`, since
+/// there's no envelope left to put it in.
 async fn query_handler(
-    State(state): State<Arc<AppState>>,
-    Json(request): Json<QueryRequest>,
-) -> Result<Json<QueryResponse>, StatusCode> {
+    State(lane): State<Arc<LaneState>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Response, ServerError> {
+    let _in_flight = InFlightGuard::start();
+    let binary = is_binary_transport(&headers);
+
+    let query: CompressedQuery = if binary {
+        bincode::deserialize(&body)
+            .map_err(|e| ServerError::MalformedBody(format!("bincode decode failed: {e}")))?
+    } else {
+        serde_json::from_slice::<QueryRequest>(&body)
+            .map_err(|e| ServerError::MalformedBody(format!("JSON decode failed: {e}")))?
+            .query
+    };
+
     let start = std::time::Instant::now();
-    
-    let result = state.responder.respond(&request.query);
-    
+    let result = lane.responder.load().respond(&query);
     let elapsed = start.elapsed();
-    
-    Ok(Json(QueryResponse {
-        result: hex::encode(result),
-        query_time_ms: elapsed.as_secs_f64() * 1000.0,
+    histogram!("pir_server_query_duration_seconds").record(elapsed.as_secs_f64());
+
+    let result = match result {
+        Ok(result) => {
+            counter!("pir_server_queries_total").increment(1);
+            result
+        }
+        Err(e) => {
+            counter!("pir_server_queries_failed_total").increment(1);
+            return Err(e);
+        }
+    };
+
+    let query_time_ms = elapsed.as_secs_f64() * 1000.0;
+    if binary {
+        Ok((
+            [(
+                header::HeaderName::from_static("x-query-time-ms"),
+                query_time_ms.to_string(),
+            )],
+            result.to_vec(),
+        )
+            .into_response())
+    } else {
+        Ok(Json(QueryResponse {
+            result: hex::encode(result),
+            query_time_ms,
+        })
+            .into_response())
+    }
+}
+
+/// Evaluate every query in the batch concurrently on the blocking thread
+/// pool, bounded by `lane.query_semaphore` so one batch can't starve other
+/// requests sharing the same lane. Permits are acquired up front (in input
+/// order) before each query's blocking task is spawned, which both bounds
+/// concurrency and lets all permitted queries run in parallel instead of one
+/// at a time.
+async fn query_batch_handler(
+    State(lane): State<Arc<LaneState>>,
+    Json(request): Json<BatchQueryRequest>,
+) -> Result<Json<BatchQueryResponse>, ServerError> {
+    let _in_flight = InFlightGuard::start();
+    let batch_start = std::time::Instant::now();
+    let query_count = request.queries.len() as u64;
+
+    let mut tasks = Vec::with_capacity(request.queries.len());
+    for query in request.queries {
+        let lane = lane.clone();
+        let permit = lane
+            .query_semaphore
+            .clone()
+            .acquire_owned()
+            .await
+            .expect("query semaphore is never closed");
+        tasks.push(tokio::task::spawn_blocking(move || {
+            let _permit = permit;
+            let start = std::time::Instant::now();
+            let result = lane.responder.load().respond(&query);
+            (result, start.elapsed().as_secs_f64() * 1000.0)
+        }));
+    }
+
+    let mut results = Vec::with_capacity(tasks.len());
+    let mut first_error = None;
+    let mut failed_count = 0u64;
+    for task in tasks {
+        let (result, query_time_ms) = task.await.expect("query task panicked");
+        histogram!("pir_server_query_duration_seconds").record(query_time_ms / 1000.0);
+        match result {
+            Ok(hint) => results.push(QueryResponse {
+                result: hex::encode(hint),
+                query_time_ms,
+            }),
+            Err(e) => {
+                failed_count += 1;
+                if first_error.is_none() {
+                    first_error = Some(e);
+                }
+            }
+        }
+    }
+
+    let total_time_ms = batch_start.elapsed().as_secs_f64() * 1000.0;
+
+    if let Some(e) = first_error {
+        counter!("pir_server_queries_failed_total").increment(failed_count);
+        return Err(e);
+    }
+    counter!("pir_server_queries_total").increment(query_count);
+
+    Ok(Json(BatchQueryResponse {
+        results,
+        total_time_ms,
     }))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_responder() -> Responder {
+        use std::io::Write;
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(&[1u8; pir_core::ENTRY_SIZE]).unwrap();
+        file.flush().unwrap();
+        let mmap = Arc::new(unsafe { memmap2::Mmap::map(file.as_file()).unwrap() });
+        Responder::new(mmap).unwrap()
+    }
+
+    /// `init_prometheus_recorder` installs a process-global recorder and
+    /// panics if called twice, so tests share one handle instead of each
+    /// installing their own.
+    fn shared_metrics_handle() -> PrometheusHandle {
+        static HANDLE: std::sync::OnceLock<PrometheusHandle> = std::sync::OnceLock::new();
+        HANDLE
+            .get_or_init(crate::metrics::init_prometheus_recorder)
+            .clone()
+    }
+
+    #[test]
+    fn test_build_rejects_no_lanes() {
+        let err = ServerBuilder::new()
+            .with_metrics_handle(shared_metrics_handle())
+            .build()
+            .unwrap_err();
+        assert!(matches!(err, ServerError::InvalidConfig(_)));
+    }
+
+    #[test]
+    fn test_build_rejects_no_metrics_handle() {
+        let err = ServerBuilder::new()
+            .with_hot_lane(make_responder(), "test-hot.bin")
+            .build()
+            .unwrap_err();
+        assert!(matches!(err, ServerError::InvalidConfig(_)));
+    }
+
+    #[test]
+    fn test_build_succeeds_with_one_lane_and_metrics() {
+        let (state, _router) = ServerBuilder::new()
+            .with_cold_lane(make_responder(), "test-cold.bin")
+            .with_metrics_handle(shared_metrics_handle())
+            .build()
+            .unwrap();
+        assert!(state.hot.is_none());
+        assert!(state.cold.is_some());
+    }
+
+    #[test]
+    fn test_admin_reload_swaps_responder_and_updates_meta() {
+        let (state, _router) = ServerBuilder::new()
+            .with_cold_lane(make_responder(), "original.bin")
+            .with_metrics_handle(shared_metrics_handle())
+            .build()
+            .unwrap();
+        let lane = state.cold.as_ref().unwrap().clone();
+        assert_eq!(lane.meta.lock().unwrap().source_path, "original.bin");
+        assert_eq!(lane.responder.load().entry_count(), 1);
+
+        // A second snapshot with more entries, swapped in via the same
+        // path ArcSwap::store takes in admin_reload_handler.
+        use std::io::Write;
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(&[2u8; pir_core::ENTRY_SIZE * 3]).unwrap();
+        file.flush().unwrap();
+        let mmap = Arc::new(unsafe { memmap2::Mmap::map(file.as_file()).unwrap() });
+        let new_responder = Responder::new(mmap).unwrap();
+
+        lane.responder.store(Arc::new(new_responder));
+        *lane.meta.lock().unwrap() = LaneMeta {
+            source_path: "replacement.bin".to_string(),
+            loaded_at: SystemTime::now(),
+        };
+
+        assert_eq!(lane.responder.load().entry_count(), 3);
+        assert_eq!(lane.meta.lock().unwrap().source_path, "replacement.bin");
+    }
+
+    #[test]
+    fn test_is_binary_transport_detects_octet_stream() {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::CONTENT_TYPE, "application/octet-stream".parse().unwrap());
+        assert!(is_binary_transport(&headers));
+    }
+
+    #[test]
+    fn test_is_binary_transport_false_for_json_or_missing() {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::CONTENT_TYPE, "application/json".parse().unwrap());
+        assert!(!is_binary_transport(&headers));
+        assert!(!is_binary_transport(&HeaderMap::new()));
+    }
+}
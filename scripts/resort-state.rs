@@ -3,23 +3,40 @@
 //!
 //! Usage: rust-script resort-state.rs input.bin output.bin
 //!
+//! Does an external k-way merge sort rather than loading the whole database
+//! into a `Vec`, so it can re-sort a full-state database (billions of
+//! entries) on a machine with modest memory. The actual sort - chunking,
+//! spilling sorted runs to disk, and the bounded-width multi-pass merge -
+//! lives in `inspire_core::external_sort::external_merge_sort`; this script
+//! just reads the PIR2 header and supplies the entry layout and stem-key
+//! derivation.
+//!
 //! ```cargo
 //! [dependencies]
 //! blake3 = "1.5"
+//! tempfile = "3"
+//! inspire-core = { path = "../crates/inspire-core" }
 //! ```
 
 use std::env;
 use std::fs::File;
-use std::io::{BufReader, BufWriter, Read, Write};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+
+use inspire_core::external_sort::external_merge_sort;
 
 const HEADER_SIZE: usize = 64;
 const ENTRY_SIZE: usize = 84;
 
+/// Entries sorted per in-memory chunk before being spilled to a run file
+/// (each entry is held with its 32-byte sort key: 116+ bytes/entry -> over
+/// 116 MB resident per chunk).
+const CHUNK_ENTRIES: usize = 1_000_000;
+
 fn compute_stem_key(address: &[u8], slot: &[u8]) -> [u8; 32] {
     let mut input = [0u8; 63];
     input[12..32].copy_from_slice(address);
     input[32..63].copy_from_slice(&slot[..31]);
-    
+
     let hash = blake3::hash(&input);
     let mut key = [0u8; 32];
     key[..31].copy_from_slice(&hash.as_bytes()[..31]);
@@ -27,7 +44,7 @@ fn compute_stem_key(address: &[u8], slot: &[u8]) -> [u8; 32] {
     key
 }
 
-fn main() -> std::io::Result<()> {
+fn main() -> io::Result<()> {
     let args: Vec<String> = env::args().collect();
     if args.len() != 3 {
         eprintln!("Usage: {} <input.bin> <output.bin>", args[0]);
@@ -39,62 +56,40 @@ fn main() -> std::io::Result<()> {
 
     eprintln!("Reading {}...", input_path);
     let mut file = BufReader::new(File::open(input_path)?);
-    
-    // Read header
+
     let mut header = [0u8; HEADER_SIZE];
     file.read_exact(&mut header)?;
-    
-    // Verify magic
+
     if &header[0..4] != b"PIR2" {
         eprintln!("Error: Not a PIR2 file");
         std::process::exit(1);
     }
-    
+
     let entry_count = u64::from_le_bytes(header[8..16].try_into().unwrap());
     eprintln!("Entry count: {}", entry_count);
-    
-    // Read all entries
-    struct SortableEntry {
-        sort_key: [u8; 32],
-        data: [u8; ENTRY_SIZE],
-    }
-    
-    let mut entries = Vec::with_capacity(entry_count as usize);
-    let mut buf = [0u8; ENTRY_SIZE];
-    
-    for i in 0..entry_count {
-        file.read_exact(&mut buf)?;
-        
-        let address = &buf[0..20];
-        let slot = &buf[20..52];
-        let sort_key = compute_stem_key(address, slot);
-        
-        let mut data = [0u8; ENTRY_SIZE];
-        data.copy_from_slice(&buf);
-        
-        entries.push(SortableEntry { sort_key, data });
-        
-        if (i + 1) % 1_000_000 == 0 {
-            eprintln!("Read {} entries...", i + 1);
-        }
-    }
-    
-    eprintln!("Sorting by stem key...");
-    entries.sort_unstable_by(|a, b| a.sort_key.cmp(&b.sort_key));
-    
-    eprintln!("Writing {}...", output_path);
+
+    eprintln!("Sorting by stem key (external merge sort, chunks of {})...", CHUNK_ENTRIES);
     let mut out = BufWriter::new(File::create(output_path)?);
     out.write_all(&header)?;
-    
-    for (i, entry) in entries.iter().enumerate() {
-        out.write_all(&entry.data)?;
-        if (i + 1) % 1_000_000 == 0 {
-            eprintln!("Written {} entries...", i + 1);
-        }
+
+    let written = external_merge_sort(
+        &mut file,
+        &mut out,
+        entry_count,
+        ENTRY_SIZE,
+        CHUNK_ENTRIES,
+        |entry| compute_stem_key(&entry[0..20], &entry[20..52]),
+    )
+    .map_err(|e| io::Error::new(io::ErrorKind::Other, e.to_string()))?;
+
+    if written != entry_count {
+        eprintln!(
+            "Error: wrote {} entries, expected {} from the header",
+            written, entry_count
+        );
+        std::process::exit(1);
     }
-    
-    out.flush()?;
-    eprintln!("Done!");
-    
+
+    eprintln!("Done! Wrote {} entries.", written);
     Ok(())
 }